@@ -0,0 +1,515 @@
+//! The cache module keeps the state received from the gateway, so that it can be looked up
+//! without requesting it from the API.
+
+use std::{collections::HashMap, fmt::Display};
+
+use bitflags::bitflags;
+
+use crate::model::{
+    channel::Channel,
+    gateway::{dispatch::DispatchEvent, intents::GatewayIntents, presence::Presence},
+    guild::{member::GuildMember, Guild},
+    snowflake::Snowflake,
+};
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    /// Which data a [`Cache`] keeps. Data is only received with the right intents, see
+    /// [`CacheSettings::recommended_for`].
+    pub struct CacheSettings: u8 {
+        /// Guilds, which require [`GatewayIntents::GUILDS`]
+        const GUILDS = 1 << 0;
+        /// Channels, which require [`GatewayIntents::GUILDS`]
+        const CHANNELS = 1 << 1;
+        /// Guild members, which require [`GatewayIntents::GUILD_MEMBERS`] for the member
+        /// list to be complete
+        const MEMBERS = 1 << 2;
+        /// Presences, which require [`GatewayIntents::GUILD_PRESENCES`]
+        const PRESENCES = 1 << 3;
+    }
+}
+
+impl Default for CacheSettings {
+    /// Caches everything.
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl CacheSettings {
+    /// The default settings, without caching data that won't be received with `intents`.
+    #[must_use]
+    pub fn recommended_for(intents: GatewayIntents) -> Self {
+        Self::default().difference(Self::unreceivable(intents))
+    }
+
+    /// The intents that are needed to receive the data cached with these settings, but
+    /// are missing from `intents`. Caching that data is pointless or incomplete.
+    #[must_use]
+    pub fn missing_intents(&self, intents: GatewayIntents) -> GatewayIntents {
+        self.intent_warnings(intents)
+            .iter()
+            .fold(GatewayIntents::empty(), |missing, warning| {
+                missing | warning.missing
+            })
+    }
+
+    /// A warning for each cache enabled by these settings that can't be filled, or only
+    /// partially, with `intents`.
+    #[must_use]
+    pub fn intent_warnings(&self, intents: GatewayIntents) -> Vec<CacheIntentWarning> {
+        self.intersection(Self::unreceivable(intents))
+            .iter()
+            .map(|cache| CacheIntentWarning {
+                cache,
+                missing: Self::required_intent(cache),
+            })
+            .collect()
+    }
+
+    /// The intent needed to receive the data of a single cache
+    fn required_intent(cache: CacheSettings) -> GatewayIntents {
+        if cache == Self::MEMBERS {
+            GatewayIntents::GUILD_MEMBERS
+        } else if cache == Self::PRESENCES {
+            GatewayIntents::GUILD_PRESENCES
+        } else {
+            GatewayIntents::GUILDS
+        }
+    }
+
+    /// The data that isn't received, or is incomplete, with `intents`
+    fn unreceivable(intents: GatewayIntents) -> Self {
+        let mut unreceivable = Self::empty();
+        if !intents.contains(GatewayIntents::GUILDS) {
+            unreceivable |= Self::GUILDS | Self::CHANNELS;
+        }
+        if !intents.contains(GatewayIntents::GUILD_MEMBERS) {
+            unreceivable |= Self::MEMBERS;
+        }
+        if !intents.contains(GatewayIntents::GUILD_PRESENCES) {
+            unreceivable |= Self::PRESENCES;
+        }
+        unreceivable
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A cache that is enabled, but can't be filled, or only partially, with the intents
+/// that are requested. See [`CacheSettings::intent_warnings`].
+pub struct CacheIntentWarning {
+    /// The cache that is affected
+    pub cache: CacheSettings,
+    /// The intent that is needed to fill the cache
+    pub missing: GatewayIntents,
+}
+
+impl Display for CacheIntentWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let consequence = if self.cache == CacheSettings::MEMBERS {
+            "member events are not received, so only members sent with guilds are cached"
+        } else {
+            "the data is not received, so the cache stays empty"
+        };
+        write!(
+            f,
+            "caching {:?} requires the {:?} intent, which is not requested: {consequence}",
+            self.cache, self.missing
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// The guilds, channels, members and presences seen by a shard, updated from its dispatch
+/// events.
+pub struct Cache {
+    settings: CacheSettings,
+    guilds: HashMap<Snowflake, Guild>,
+    channels: HashMap<Snowflake, Channel>,
+    /// The members of each guild by their user ID
+    members: HashMap<Snowflake, HashMap<Snowflake, GuildMember>>,
+    /// The presences of each guild by their user ID
+    presences: HashMap<Snowflake, HashMap<Snowflake, Presence>>,
+}
+
+impl Cache {
+    /// Creates an empty cache with the default [`CacheSettings`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty cache that only keeps the data enabled by `settings`, see
+    /// [`CacheSettings::recommended_for`].
+    #[must_use]
+    pub fn with_settings(settings: CacheSettings) -> Self {
+        Self {
+            settings,
+            ..Self::default()
+        }
+    }
+
+    /// Which data this cache keeps
+    #[must_use]
+    pub fn settings(&self) -> CacheSettings {
+        self.settings
+    }
+
+    /// Updates the cache with a dispatch event.
+    pub fn update(&mut self, event: &DispatchEvent) {
+        match event {
+            DispatchEvent::GuildCreate(guild_create) => {
+                let mut guild = guild_create.0.clone();
+                let guild_id = guild.id;
+                // Channels and presences sent as part of a guild omit the ID of the guild
+                // they're in
+                let channels = std::mem::take(&mut guild.channels);
+                if self.settings.contains(CacheSettings::CHANNELS) {
+                    for mut channel in channels {
+                        channel.guild_id = channel.guild_id.or(Some(guild_id));
+                        self.channels.insert(channel.id, channel);
+                    }
+                }
+                let members = std::mem::take(&mut guild.members);
+                self.insert_members(guild_id, members);
+                let presences = std::mem::take(&mut guild.presences);
+                self.insert_presences(guild_id, presences);
+                if self.settings.contains(CacheSettings::GUILDS) {
+                    self.guilds.insert(guild.id, guild);
+                }
+            }
+            DispatchEvent::GuildUpdate(guild_update) => {
+                if let Some(cached) = self.guilds.get_mut(&guild_update.0.id) {
+                    // These fields are only sent with `GUILD_CREATE`
+                    *cached = Guild {
+                        member_count: cached.member_count,
+                        joined_at: cached.joined_at.take(),
+                        large: cached.large,
+                        unavailable: cached.unavailable,
+                        ..guild_update.0.clone()
+                    };
+                }
+            }
+            DispatchEvent::GuildDelete(guild_delete) if !guild_delete.is_outage() => {
+                self.guilds.remove(&guild_delete.0.id);
+                let guild_id = guild_delete.0.id;
+                self.channels
+                    .retain(|_, channel| channel.guild_id != Some(guild_id));
+                self.members.remove(&guild_id);
+                self.presences.remove(&guild_id);
+            }
+            DispatchEvent::GuildMemberAdd(event) | DispatchEvent::GuildMemberUpdate(event) => {
+                self.insert_members(event.guild_id, [event.member.clone()]);
+            }
+            DispatchEvent::GuildMemberRemove { guild_id, user } => {
+                if let Some(members) = self.members.get_mut(guild_id) {
+                    members.remove(&user.id);
+                }
+            }
+            DispatchEvent::GuildMembersChunk(chunk) => {
+                self.insert_members(chunk.guild_id, chunk.members.iter().cloned());
+                let presences = chunk.presences.iter().flatten().cloned();
+                self.insert_presences(chunk.guild_id, presences);
+            }
+            DispatchEvent::PresenceUpdate(presence) => {
+                if let Some(guild_id) = presence.guild_id {
+                    self.insert_presences(guild_id, [(**presence).clone()]);
+                }
+            }
+            DispatchEvent::ChannelCreate(channel) | DispatchEvent::ChannelUpdate(channel)
+                if self.settings.contains(CacheSettings::CHANNELS) =>
+            {
+                self.channels.insert(channel.id, (**channel).clone());
+            }
+            DispatchEvent::ChannelDelete(channel) => {
+                self.channels.remove(&channel.id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Caches members of a guild, if members are cached. Members without a user can't be
+    /// told apart, so they are skipped.
+    fn insert_members(
+        &mut self,
+        guild_id: Snowflake,
+        members: impl IntoIterator<Item = GuildMember>,
+    ) {
+        if !self.settings.contains(CacheSettings::MEMBERS) {
+            return;
+        }
+        let cached = self.members.entry(guild_id).or_default();
+        for member in members {
+            if let Some(user_id) = member.user.as_ref().map(|user| user.id) {
+                cached.insert(user_id, member);
+            }
+        }
+    }
+
+    /// Caches presences in a guild, if presences are cached
+    fn insert_presences(
+        &mut self,
+        guild_id: Snowflake,
+        presences: impl IntoIterator<Item = Presence>,
+    ) {
+        if !self.settings.contains(CacheSettings::PRESENCES) {
+            return;
+        }
+        let cached = self.presences.entry(guild_id).or_default();
+        for mut presence in presences {
+            presence.guild_id = Some(guild_id);
+            cached.insert(presence.user.id, presence);
+        }
+    }
+
+    /// The cached guild with this ID. Its channels, members and presences are cached
+    /// separately, see [`Cache::guild_channels`], [`Cache::guild_members`] and
+    /// [`Cache::presence`].
+    #[must_use]
+    pub fn guild(&self, id: Snowflake) -> Option<&Guild> {
+        self.guilds.get(&id)
+    }
+
+    /// The number of cached guilds
+    #[must_use]
+    pub fn guild_count(&self) -> usize {
+        self.guilds.len()
+    }
+
+    /// The total number of members in the cached guilds, as sent with `GUILD_CREATE`
+    #[must_use]
+    pub fn member_count(&self) -> u64 {
+        self.guilds
+            .values()
+            .filter_map(|guild| guild.member_count)
+            .sum()
+    }
+
+    /// The cached channel with this ID
+    #[must_use]
+    pub fn channel(&self, id: Snowflake) -> Option<&Channel> {
+        self.channels.get(&id)
+    }
+
+    /// The cached channels in a guild
+    pub fn guild_channels(&self, guild_id: Snowflake) -> impl Iterator<Item = &Channel> {
+        self.channels
+            .values()
+            .filter(move |channel| channel.guild_id == Some(guild_id))
+    }
+
+    /// The cached member of a guild with this user ID
+    #[must_use]
+    pub fn member(&self, guild_id: Snowflake, user_id: Snowflake) -> Option<&GuildMember> {
+        self.members.get(&guild_id)?.get(&user_id)
+    }
+
+    /// The cached members of a guild. Without [`GatewayIntents::GUILD_MEMBERS`], these
+    /// are only the members sent with the guild and those who were requested.
+    pub fn guild_members(&self, guild_id: Snowflake) -> impl Iterator<Item = &GuildMember> {
+        self.members
+            .get(&guild_id)
+            .into_iter()
+            .flat_map(HashMap::values)
+    }
+
+    /// The cached presence of a user in a guild
+    #[must_use]
+    pub fn presence(&self, guild_id: Snowflake, user_id: Snowflake) -> Option<&Presence> {
+        self.presences.get(&guild_id)?.get(&user_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Cache, CacheIntentWarning, CacheSettings};
+    use crate::gateway::mock::user_payload;
+    use crate::model::{
+        gateway::{dispatch::DispatchEvent, intents::GatewayIntents, presence::StatusType},
+        snowflake::Snowflake,
+    };
+
+    #[test]
+    fn recommendation_follows_intents() {
+        let settings = CacheSettings::recommended_for(GatewayIntents::non_privileged());
+        assert!(settings.contains(CacheSettings::GUILDS | CacheSettings::CHANNELS));
+        assert!(!settings.contains(CacheSettings::PRESENCES));
+        assert!(!settings.contains(CacheSettings::MEMBERS));
+        assert!(settings
+            .missing_intents(GatewayIntents::non_privileged())
+            .is_empty());
+
+        let settings = CacheSettings::recommended_for(GatewayIntents::all());
+        assert!(settings.contains(CacheSettings::PRESENCES | CacheSettings::MEMBERS));
+    }
+
+    #[test]
+    fn missing_intents_for_enabled_caches() {
+        let settings = CacheSettings::default();
+        assert_eq!(
+            settings.missing_intents(GatewayIntents::GUILDS),
+            GatewayIntents::GUILD_MEMBERS | GatewayIntents::GUILD_PRESENCES
+        );
+    }
+
+    #[test]
+    fn member_cache_warns_without_guild_members() {
+        let settings = CacheSettings::GUILDS | CacheSettings::MEMBERS;
+        let warnings = settings.intent_warnings(GatewayIntents::GUILDS);
+        assert_eq!(
+            warnings,
+            [CacheIntentWarning {
+                cache: CacheSettings::MEMBERS,
+                missing: GatewayIntents::GUILD_MEMBERS,
+            }]
+        );
+        assert!(warnings[0].to_string().contains("GUILD_MEMBERS"));
+        assert!(settings
+            .intent_warnings(GatewayIntents::GUILDS | GatewayIntents::GUILD_MEMBERS)
+            .is_empty());
+    }
+
+    #[test]
+    fn guild_create_backfills_channel_guild_ids() {
+        let guild_create: DispatchEvent = serde_json::from_value(json!({
+            "t": "GUILD_CREATE",
+            "d": {
+                "id": "41771983423143937",
+                "name": "Guild",
+                "owner_id": "80351110224678912",
+                "premium_tier": 0,
+                "channels": [
+                    { "id": "41771983423143938", "type": 0, "name": "general" },
+                    { "id": "41771983423143939", "type": 2, "name": "voice" },
+                ],
+            },
+        }))
+        .unwrap();
+
+        let mut cache = Cache::new();
+        cache.update(&guild_create);
+
+        let guild_id = Snowflake(41_771_983_423_143_937);
+        let channel = cache.channel(Snowflake(41_771_983_423_143_938)).unwrap();
+        assert_eq!(channel.guild_id, Some(guild_id));
+        assert_eq!(cache.guild_channels(guild_id).count(), 2);
+        assert!(cache
+            .guild(Snowflake(41_771_983_423_143_937))
+            .unwrap()
+            .channels
+            .is_empty());
+    }
+
+    fn guild_create_with_member() -> DispatchEvent {
+        serde_json::from_value(json!({
+            "t": "GUILD_CREATE",
+            "d": {
+                "id": "41771983423143937",
+                "name": "Guild",
+                "owner_id": "80351110224678912",
+                "premium_tier": 0,
+                "members": [{
+                    "user": user_payload(),
+                    "roles": [],
+                    "joined_at": "2016-06-22T19:20:40.123000+00:00",
+                    "deaf": false,
+                    "mute": false,
+                }],
+                "presences": [{ "user": { "id": "80351110224678912" }, "status": "idle" }],
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn members_and_presences_are_cached() {
+        let guild_id = Snowflake(41_771_983_423_143_937);
+        let user_id = Snowflake(80_351_110_224_678_912);
+        let mut cache = Cache::new();
+        cache.update(&guild_create_with_member());
+
+        assert_eq!(cache.guild_members(guild_id).count(), 1);
+        assert!(cache.guild(guild_id).unwrap().members.is_empty());
+        // Presences sent with a guild are given its ID
+        let presence = cache.presence(guild_id, user_id).unwrap();
+        assert_eq!(presence.status, StatusType::Idle);
+        assert_eq!(presence.guild_id, Some(guild_id));
+
+        cache.update(
+            &serde_json::from_value(json!({
+                "t": "PRESENCE_UPDATE",
+                "d": {
+                    "user": { "id": "80351110224678912" },
+                    "guild_id": "41771983423143937",
+                    "status": "online",
+                },
+            }))
+            .unwrap(),
+        );
+        assert_eq!(
+            cache.presence(guild_id, user_id).unwrap().status,
+            StatusType::Online
+        );
+
+        cache.update(
+            &serde_json::from_value(json!({
+                "t": "GUILD_MEMBER_REMOVE",
+                "d": {
+                    "guild_id": "41771983423143937",
+                    "user": user_payload(),
+                },
+            }))
+            .unwrap(),
+        );
+        assert!(cache.member(guild_id, user_id).is_none());
+    }
+
+    #[test]
+    fn disabled_caches_stay_empty() {
+        let guild_id = Snowflake(41_771_983_423_143_937);
+        let user_id = Snowflake(80_351_110_224_678_912);
+        let mut cache = Cache::with_settings(CacheSettings::GUILDS);
+        cache.update(&guild_create_with_member());
+
+        assert_eq!(cache.guild_count(), 1);
+        assert!(cache.member(guild_id, user_id).is_none());
+        assert!(cache.presence(guild_id, user_id).is_none());
+    }
+
+    #[test]
+    fn guild_removal_clears_guild_and_channels() {
+        let mut cache = Cache::new();
+        cache.update(
+            &serde_json::from_value(json!({
+                "t": "GUILD_CREATE",
+                "d": {
+                    "id": "41771983423143937",
+                    "name": "Guild",
+                    "owner_id": "80351110224678912",
+                    "premium_tier": 0,
+                    "channels": [{ "id": "41771983423143938", "type": 0, "name": "general" }],
+                },
+            }))
+            .unwrap(),
+        );
+
+        let outage = serde_json::from_value(json!({
+            "t": "GUILD_DELETE",
+            "d": { "id": "41771983423143937", "unavailable": true },
+        }))
+        .unwrap();
+        cache.update(&outage);
+        assert_eq!(cache.guild_count(), 1);
+
+        let removal = serde_json::from_value(json!({
+            "t": "GUILD_DELETE",
+            "d": { "id": "41771983423143937" },
+        }))
+        .unwrap();
+        cache.update(&removal);
+        assert_eq!(cache.guild_count(), 0);
+        assert!(cache.channel(Snowflake(41_771_983_423_143_938)).is_none());
+    }
+}
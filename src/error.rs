@@ -1,5 +1,7 @@
 use std::fmt::Display;
 
+use tokio_tungstenite::tungstenite::{error::ProtocolError, Error as WebsocketError};
+
 use crate::gateway;
 
 #[derive(Debug)]
@@ -10,6 +12,32 @@ pub enum Error {
     Io(std::io::Error)
 }
 
+impl Error {
+    /// Whether retrying the operation that failed, such as by reconnecting, may succeed.
+    ///
+    /// - I/O errors, and websocket errors caused by the connection, are recoverable
+    /// - Gateway errors are classified by [`gateway::error::Error::is_recoverable`]
+    /// - JSON errors are not recoverable, as the same payload will fail again
+    /// - Other websocket errors, such as an invalid URL or a rejected handshake, are not
+    ///   recoverable, except for server errors during the handshake
+    #[must_use]
+    pub fn is_recoverable(&self) -> bool {
+        match self {
+            Error::Json(_) => false,
+            Error::Websocket(e) => match e {
+                WebsocketError::Io(_)
+                | WebsocketError::ConnectionClosed
+                | WebsocketError::AlreadyClosed
+                | WebsocketError::Protocol(ProtocolError::ResetWithoutClosingHandshake) => true,
+                WebsocketError::Http(response) => response.status().is_server_error(),
+                _ => false,
+            },
+            Error::Gateway(e) => e.is_recoverable(),
+            Error::Io(_) => true,
+        }
+    }
+}
+
 impl From<serde_json::Error> for Error {
     fn from(e: serde_json::Error) -> Self {
         Self::Json(e)
@@ -56,4 +84,39 @@ impl std::error::Error for Error {
     }
 }
 
-pub type Result<T, E = Error> = std::result::Result<T, E>;
\ No newline at end of file
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use tokio_tungstenite::tungstenite::{error::UrlError, Error as WebsocketError};
+
+    use super::Error;
+    use crate::gateway::error::{CloseCode, Error as GatewayError, GatewayDecodeError};
+
+    #[test]
+    fn connection_errors_are_recoverable() {
+        let io = std::io::Error::from(ErrorKind::ConnectionReset);
+        assert!(Error::Io(io).is_recoverable());
+        let io = std::io::Error::from(ErrorKind::ConnectionReset);
+        assert!(Error::Websocket(WebsocketError::Io(io)).is_recoverable());
+        assert!(Error::Websocket(WebsocketError::ConnectionClosed).is_recoverable());
+        assert!(Error::Gateway(GatewayError::Closed(None)).is_recoverable());
+        let closed = GatewayError::Closed(Some(CloseCode::SessionTimedOut));
+        assert!(Error::Gateway(closed).is_recoverable());
+        let json = serde_json::from_str::<u8>("{").unwrap_err();
+        let dispatch = GatewayDecodeError::InvalidDispatch(None, Some(2), json);
+        assert!(Error::Gateway(GatewayError::Decode(dispatch)).is_recoverable());
+    }
+
+    #[test]
+    fn invalid_input_is_not_recoverable() {
+        let json = serde_json::from_str::<u8>("{").unwrap_err();
+        assert!(!Error::Json(json).is_recoverable());
+        let url = WebsocketError::Url(UrlError::NoHostName);
+        assert!(!Error::Websocket(url).is_recoverable());
+        let closed = GatewayError::Closed(Some(CloseCode::AuthenticationFailed));
+        assert!(!Error::Gateway(closed).is_recoverable());
+    }
+}
@@ -0,0 +1,303 @@
+//! The builder module configures a [`Shard`] before it connects to the gateway.
+
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    error::Result,
+    model::gateway::{
+        event::IdentifyProperties, intents::GatewayIntents, presence::PresenceUpdate,
+    },
+};
+
+use super::{
+    compression::TransportCompression,
+    encoding::Encoding,
+    proxy::ProxyConfig,
+    shard::{ConnectionStage, SessionState, Shard, ShardInformation},
+    shard_manager::GATEWAY_URL,
+    url::GatewayUrl,
+    websocket::{WebSocketConfig, WebsocketClient},
+};
+
+/// The gateway API version connected to unless another is requested
+pub const GATEWAY_VERSION: u8 = 10;
+
+#[derive(Debug, Clone, Default)]
+/// Configures a [`Shard`] before connecting it to the gateway, created with
+/// [`Shard::builder`]. Every option has a default, although the gateway rejects an
+/// identify without a valid [`ShardBuilder::token`].
+pub struct ShardBuilder {
+    url: Option<String>,
+    token: String,
+    intents: GatewayIntents,
+    shard_information: Option<ShardInformation>,
+    compression: TransportCompression,
+    encoding: Encoding,
+    large_threshold: Option<u8>,
+    presence: Option<PresenceUpdate>,
+    properties: IdentifyProperties,
+    gateway_version: Option<u8>,
+    websocket_config: Option<WebSocketConfig>,
+    /// `None` until set, when the proxy is read from the environment
+    #[allow(clippy::option_option)]
+    proxy: Option<Option<ProxyConfig>>,
+    resume_from: Option<SessionState>,
+    connection_stage_sender: Option<UnboundedSender<ConnectionStage>>,
+}
+
+impl ShardBuilder {
+    /// The gateway URL to connect to, [`GATEWAY_URL`] by default
+    #[must_use]
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = Some(url.to_string());
+        self
+    }
+
+    /// The token of the bot that the shard identifies with
+    #[must_use]
+    pub fn token(mut self, token: &str) -> Self {
+        self.token = token.to_string();
+        self
+    }
+
+    /// The intents the shard identifies with, [`GatewayIntents::non_privileged`] by default
+    #[must_use]
+    pub fn intents(mut self, intents: GatewayIntents) -> Self {
+        self.intents = intents;
+        self
+    }
+
+    /// Which shard this is. A shard identifies without shard information by default,
+    /// which receives the events of every guild.
+    #[must_use]
+    pub fn shard_info(mut self, shard_information: ShardInformation) -> Self {
+        self.shard_information = Some(shard_information);
+        self
+    }
+
+    /// How the gateway compresses the messages it sends
+    #[must_use]
+    pub fn compression(mut self, compression: TransportCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// The format payloads are encoded with
+    #[must_use]
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// The member count from which the gateway stops sending the offline members of a
+    /// guild, see [`Shard::large_threshold`]. Values outside of 50 to 250 are clamped.
+    #[must_use]
+    pub fn large_threshold(mut self, large_threshold: u8) -> Self {
+        self.large_threshold = Some(large_threshold.clamp(50, 250));
+        self
+    }
+
+    /// The presence the shard identifies with
+    #[must_use]
+    pub fn presence(mut self, presence: PresenceUpdate) -> Self {
+        self.presence = Some(presence);
+        self
+    }
+
+    /// The properties of the client the shard identifies with, see [`IdentifyProperties::new`]
+    #[must_use]
+    pub fn properties(mut self, properties: IdentifyProperties) -> Self {
+        self.properties = properties;
+        self
+    }
+
+    /// The version of the gateway to connect to, replacing the version in
+    /// [`ShardBuilder::url`]. A URL without a version connects to [`GATEWAY_VERSION`].
+    #[must_use]
+    pub fn gateway_version(mut self, version: u8) -> Self {
+        self.gateway_version = Some(version);
+        self
+    }
+
+    /// The limits and buffers of the shard's connections, see
+    /// [`WebsocketClient::default_config`]
+    #[must_use]
+    pub fn websocket_config(mut self, config: WebSocketConfig) -> Self {
+        self.websocket_config = Some(config);
+        self
+    }
+
+    /// The proxy the shard's connections are tunnelled through, where `None` connects
+    /// directly. The proxy for the gateway URL is read with [`ProxyConfig::from_env`] by
+    /// default.
+    #[must_use]
+    pub fn proxy(mut self, proxy: Option<ProxyConfig>) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// A session saved with [`Shard::session_state`] to resume rather than identifying, such
+    /// as after the process restarts. The shard identifies instead if the session can no
+    /// longer be resumed.
+    #[must_use]
+    pub fn resume_from(mut self, state: SessionState) -> Self {
+        self.resume_from = Some(state);
+        self
+    }
+
+    /// Receives the shard's [`ConnectionStage`] each time it changes, starting with
+    /// [`ConnectionStage::Connecting`] when [`ShardBuilder::build`] connects. See
+    /// [`Shard::connection_stage_sender`].
+    #[must_use]
+    pub fn connection_stage_sender(mut self, sender: UnboundedSender<ConnectionStage>) -> Self {
+        self.connection_stage_sender = Some(sender);
+        self
+    }
+
+    /// Connects the shard to the gateway. The shard identifies once the gateway says
+    /// hello, such as when run by a [`ShardManager`](super::shard_manager::ShardManager).
+    pub async fn build(self) -> Result<Shard> {
+        let gateway_url = self.gateway_url();
+        let websocket_config = self
+            .websocket_config
+            .unwrap_or_else(WebsocketClient::default_config);
+        // A restored session is resumed on its own host
+        let connect_url = match &self.resume_from {
+            Some(state) => gateway_url.with_host(&state.resume_url),
+            None => gateway_url.clone(),
+        };
+        let proxy = self
+            .proxy
+            .unwrap_or_else(|| ProxyConfig::from_env(&connect_url.to_string()));
+        if let Some(sender) = &self.connection_stage_sender {
+            let _ = sender.send(ConnectionStage::Connecting);
+        }
+        let websocket = WebsocketClient::connect_through(
+            &connect_url.to_string(),
+            connect_url.compression,
+            connect_url.encoding,
+            websocket_config,
+            proxy.as_ref(),
+        )
+        .await?;
+
+        let mut shard = Shard::with_websocket(
+            gateway_url,
+            websocket,
+            self.token,
+            self.shard_information,
+            self.intents,
+        );
+        shard.large_threshold = self.large_threshold;
+        shard.presence = self.presence;
+        shard.properties = self.properties;
+        shard.websocket_config = websocket_config;
+        shard.proxy = proxy;
+        if let Some(state) = self.resume_from {
+            shard.restore_session(state);
+        }
+        // The shard starts in the handshake, so it is sent here rather than on a change
+        if let Some(sender) = self.connection_stage_sender {
+            let _ = sender.send(shard.connection_stage());
+            shard.connection_stage_sender = Some(sender);
+        }
+        Ok(shard)
+    }
+}
+
+impl ShardBuilder {
+    /// The URL connected to
+    fn gateway_url(&self) -> GatewayUrl {
+        let url = GatewayUrl::parse(self.url.as_deref().unwrap_or(GATEWAY_URL))
+            .encoding(self.encoding)
+            .compression(self.compression);
+        match self.gateway_version {
+            Some(version) => url.version(version),
+            None => url,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GATEWAY_VERSION;
+    use crate::{
+        gateway::{mock::MockGateway, shard::Shard, websocket::WebSocketConfig},
+        model::gateway::event::IdentifyProperties,
+    };
+
+    #[tokio::test]
+    async fn large_threshold_is_sent_when_identifying() {
+        let mut gateway = MockGateway::bind().await;
+        let mut shard = Shard::builder()
+            .url(&gateway.url)
+            .token("token")
+            .proxy(None)
+            .large_threshold(100)
+            .build()
+            .await
+            .unwrap();
+
+        shard.identify().await.unwrap();
+        let identify = gateway.next_sent_json().await.unwrap();
+        assert_eq!(identify["d"]["large_threshold"], 100);
+        assert!(identify["d"].get("shard").is_none());
+    }
+
+    #[tokio::test]
+    async fn websocket_config_is_kept_by_the_shard() {
+        let gateway = MockGateway::bind().await;
+        let config = WebSocketConfig {
+            max_message_size: Some(1 << 30),
+            ..WebSocketConfig::default()
+        };
+        let shard = Shard::builder()
+            .url(&gateway.url)
+            .token("token")
+            .proxy(None)
+            .websocket_config(config)
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(shard.websocket_config.max_message_size, Some(1 << 30));
+    }
+
+    #[tokio::test]
+    async fn custom_properties_are_sent_when_identifying() {
+        let mut gateway = MockGateway::bind().await;
+        let mut shard = Shard::builder()
+            .url(&gateway.url)
+            .token("token")
+            .proxy(None)
+            .properties(IdentifyProperties::new("linux", "my-bot", "my-bot"))
+            .build()
+            .await
+            .unwrap();
+
+        shard.identify().await.unwrap();
+        let identify = gateway.next_sent_json().await.unwrap();
+        assert_eq!(identify["d"]["properties"]["os"], "linux");
+        assert_eq!(identify["d"]["properties"]["browser"], "my-bot");
+    }
+
+    #[test]
+    fn gateway_url_has_the_requested_version() {
+        let builder = Shard::builder().url("wss://gateway.discord.gg");
+        assert_eq!(
+            builder.gateway_url().to_string(),
+            format!("wss://gateway.discord.gg/?v={GATEWAY_VERSION}&encoding=json")
+        );
+        assert_eq!(
+            builder.gateway_version(9).gateway_url().to_string(),
+            "wss://gateway.discord.gg/?v=9&encoding=json"
+        );
+        assert_eq!(
+            Shard::builder()
+                .url("wss://gateway.discord.gg/?v=8&encoding=json")
+                .gateway_url()
+                .version,
+            8
+        );
+    }
+}
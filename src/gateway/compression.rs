@@ -0,0 +1,300 @@
+use std::io::{Error as IoError, ErrorKind};
+
+use flate2::{Decompress, FlushDecompress};
+
+use super::encoding::set_query_parameter;
+
+/// Every message sent with the `zlib-stream` transport ends with this suffix.
+const ZLIB_SUFFIX: [u8; 4] = [0x00, 0x00, 0xFF, 0xFF];
+
+/// The size of a zstd block header, which the decoder asks for once every block it was
+/// given has been decompressed.
+#[cfg(feature = "zstd")]
+const ZSTD_BLOCK_HEADER_SIZE: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// How the gateway compresses the messages it sends.
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway#compression)
+pub enum TransportCompression {
+    /// Messages are only compressed when requested with the `compress` field when
+    /// identifying, in which case each binary message is compressed on its own.
+    #[default]
+    Payload,
+    /// Every message is compressed with a single zlib context that is shared for the
+    /// lifetime of the connection.
+    ZlibStream,
+    /// Every message is compressed with a single zstd context that is shared for the
+    /// lifetime of the connection.
+    #[cfg(feature = "zstd")]
+    ZstdStream,
+}
+
+impl TransportCompression {
+    /// The value of the `compress` query parameter for this compression, if any
+    #[must_use]
+    pub fn query_value(&self) -> Option<&'static str> {
+        match self {
+            TransportCompression::Payload => None,
+            TransportCompression::ZlibStream => Some("zlib-stream"),
+            #[cfg(feature = "zstd")]
+            TransportCompression::ZstdStream => Some("zstd-stream"),
+        }
+    }
+
+    /// The decoder shared by every message on a connection, if this compression uses one
+    #[cfg_attr(not(feature = "zstd"), allow(clippy::unnecessary_wraps))]
+    pub(crate) fn stream_decoder(self) -> Result<Option<StreamDecoder>, IoError> {
+        Ok(match self {
+            TransportCompression::Payload => None,
+            TransportCompression::ZlibStream => Some(StreamDecoder::Zlib(ZlibStreamDecoder::new())),
+            #[cfg(feature = "zstd")]
+            TransportCompression::ZstdStream => {
+                Some(StreamDecoder::Zstd(ZstdStreamDecoder::new()?))
+            }
+        })
+    }
+
+    /// Sets the `compress` query parameter for this compression on a gateway URL,
+    /// replacing any existing value.
+    #[must_use]
+    pub fn apply_to_url(&self, url: &str) -> String {
+        match self.query_value() {
+            Some(value) => set_query_parameter(url, "compress", value),
+            None => url.to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Decompresses a transport that shares its compression context between messages.
+pub(crate) enum StreamDecoder {
+    Zlib(ZlibStreamDecoder),
+    #[cfg(feature = "zstd")]
+    Zstd(ZstdStreamDecoder),
+}
+
+impl StreamDecoder {
+    /// Adds a frame to the decoder, returning the decompressed message once it is complete.
+    pub fn push(&mut self, frame: &[u8]) -> Result<Option<Vec<u8>>, IoError> {
+        match self {
+            StreamDecoder::Zlib(decoder) => decoder.push(frame),
+            #[cfg(feature = "zstd")]
+            StreamDecoder::Zstd(decoder) => decoder.push(frame),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Decompresses the `zlib-stream` transport, which spreads messages over
+/// any number of binary frames.
+pub(crate) struct ZlibStreamDecoder {
+    decompress: Decompress,
+    buffer: Vec<u8>,
+}
+
+impl ZlibStreamDecoder {
+    pub fn new() -> Self {
+        Self {
+            decompress: Decompress::new(true),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Adds a frame to the buffer, returning the decompressed message once it is complete.
+    pub fn push(&mut self, frame: &[u8]) -> Result<Option<Vec<u8>>, IoError> {
+        self.buffer.extend_from_slice(frame);
+        if !self.buffer.ends_with(&ZLIB_SUFFIX) {
+            return Ok(None);
+        }
+
+        let mut output = Vec::with_capacity(self.buffer.len() * 3);
+        let mut input = &self.buffer[..];
+        loop {
+            if output.len() == output.capacity() {
+                output.reserve(output.capacity().max(1024));
+            }
+
+            let (total_in, total_out) = (self.decompress.total_in(), self.decompress.total_out());
+            self.decompress
+                .decompress_vec(input, &mut output, FlushDecompress::Sync)
+                .map_err(|err| IoError::new(ErrorKind::InvalidData, err))?;
+            let consumed = (self.decompress.total_in() - total_in) as usize;
+            input = &input[consumed..];
+
+            if input.is_empty() && output.len() < output.capacity() {
+                break;
+            }
+            if consumed == 0 && self.decompress.total_out() == total_out {
+                return Err(IoError::new(
+                    ErrorKind::InvalidData,
+                    "zlib-stream made no progress",
+                ));
+            }
+        }
+
+        self.buffer.clear();
+        Ok(Some(output))
+    }
+}
+
+#[cfg(feature = "zstd")]
+/// Decompresses the `zstd-stream` transport, where every message is flushed at
+/// the end of the last binary frame it is spread over.
+pub(crate) struct ZstdStreamDecoder {
+    decoder: zstd::stream::raw::Decoder<'static>,
+    /// The output of the frames received so far of an incomplete message
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "zstd")]
+impl std::fmt::Debug for ZstdStreamDecoder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZstdStreamDecoder").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl ZstdStreamDecoder {
+    pub fn new() -> Result<Self, IoError> {
+        Ok(Self {
+            decoder: zstd::stream::raw::Decoder::new()?,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Decompresses a frame, returning the decompressed message once it is complete.
+    pub fn push(&mut self, frame: &[u8]) -> Result<Option<Vec<u8>>, IoError> {
+        use zstd::stream::raw::{InBuffer, Operation, OutBuffer};
+
+        let mut input = InBuffer::around(frame);
+        self.buffer.reserve(frame.len() * 4);
+        let next_input_size = loop {
+            if self.buffer.len() == self.buffer.capacity() {
+                self.buffer.reserve(self.buffer.capacity().max(1024));
+            }
+
+            let position = self.buffer.len();
+            let mut output = OutBuffer::around_pos(&mut self.buffer, position);
+            let next_input_size = self.decoder.run(&mut input, &mut output)?;
+            let filled = output.pos() == output.capacity();
+
+            if input.pos() == frame.len() && !filled {
+                break next_input_size;
+            }
+        };
+
+        // The message was flushed once the decoder waits for the next block or frame,
+        // rather than for the rest of a block
+        if !matches!(next_input_size, 0 | ZSTD_BLOCK_HEADER_SIZE) || self.buffer.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(std::mem::take(&mut self.buffer)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::{Compress, Compression, FlushCompress};
+
+    use super::{TransportCompression, ZlibStreamDecoder};
+
+    fn compress(compress: &mut Compress, payload: &str) -> Vec<u8> {
+        let mut output = Vec::with_capacity(payload.len() + 64);
+        compress
+            .compress_vec(payload.as_bytes(), &mut output, FlushCompress::Sync)
+            .unwrap();
+        output
+    }
+
+    #[test]
+    fn decodes_messages_split_across_frames() {
+        let mut compressor = Compress::new(Compression::default(), true);
+        let mut decoder = ZlibStreamDecoder::new();
+
+        let hello = r#"{"op":10,"d":{"heartbeat_interval":41250}}"#;
+        let compressed = compress(&mut compressor, hello);
+        let (first, second) = compressed.split_at(compressed.len() - 16);
+        assert_eq!(decoder.push(first).unwrap(), None);
+        assert_eq!(decoder.push(second).unwrap().unwrap(), hello.as_bytes());
+
+        // The compression context is shared with every following message
+        let ack = r#"{"op":11,"d":null}"#;
+        let compressed = compress(&mut compressor, ack);
+        assert_eq!(decoder.push(&compressed).unwrap().unwrap(), ack.as_bytes());
+    }
+
+    #[test]
+    fn appends_query_to_url() {
+        assert_eq!(
+            TransportCompression::ZlibStream.apply_to_url("wss://gateway.discord.gg/?v=10"),
+            "wss://gateway.discord.gg/?v=10&compress=zlib-stream"
+        );
+        assert_eq!(
+            TransportCompression::ZlibStream
+                .apply_to_url("wss://gateway.discord.gg/?v=10&compress=zlib-stream"),
+            "wss://gateway.discord.gg/?v=10&compress=zlib-stream"
+        );
+        assert_eq!(
+            TransportCompression::Payload.apply_to_url("wss://gateway.discord.gg/?v=10"),
+            "wss://gateway.discord.gg/?v=10"
+        );
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn decodes_zstd_stream_messages() {
+        use std::io::Write;
+
+        use super::ZstdStreamDecoder;
+
+        let mut encoder = zstd::stream::write::Encoder::new(Vec::new(), 0).unwrap();
+        let mut decoder = ZstdStreamDecoder::new().unwrap();
+
+        for payload in [
+            r#"{"op":10,"d":{"heartbeat_interval":41250}}"#,
+            r#"{"op":11,"d":null}"#,
+        ] {
+            encoder.write_all(payload.as_bytes()).unwrap();
+            encoder.flush().unwrap();
+            let frame = std::mem::take(encoder.get_mut());
+            assert_eq!(decoder.push(&frame).unwrap().unwrap(), payload.as_bytes());
+        }
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn decodes_zstd_messages_split_across_frames() {
+        use std::io::Write;
+
+        use super::ZstdStreamDecoder;
+
+        let mut encoder = zstd::stream::write::Encoder::new(Vec::new(), 0).unwrap();
+        let mut decoder = ZstdStreamDecoder::new().unwrap();
+
+        // Content that spans more than one 128 KiB zstd block, so that the first block can
+        // be decompressed before the rest of the message is received
+        let mut state = 1_u32;
+        let content: String = (0..200_000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                char::from(b'a' + (state >> 16) as u8 % 26)
+            })
+            .collect();
+        let payload = format!(r#"{{"op":0,"t":"READY","d":"{content}"}}"#);
+        encoder.write_all(payload.as_bytes()).unwrap();
+        encoder.flush().unwrap();
+        let compressed = std::mem::take(encoder.get_mut());
+
+        let (first, second) = compressed.split_at(compressed.len() - 16);
+        assert_eq!(decoder.push(first).unwrap(), None);
+        assert_eq!(decoder.push(second).unwrap().unwrap(), payload.as_bytes());
+
+        // The next message is decompressed on its own
+        let ack = r#"{"op":11,"d":null}"#;
+        encoder.write_all(ack.as_bytes()).unwrap();
+        encoder.flush().unwrap();
+        let frame = std::mem::take(encoder.get_mut());
+        assert_eq!(decoder.push(&frame).unwrap().unwrap(), ack.as_bytes());
+    }
+}
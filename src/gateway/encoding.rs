@@ -0,0 +1,143 @@
+use serde::Serialize;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::{error::Error, model::gateway::event::IncomingEvent};
+
+use super::error::{Error as GatewayError, GatewayDecodeError};
+
+/// The first byte of every payload encoded with ETF.
+#[cfg(feature = "etf")]
+const ETF_VERSION: u8 = 131;
+
+/// Sets a query parameter of a gateway URL, replacing any existing value.
+pub(crate) fn set_query_parameter(url: &str, name: &str, value: &str) -> String {
+    let (base, query) = url.split_once('?').unwrap_or((url, ""));
+    // The query can't directly follow the host, such as in `resume_gateway_url`
+    let path = match base.split_once("://") {
+        Some((_, rest)) if !rest.contains('/') => "/",
+        _ => "",
+    };
+    let prefix = format!("{name}=");
+    let mut parameters: Vec<&str> = query
+        .split('&')
+        .filter(|parameter| !parameter.is_empty() && !parameter.starts_with(&prefix))
+        .collect();
+    let parameter = format!("{prefix}{value}");
+    parameters.push(&parameter);
+    format!("{base}{path}?{}", parameters.join("&"))
+}
+
+/// The value of a query parameter of a gateway URL, if it is set
+pub(crate) fn query_parameter<'a>(url: &'a str, name: &str) -> Option<&'a str> {
+    let (_, query) = url.split_once('?')?;
+    query.split('&').find_map(|parameter| {
+        parameter
+            .split_once('=')
+            .filter(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// The format payloads are encoded with, in both directions.
+///
+/// [Discord documentation](https://discord.com/developers/docs/topics/gateway#encoding-and-compression)
+pub enum Encoding {
+    /// Payloads are sent as JSON in text messages.
+    #[default]
+    Json,
+    /// Payloads are sent as Erlang's External Term Format in binary messages.
+    #[cfg(feature = "etf")]
+    Etf,
+}
+
+impl Encoding {
+    /// The value of the `encoding` query parameter for this encoding
+    #[must_use]
+    pub fn query_value(&self) -> &'static str {
+        match self {
+            Encoding::Json => "json",
+            #[cfg(feature = "etf")]
+            Encoding::Etf => "etf",
+        }
+    }
+
+    /// Sets the `encoding` query parameter of a gateway URL, replacing any existing value.
+    #[must_use]
+    pub fn apply_to_url(&self, url: &str) -> String {
+        set_query_parameter(url, "encoding", self.query_value())
+    }
+
+    /// Whether a binary message holds a payload in this encoding, rather than one compressed
+    /// on its own.
+    #[cfg_attr(not(feature = "etf"), allow(unused_variables))]
+    pub(crate) fn is_uncompressed(self, bytes: &[u8]) -> bool {
+        match self {
+            Encoding::Json => false,
+            #[cfg(feature = "etf")]
+            Encoding::Etf => bytes.first() == Some(&ETF_VERSION),
+        }
+    }
+
+    /// Builds the message sending a payload in this encoding.
+    pub(crate) fn encode(self, payload: &impl Serialize) -> serde_json::Result<Message> {
+        Ok(match self {
+            Encoding::Json => Message::Text(serde_json::to_string(payload)?),
+            #[cfg(feature = "etf")]
+            Encoding::Etf => Message::Binary(super::etf::to_vec(&serde_json::to_value(payload)?)),
+        })
+    }
+
+    /// Parses a decompressed payload in this encoding.
+    pub(crate) fn decode(self, bytes: &[u8]) -> Result<IncomingEvent, DecodeError> {
+        let event_map = match self {
+            Encoding::Json => serde_json::from_slice(bytes).map_err(DecodeError::Json)?,
+            #[cfg(feature = "etf")]
+            Encoding::Etf => {
+                let value = super::etf::from_slice(bytes).map_err(DecodeError::Etf)?;
+                serde_json::from_value(value).map_err(DecodeError::Json)?
+            }
+        };
+        IncomingEvent::from_map(event_map).map_err(DecodeError::Event)
+    }
+}
+
+#[derive(Debug)]
+/// Why [`Encoding::decode`] failed, which is smaller than an [`Error`] and converts into one
+pub(crate) enum DecodeError {
+    /// The payload isn't valid JSON, or doesn't have the shape of an event
+    Json(serde_json::Error),
+    /// The payload isn't a valid ETF term
+    #[cfg(feature = "etf")]
+    Etf(std::io::Error),
+    /// The payload is an event that couldn't be decoded
+    Event(GatewayDecodeError),
+}
+
+impl From<DecodeError> for Error {
+    fn from(err: DecodeError) -> Self {
+        match err {
+            DecodeError::Json(err) => Error::Json(err),
+            #[cfg(feature = "etf")]
+            DecodeError::Etf(err) => Error::Io(err),
+            DecodeError::Event(err) => Error::Gateway(GatewayError::Decode(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Encoding;
+
+    #[test]
+    fn replaces_encoding_query_parameter() {
+        assert_eq!(
+            Encoding::Json.apply_to_url("wss://gateway.discord.gg/?v=10&encoding=etf"),
+            "wss://gateway.discord.gg/?v=10&encoding=json"
+        );
+        assert_eq!(
+            Encoding::Json.apply_to_url("wss://gateway.discord.gg"),
+            "wss://gateway.discord.gg/?encoding=json"
+        );
+    }
+}
@@ -1,23 +1,251 @@
 use std::fmt::Display;
 
+use serde_json::Value;
 use tokio_tungstenite::tungstenite::protocol::CloseFrame;
 
+use crate::model::gateway::{event::OpCode, intents::GatewayIntents};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+/// The close codes the gateway may send when closing the connection.
+///
+/// [Discord documentation](https://discord.com/developers/docs/topics/opcodes-and-status-codes#gateway-gateway-close-event-codes)
+pub enum CloseCode {
+    /// We're not sure what went wrong. Try reconnecting?
+    UnknownError,
+    /// An invalid opcode or an invalid payload for an opcode was sent.
+    UnknownOpcode,
+    /// An invalid payload was sent.
+    DecodeError,
+    /// A payload was sent prior to identifying.
+    NotAuthenticated,
+    /// The account token sent with the identify payload is incorrect.
+    AuthenticationFailed,
+    /// More than one identify payload was sent.
+    AlreadyAuthenticated,
+    /// The sequence sent when resuming the session was invalid.
+    InvalidSequence,
+    /// Payloads are being sent too quickly.
+    RateLimited,
+    /// The session timed out.
+    SessionTimedOut,
+    /// An invalid shard was sent when identifying.
+    InvalidShard,
+    /// The session would have handled too many guilds, sharding is required.
+    ShardingRequired,
+    /// An invalid version of the gateway was sent.
+    InvalidApiVersion,
+    /// An invalid intent was sent.
+    InvalidIntents,
+    /// A disallowed intent was sent, either because it has not been enabled or
+    /// the application has not been approved for it.
+    DisallowedIntents,
+    /// A close code that is not documented by Discord.
+    Unknown(u16),
+}
+
+impl CloseCode {
+    /// Whether the client should attempt to reconnect after the gateway closes
+    /// the connection with this code. Codes caused by an invalid configuration
+    /// (token, shard, version or intents) will fail again on every reconnect.
+    #[must_use]
+    pub fn is_reconnectable(&self) -> bool {
+        !matches!(
+            self,
+            CloseCode::AuthenticationFailed
+                | CloseCode::InvalidShard
+                | CloseCode::ShardingRequired
+                | CloseCode::InvalidApiVersion
+                | CloseCode::InvalidIntents
+                | CloseCode::DisallowedIntents
+        )
+    }
+}
+
+impl From<u16> for CloseCode {
+    fn from(value: u16) -> Self {
+        match value {
+            4000 => CloseCode::UnknownError,
+            4001 => CloseCode::UnknownOpcode,
+            4002 => CloseCode::DecodeError,
+            4003 => CloseCode::NotAuthenticated,
+            4004 => CloseCode::AuthenticationFailed,
+            4005 => CloseCode::AlreadyAuthenticated,
+            4007 => CloseCode::InvalidSequence,
+            4008 => CloseCode::RateLimited,
+            4009 => CloseCode::SessionTimedOut,
+            4010 => CloseCode::InvalidShard,
+            4011 => CloseCode::ShardingRequired,
+            4012 => CloseCode::InvalidApiVersion,
+            4013 => CloseCode::InvalidIntents,
+            4014 => CloseCode::DisallowedIntents,
+            other => CloseCode::Unknown(other),
+        }
+    }
+}
+
+impl From<CloseCode> for u16 {
+    fn from(value: CloseCode) -> Self {
+        match value {
+            CloseCode::UnknownError => 4000,
+            CloseCode::UnknownOpcode => 4001,
+            CloseCode::DecodeError => 4002,
+            CloseCode::NotAuthenticated => 4003,
+            CloseCode::AuthenticationFailed => 4004,
+            CloseCode::AlreadyAuthenticated => 4005,
+            CloseCode::InvalidSequence => 4007,
+            CloseCode::RateLimited => 4008,
+            CloseCode::SessionTimedOut => 4009,
+            CloseCode::InvalidShard => 4010,
+            CloseCode::ShardingRequired => 4011,
+            CloseCode::InvalidApiVersion => 4012,
+            CloseCode::InvalidIntents => 4013,
+            CloseCode::DisallowedIntents => 4014,
+            CloseCode::Unknown(code) => code,
+        }
+    }
+}
+
+impl From<&CloseFrame<'_>> for CloseCode {
+    fn from(frame: &CloseFrame<'_>) -> Self {
+        Self::from(u16::from(frame.code))
+    }
+}
+
+impl Display for CloseCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CloseCode::Unknown(code) => write!(f, "{code}"),
+            code => write!(f, "{} ({code:?})", u16::from(*code)),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Where decoding an event received from the gateway failed
+pub enum GatewayDecodeError {
+    /// The event has no `op` field
+    MissingOpcode,
+    /// The `op` field is not an unsigned integer
+    InvalidOpcode(Value),
+    /// The event has no `d` field, which is required by its opcode
+    MissingData(OpCode),
+    /// The `d` field does not match the data of its opcode
+    InvalidData(OpCode, serde_json::Error),
+    /// The dispatch event, named by the `t` field if any, could not be decoded. The
+    /// sequence number of the `s` field is kept so that the event can be skipped.
+    InvalidDispatch(Option<String>, Option<u64>, serde_json::Error),
+}
+
+impl Display for GatewayDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GatewayDecodeError::MissingOpcode => write!(f, "Event is missing its opcode"),
+            GatewayDecodeError::InvalidOpcode(op) => write!(f, "Event has an invalid opcode {op}"),
+            GatewayDecodeError::MissingData(op) => write!(f, "{op:?} event is missing its data"),
+            GatewayDecodeError::InvalidData(op, err) => {
+                write!(f, "{op:?} event has invalid data: {err}")
+            }
+            GatewayDecodeError::InvalidDispatch(Some(event), _, err) => {
+                write!(f, "Invalid {event} dispatch event: {err}")
+            }
+            GatewayDecodeError::InvalidDispatch(None, _, err) => {
+                write!(f, "Invalid dispatch event: {err}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GatewayDecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GatewayDecodeError::InvalidData(_, err)
+            | GatewayDecodeError::InvalidDispatch(_, _, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
     NoSessionToResume,
-    Closed(Option<CloseFrame<'static>>),
+    Closed(Option<CloseCode>),
+    /// The gateway closed the connection with [`CloseCode::DisallowedIntents`], with the
+    /// privileged intents that were requested
+    DisallowedIntents(GatewayIntents),
+    Decode(GatewayDecodeError),
+}
+
+impl Error {
+    /// Whether reconnecting may succeed after this error. The connection being lost, or
+    /// closed with a [reconnectable](CloseCode::is_reconnectable) code, is recoverable,
+    /// as is a dispatch event that cannot be decoded, which only affects that event.
+    /// Disallowed intents and other events that cannot be decoded will fail again.
+    #[must_use]
+    pub fn is_recoverable(&self) -> bool {
+        match self {
+            Error::NoSessionToResume
+            | Error::Closed(None)
+            | Error::Decode(GatewayDecodeError::InvalidDispatch(..)) => true,
+            Error::Closed(Some(code)) => code.is_reconnectable(),
+            Error::DisallowedIntents(_) | Error::Decode(_) => false,
+        }
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::NoSessionToResume => write!(f, "No session to resume"),
-            Error::Closed(frame) => match frame {
-                Some(frame) => write!(f, "Websocket closed with code {}", frame.code),
+            Error::Closed(code) => match code {
+                Some(code) => write!(f, "Websocket closed with code {code}"),
                 None => write!(f, "Websocket closed"),
             },
+            Error::DisallowedIntents(intents) if intents.is_empty() => write!(
+                f,
+                "Websocket closed with code {}, although no privileged intents were requested",
+                CloseCode::DisallowedIntents
+            ),
+            Error::DisallowedIntents(intents) => write!(
+                f,
+                "Websocket closed with code {}, the privileged intents {intents} must be \
+                enabled for the application in the developer portal",
+                CloseCode::DisallowedIntents
+            ),
+            Error::Decode(err) => Display::fmt(err, f),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Decode(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CloseCode;
+
+    #[test]
+    fn close_code_round_trips() {
+        for code in 4000..=4014 {
+            assert_eq!(u16::from(CloseCode::from(code)), code);
+        }
+        assert_eq!(CloseCode::from(4006), CloseCode::Unknown(4006));
+        assert_eq!(CloseCode::from(1000), CloseCode::Unknown(1000));
+    }
+
+    #[test]
+    fn fatal_close_codes_are_not_reconnectable() {
+        for code in [4004, 4010, 4011, 4012, 4013, 4014] {
+            assert!(!CloseCode::from(code).is_reconnectable());
+        }
+        for code in [4000, 4001, 4008, 4009, 1001] {
+            assert!(CloseCode::from(code).is_reconnectable());
+        }
+    }
+}
@@ -0,0 +1,368 @@
+//! Conversion between [`Value`]s and Erlang's External Term Format, limited to the
+//! terms sent and accepted by the gateway.
+//!
+//! [Erlang documentation](https://www.erlang.org/doc/apps/erts/erl_ext_dist.html)
+
+use std::io::{Error as IoError, ErrorKind};
+
+use serde_json::{Map, Number, Value};
+
+const VERSION: u8 = 131;
+const NEW_FLOAT_EXT: u8 = 70;
+const SMALL_INTEGER_EXT: u8 = 97;
+const INTEGER_EXT: u8 = 98;
+const ATOM_EXT: u8 = 100;
+const SMALL_TUPLE_EXT: u8 = 104;
+const LARGE_TUPLE_EXT: u8 = 105;
+const NIL_EXT: u8 = 106;
+const STRING_EXT: u8 = 107;
+const LIST_EXT: u8 = 108;
+const BINARY_EXT: u8 = 109;
+const SMALL_BIG_EXT: u8 = 110;
+const LARGE_BIG_EXT: u8 = 111;
+const SMALL_ATOM_EXT: u8 = 115;
+const MAP_EXT: u8 = 116;
+const ATOM_UTF8_EXT: u8 = 118;
+const SMALL_ATOM_UTF8_EXT: u8 = 119;
+
+/// Encodes a value as a term, with maps keyed by binaries and `null` as the `nil` atom.
+pub(crate) fn to_vec(value: &Value) -> Vec<u8> {
+    let mut bytes = vec![VERSION];
+    encode(value, &mut bytes);
+    bytes
+}
+
+/// Decodes a term, turning atoms into strings other than `true`, `false` and `nil`.
+pub(crate) fn from_slice(bytes: &[u8]) -> Result<Value, IoError> {
+    let mut decoder = Decoder { bytes, position: 0 };
+    if decoder.u8()? != VERSION {
+        return Err(invalid("unsupported external term format version"));
+    }
+    let value = decoder.term()?;
+    if decoder.position != bytes.len() {
+        return Err(invalid("trailing bytes after term"));
+    }
+    Ok(value)
+}
+
+fn invalid(message: &str) -> IoError {
+    IoError::new(ErrorKind::InvalidData, format!("invalid ETF: {message}"))
+}
+
+fn encode(value: &Value, bytes: &mut Vec<u8>) {
+    match value {
+        Value::Null => encode_atom("nil", bytes),
+        Value::Bool(value) => encode_atom(if *value { "true" } else { "false" }, bytes),
+        Value::Number(number) => encode_number(number, bytes),
+        Value::String(string) => encode_binary(string, bytes),
+        Value::Array(values) => {
+            if !values.is_empty() {
+                bytes.push(LIST_EXT);
+                bytes.extend_from_slice(&(values.len() as u32).to_be_bytes());
+                for value in values {
+                    encode(value, bytes);
+                }
+            }
+            bytes.push(NIL_EXT);
+        }
+        Value::Object(map) => {
+            bytes.push(MAP_EXT);
+            bytes.extend_from_slice(&(map.len() as u32).to_be_bytes());
+            for (key, value) in map {
+                encode_binary(key, bytes);
+                encode(value, bytes);
+            }
+        }
+    }
+}
+
+fn encode_atom(atom: &str, bytes: &mut Vec<u8>) {
+    bytes.push(SMALL_ATOM_UTF8_EXT);
+    bytes.push(atom.len() as u8);
+    bytes.extend_from_slice(atom.as_bytes());
+}
+
+fn encode_binary(string: &str, bytes: &mut Vec<u8>) {
+    bytes.push(BINARY_EXT);
+    bytes.extend_from_slice(&(string.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(string.as_bytes());
+}
+
+fn encode_number(number: &Number, bytes: &mut Vec<u8>) {
+    if let Some(value) = number.as_u64() {
+        if let Ok(value) = u8::try_from(value) {
+            bytes.push(SMALL_INTEGER_EXT);
+            bytes.push(value);
+        } else if let Ok(value) = i32::try_from(value) {
+            bytes.push(INTEGER_EXT);
+            bytes.extend_from_slice(&value.to_be_bytes());
+        } else {
+            encode_big(false, value, bytes);
+        }
+    } else if let Some(value) = number.as_i64() {
+        if let Ok(value) = i32::try_from(value) {
+            bytes.push(INTEGER_EXT);
+            bytes.extend_from_slice(&value.to_be_bytes());
+        } else {
+            encode_big(true, value.unsigned_abs(), bytes);
+        }
+    } else if let Some(value) = number.as_f64() {
+        bytes.push(NEW_FLOAT_EXT);
+        bytes.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+fn encode_big(negative: bool, value: u64, bytes: &mut Vec<u8>) {
+    let digits = value.to_le_bytes();
+    let length = digits
+        .iter()
+        .rposition(|&digit| digit != 0)
+        .map_or(0, |i| i + 1);
+    bytes.push(SMALL_BIG_EXT);
+    bytes.push(length as u8);
+    bytes.push(u8::from(negative));
+    bytes.extend_from_slice(&digits[..length]);
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], IoError> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| invalid("unexpected end of term"))?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, IoError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<usize, IoError> {
+        let bytes = self.take(2)?;
+        Ok(usize::from(u16::from_be_bytes([bytes[0], bytes[1]])))
+    }
+
+    fn u32(&mut self) -> Result<usize, IoError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    }
+
+    fn string(&mut self, length: usize) -> Result<String, IoError> {
+        String::from_utf8(self.take(length)?.to_vec()).map_err(|_| invalid("invalid UTF-8"))
+    }
+
+    fn term(&mut self) -> Result<Value, IoError> {
+        match self.u8()? {
+            SMALL_INTEGER_EXT => Ok(Value::from(self.u8()?)),
+            INTEGER_EXT => {
+                let bytes = self.take(4)?;
+                Ok(Value::from(i32::from_be_bytes([
+                    bytes[0], bytes[1], bytes[2], bytes[3],
+                ])))
+            }
+            NEW_FLOAT_EXT => {
+                let mut bytes = [0; 8];
+                bytes.copy_from_slice(self.take(8)?);
+                Ok(Value::from(f64::from_be_bytes(bytes)))
+            }
+            SMALL_BIG_EXT => {
+                let length = usize::from(self.u8()?);
+                self.big(length)
+            }
+            LARGE_BIG_EXT => {
+                let length = self.u32()?;
+                self.big(length)
+            }
+            ATOM_EXT | ATOM_UTF8_EXT => {
+                let length = self.u16()?;
+                self.atom(length)
+            }
+            SMALL_ATOM_EXT | SMALL_ATOM_UTF8_EXT => {
+                let length = usize::from(self.u8()?);
+                self.atom(length)
+            }
+            BINARY_EXT => {
+                let length = self.u32()?;
+                Ok(Value::String(self.string(length)?))
+            }
+            STRING_EXT => {
+                let length = self.u16()?;
+                Ok(Value::Array(
+                    self.take(length)?
+                        .iter()
+                        .copied()
+                        .map(Value::from)
+                        .collect(),
+                ))
+            }
+            NIL_EXT => Ok(Value::Array(Vec::new())),
+            LIST_EXT => {
+                let length = self.u32()?;
+                let values = self.terms(length)?;
+                // Proper lists end with an empty list as their tail
+                if self.term()? != Value::Array(Vec::new()) {
+                    return Err(invalid("improper lists are not supported"));
+                }
+                Ok(Value::Array(values))
+            }
+            SMALL_TUPLE_EXT => {
+                let length = usize::from(self.u8()?);
+                self.terms(length).map(Value::Array)
+            }
+            LARGE_TUPLE_EXT => {
+                let length = self.u32()?;
+                self.terms(length).map(Value::Array)
+            }
+            MAP_EXT => {
+                let length = self.u32()?;
+                let mut map = Map::new();
+                for _ in 0..length {
+                    let key = match self.term()? {
+                        Value::String(key) => key,
+                        key @ (Value::Number(_) | Value::Bool(_) | Value::Null) => key.to_string(),
+                        _ => return Err(invalid("unsupported map key")),
+                    };
+                    map.insert(key, self.term()?);
+                }
+                Ok(Value::Object(map))
+            }
+            tag => Err(invalid(&format!("unsupported tag {tag}"))),
+        }
+    }
+
+    fn terms(&mut self, length: usize) -> Result<Vec<Value>, IoError> {
+        (0..length).map(|_| self.term()).collect()
+    }
+
+    fn atom(&mut self, length: usize) -> Result<Value, IoError> {
+        let atom = self.string(length)?;
+        Ok(match atom.as_str() {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            "nil" | "null" => Value::Null,
+            _ => Value::String(atom),
+        })
+    }
+
+    fn big(&mut self, length: usize) -> Result<Value, IoError> {
+        let negative = self.u8()? != 0;
+        let digits = self.take(length)?;
+        if digits[8.min(length)..].iter().any(|&digit| digit != 0) {
+            return Err(invalid("integer does not fit in 64 bits"));
+        }
+        let mut bytes = [0; 8];
+        bytes[..8.min(length)].copy_from_slice(&digits[..8.min(length)]);
+        let value = u64::from_le_bytes(bytes);
+
+        if negative {
+            i64::try_from(value)
+                .map(|value| Value::from(-value))
+                .map_err(|_| invalid("integer does not fit in 64 bits"))
+        } else {
+            Ok(Value::from(value))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{from_slice, to_vec};
+    use crate::gateway::mock::user_payload;
+    use crate::model::{
+        gateway::{
+            dispatch::DispatchEvent,
+            event::{IncomingEvent, OutgoingEvent, ReceiveEventData, SendEventData},
+        },
+        snowflake::Snowflake,
+    };
+
+    #[test]
+    fn heartbeat_round_trips() {
+        let heartbeat =
+            serde_json::to_value(OutgoingEvent::from(SendEventData::Heartbeat(Some(251)))).unwrap();
+
+        assert_eq!(from_slice(&to_vec(&heartbeat)).unwrap(), heartbeat);
+    }
+
+    #[test]
+    fn ready_round_trips() {
+        let mut user = user_payload();
+        user["bot"] = json!(true);
+        let ready = json!({
+            "op": 0,
+            "s": 1,
+            "t": "READY",
+            "d": {
+                "v": 10,
+                "user": user,
+                "guilds": [{ "id": "41771983423143937", "unavailable": true }],
+                "session_id": "session",
+                "resume_gateway_url": "wss://gateway.discord.gg",
+                "shard": [0, 1],
+            },
+        });
+
+        let decoded = from_slice(&to_vec(&ready)).unwrap();
+        assert_eq!(decoded, ready);
+
+        let event: IncomingEvent = serde_json::from_value(decoded).unwrap();
+        let Some(ReceiveEventData::Dispatch(DispatchEvent::Ready(ready))) = event.data else {
+            panic!("expected a READY event");
+        };
+        assert_eq!(ready.session_id, "session");
+    }
+
+    #[test]
+    fn ready_with_integer_ids_decodes() {
+        // Discord sends snowflakes as integers over ETF rather than as strings
+        let mut user = user_payload();
+        user["id"] = json!(80_351_110_224_678_912_u64);
+        user["bot"] = json!(true);
+        let ready = json!({
+            "op": 0,
+            "s": 1,
+            "t": "READY",
+            "d": {
+                "v": 10,
+                "user": user,
+                "guilds": [{ "id": 41_771_983_423_143_937_u64, "unavailable": true }],
+                "session_id": "session",
+                "resume_gateway_url": "wss://gateway.discord.gg",
+                "shard": [0, 1],
+            },
+        });
+
+        let event: IncomingEvent = serde_json::from_value(from_slice(&to_vec(&ready)).unwrap())
+            .expect("integer IDs should decode");
+        let Some(ReceiveEventData::Dispatch(DispatchEvent::Ready(ready))) = event.data else {
+            panic!("expected a READY event");
+        };
+        assert_eq!(ready.user.id, Snowflake(80_351_110_224_678_912));
+        assert_eq!(ready.guilds[0].id, Snowflake(41_771_983_423_143_937));
+    }
+
+    #[test]
+    fn decodes_large_integers() {
+        let snowflake = json!(175_928_847_299_117_063_u64);
+        assert_eq!(from_slice(&to_vec(&snowflake)).unwrap(), snowflake);
+
+        let negative = json!(-5_000_000_000_i64);
+        assert_eq!(from_slice(&to_vec(&negative)).unwrap(), negative);
+    }
+
+    #[test]
+    fn rejects_truncated_terms() {
+        let bytes = to_vec(&json!({ "op": 11 }));
+        assert!(from_slice(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
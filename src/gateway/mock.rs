@@ -0,0 +1,199 @@
+//! An in-process gateway used by tests to drive a [`Shard`](super::shard::Shard)
+//! without connecting to Discord.
+
+use std::{sync::Arc, time::Duration};
+
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::{
+    net::TcpListener,
+    sync::{mpsc, Mutex},
+    time::timeout,
+};
+use tokio_tungstenite::{
+    accept_hdr_async,
+    tungstenite::{
+        handshake::server::{Request, Response},
+        Message,
+    },
+};
+
+#[derive(Debug)]
+pub(crate) struct MockGateway {
+    pub url: String,
+    sent: mpsc::UnboundedReceiver<Message>,
+    /// The request URI of every connection, including its query
+    requests: mpsc::UnboundedReceiver<String>,
+    /// Messages to send to the connected client, where `None` drops the connection
+    outgoing: mpsc::UnboundedSender<Option<Message>>,
+}
+
+impl MockGateway {
+    /// Listens on a random local port, accepting any number of connections.
+    pub async fn bind() -> Self {
+        Self::listen(false).await
+    }
+
+    /// Like [`MockGateway::bind`], but every connection is sent a `HELLO` and answers
+    /// each identify with a `READY` for the identifying shard, so that several shards
+    /// can connect at once. Queued messages are not sent.
+    pub async fn bind_handshaking() -> Self {
+        Self::listen(true).await
+    }
+
+    async fn listen(handshaking: bool) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (sent_tx, sent) = mpsc::unbounded_channel();
+        let (requests_tx, requests) = mpsc::unbounded_channel();
+        let (outgoing, outgoing_rx) = mpsc::unbounded_channel::<Option<Message>>();
+        let outgoing_rx = Arc::new(Mutex::new(outgoing_rx));
+
+        let listener_url = url.clone();
+        tokio::spawn(async move {
+            let url = listener_url;
+            while let Ok((stream, _)) = listener.accept().await {
+                let requests_tx = requests_tx.clone();
+                // The response type is set by tungstenite
+                #[allow(clippy::result_large_err)]
+                let record_request = move |request: &Request, response: Response| {
+                    let _ = requests_tx.send(request.uri().to_string());
+                    Ok(response)
+                };
+                let Ok(websocket) = accept_hdr_async(stream, record_request).await else {
+                    continue;
+                };
+                let sent_tx = sent_tx.clone();
+                let outgoing_rx = outgoing_rx.clone();
+                let url = url.clone();
+                tokio::spawn(async move {
+                    let (mut write, mut read) = websocket.split();
+                    if handshaking {
+                        let hello = json!({ "op": 10, "d": { "heartbeat_interval": 45000 } });
+                        let _ = write.send(Message::Text(hello.to_string())).await;
+                        while let Some(Ok(message)) = read.next().await {
+                            if let Some(shard) = identified_shard(&message) {
+                                let ready = ready_payload(&url, &shard);
+                                let _ = write.send(Message::Text(ready.to_string())).await;
+                            }
+                            let _ = sent_tx.send(message);
+                        }
+                        return;
+                    }
+                    let mut outgoing_rx = outgoing_rx.lock().await;
+                    loop {
+                        tokio::select! {
+                            message = read.next() => match message {
+                                Some(Ok(message)) => {
+                                    let _ = sent_tx.send(message);
+                                }
+                                _ => break,
+                            },
+                            Some(message) = outgoing_rx.recv() => {
+                                // Dropping the connection closes it without a close frame
+                                let Some(message) = message else {
+                                    break;
+                                };
+                                let closing = message.is_close();
+                                if write.send(message).await.is_err() || closing {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        Self {
+            url,
+            sent,
+            requests,
+            outgoing,
+        }
+    }
+
+    /// Queues a message to be sent to the connected client.
+    pub fn send(&self, message: Message) {
+        self.outgoing.send(Some(message)).unwrap();
+    }
+
+    /// Drops the connection to the client without sending a close frame, such as when
+    /// the network fails.
+    pub fn disconnect(&self) {
+        self.outgoing.send(None).unwrap();
+    }
+
+    /// Queues a JSON payload to be sent to the connected client.
+    pub fn send_json(&self, value: &Value) {
+        self.send(Message::Text(value.to_string()));
+    }
+
+    /// Sends a `READY` dispatch to the connected client.
+    pub fn send_ready(&self) {
+        self.send_json(&ready_payload(&self.url, &json!([0, 1])));
+    }
+
+    /// The next message sent by the client, if one arrives within a second.
+    pub async fn next_sent(&mut self) -> Option<Message> {
+        timeout(Duration::from_secs(1), self.sent.recv())
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// The request URI of the next connection, if one is made within a second.
+    pub async fn next_request_uri(&mut self) -> Option<String> {
+        timeout(Duration::from_secs(1), self.requests.recv())
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// The next JSON payload sent by the client, if one arrives within a second.
+    pub async fn next_sent_json(&mut self) -> Option<Value> {
+        loop {
+            match self.next_sent().await? {
+                Message::Text(text) => return serde_json::from_str(&text).ok(),
+                Message::Binary(bytes) => return serde_json::from_slice(&bytes).ok(),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The `shard` of an identify payload sent by the client
+fn identified_shard(message: &Message) -> Option<Value> {
+    let Message::Text(text) = message else {
+        return None;
+    };
+    let payload: Value = serde_json::from_str(text).ok()?;
+    (payload["op"] == 2).then(|| payload["d"]["shard"].clone())
+}
+
+/// A user as sent by the gateway, for use in test payloads
+pub(crate) fn user_payload() -> Value {
+    json!({
+        "id": "80351110224678912",
+        "username": "Nelly",
+        "discriminator": "0",
+        "global_name": null,
+        "avatar": null,
+    })
+}
+
+fn ready_payload(url: &str, shard: &Value) -> Value {
+    json!({
+        "op": 0,
+        "s": 1,
+        "t": "READY",
+        "d": {
+            "v": 10,
+            "user": user_payload(),
+            "guilds": [],
+            "session_id": "session",
+            "resume_gateway_url": url,
+            "shard": shard,
+        },
+    })
+}
@@ -1,4 +1,15 @@
+pub mod builder;
+pub mod compression;
+pub mod encoding;
 pub mod error;
+#[cfg(feature = "etf")]
+mod etf;
+#[cfg(test)]
+pub(crate) mod mock;
+pub mod proxy;
+pub mod ratelimit;
+pub mod reconnect;
 pub mod shard;
 pub mod shard_manager;
+pub mod url;
 pub mod websocket;
@@ -0,0 +1,180 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::{
+    sync::Mutex,
+    time::{sleep_until, Instant},
+};
+
+use super::shard::IDENTIFY_INTERVAL;
+
+#[derive(Debug, Clone)]
+/// Spaces out identifies sharing the same rate limit key.
+///
+/// Cloning the limiter shares it, so every shard identifying with the same
+/// token should be given a clone of the same limiter.
+pub struct IdentifyRateLimiter {
+    interval: Duration,
+    last_identify: Arc<Mutex<Option<Instant>>>,
+}
+
+impl IdentifyRateLimiter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_interval(IDENTIFY_INTERVAL)
+    }
+
+    /// Creates a limiter allowing a single identify per `interval`, rather than
+    /// the gateway's default of [`IDENTIFY_INTERVAL`].
+    #[must_use]
+    pub fn with_interval(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_identify: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    #[must_use]
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Waits until an identify is allowed, then records it. Concurrent callers
+    /// are let through one at a time, in the order they started waiting.
+    pub async fn wait(&self) {
+        let mut last_identify = self.last_identify.lock().await;
+        if let Some(last_identify) = *last_identify {
+            sleep_until(last_identify + self.interval).await;
+        }
+        *last_identify = Some(Instant::now());
+    }
+}
+
+impl Default for IdentifyRateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Spaces out identifies for bots allowed to identify several shards at once.
+///
+/// Shards are grouped into buckets by `shard_id % max_concurrency`. Shards in
+/// the same bucket identify one at a time, while shards in different buckets may
+/// identify concurrently.
+pub struct BucketedIdentifyLimiter {
+    buckets: Vec<IdentifyRateLimiter>,
+}
+
+impl BucketedIdentifyLimiter {
+    /// Creates a limiter with `max_concurrency` buckets. This value is provided by the
+    /// Get Gateway Bot endpoint as [`SessionStartLimit::max_concurrency`].
+    ///
+    /// [`SessionStartLimit::max_concurrency`]: crate::model::gateway::session::SessionStartLimit::max_concurrency
+    #[must_use]
+    pub fn new(max_concurrency: u64) -> Self {
+        Self::with_interval(max_concurrency, IDENTIFY_INTERVAL)
+    }
+
+    /// Creates a limiter with `max_concurrency` buckets, each allowing a single identify
+    /// per `interval`.
+    #[must_use]
+    pub fn with_interval(max_concurrency: u64, interval: Duration) -> Self {
+        Self {
+            buckets: (0..max_concurrency.max(1))
+                .map(|_| IdentifyRateLimiter::with_interval(interval))
+                .collect(),
+        }
+    }
+
+    #[must_use]
+    pub fn max_concurrency(&self) -> u64 {
+        self.buckets.len() as u64
+    }
+
+    /// The bucket a shard identifies in
+    #[must_use]
+    pub fn bucket(&self, shard_id: u64) -> u64 {
+        shard_id % self.max_concurrency()
+    }
+
+    /// The limiter shared by every shard in the same bucket as `shard_id`
+    #[must_use]
+    pub fn limiter(&self, shard_id: u64) -> IdentifyRateLimiter {
+        self.buckets[self.bucket(shard_id) as usize].clone()
+    }
+
+    /// Waits until the shard is allowed to identify, then records it.
+    pub async fn wait(&self, shard_id: u64) {
+        self.buckets[self.bucket(shard_id) as usize].wait().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::time::Instant;
+
+    use super::{BucketedIdentifyLimiter, IdentifyRateLimiter};
+    use crate::gateway::shard::IDENTIFY_INTERVAL;
+
+    #[tokio::test(start_paused = true)]
+    async fn identifies_are_spaced_by_interval() {
+        let limiter = IdentifyRateLimiter::new();
+        let start = Instant::now();
+
+        let tasks: Vec<_> = (0..3)
+            .map(|_| {
+                let limiter = limiter.clone();
+                tokio::spawn(async move {
+                    limiter.wait().await;
+                    Instant::now()
+                })
+            })
+            .collect();
+        let mut identified = Vec::new();
+        for task in tasks {
+            identified.push(task.await.unwrap());
+        }
+        identified.sort();
+
+        assert_eq!(identified[0], start);
+        assert!(identified[1] - identified[0] >= IDENTIFY_INTERVAL);
+        assert!(identified[2] - identified[1] >= IDENTIFY_INTERVAL);
+    }
+
+    async fn identify_times(limiter: &BucketedIdentifyLimiter, shard_ids: &[u64]) -> Vec<Instant> {
+        let tasks: Vec<_> = shard_ids
+            .iter()
+            .map(|&shard_id| {
+                let limiter = limiter.clone();
+                tokio::spawn(async move {
+                    limiter.wait(shard_id).await;
+                    Instant::now()
+                })
+            })
+            .collect();
+        let mut identified = Vec::new();
+        for task in tasks {
+            identified.push(task.await.unwrap());
+        }
+        identified.sort();
+        identified
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn different_buckets_identify_concurrently() {
+        let limiter = BucketedIdentifyLimiter::new(2);
+        assert_ne!(limiter.bucket(0), limiter.bucket(1));
+
+        let identified = identify_times(&limiter, &[0, 1]).await;
+        assert_eq!(identified[0], identified[1]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn same_bucket_identifies_serially() {
+        let limiter = BucketedIdentifyLimiter::new(2);
+        assert_eq!(limiter.bucket(0), limiter.bucket(2));
+
+        let identified = identify_times(&limiter, &[0, 2]).await;
+        assert!(identified[1] - identified[0] >= IDENTIFY_INTERVAL);
+    }
+}
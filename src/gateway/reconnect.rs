@@ -0,0 +1,125 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+/// Returns a random value in `[0, 1)`.
+pub(crate) fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish().to_be_bytes();
+    let high = u32::from_be_bytes([random[0], random[1], random[2], random[3]]);
+    f64::from(high) / (f64::from(u32::MAX) + 1.0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Controls how long a shard waits before each reconnect attempt.
+///
+/// The delay doubles with every consecutive failed reconnect, starting at `base`
+/// and capped at `max`, with up to `jitter` added on top so that many shards
+/// don't reconnect in lockstep. The counter is reset once the gateway sends
+/// a `READY` or `RESUMED` event.
+pub struct ReconnectPolicy {
+    /// The delay before the first reconnect attempt
+    pub base: Duration,
+    /// The maximum delay between reconnect attempts, excluding jitter
+    pub max: Duration,
+    /// The maximum random delay added to every reconnect attempt
+    pub jitter: Duration,
+    attempts: u32,
+}
+
+impl ReconnectPolicy {
+    #[must_use]
+    pub fn new(base: Duration, max: Duration, jitter: Duration) -> Self {
+        Self {
+            base,
+            max,
+            jitter,
+            attempts: 0,
+        }
+    }
+
+    /// The number of consecutive reconnect attempts since the last successful connection
+    #[must_use]
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// The delay before the next reconnect attempt, without jitter
+    #[must_use]
+    pub fn backoff(&self) -> Duration {
+        self.base
+            .saturating_mul(1 << self.attempts.min(31))
+            .min(self.max)
+    }
+
+    /// Returns the delay to wait before the next reconnect attempt and records the attempt.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.backoff() + self.random_jitter();
+        self.attempts = self.attempts.saturating_add(1);
+        delay
+    }
+
+    /// Resets the backoff after a successful connection.
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+    }
+
+    fn random_jitter(&self) -> Duration {
+        let jitter = self.jitter.as_millis() as u64;
+        if jitter == 0 {
+            return Duration::ZERO;
+        }
+        let random = RandomState::new().build_hasher().finish();
+        Duration::from_millis(random % (jitter + 1))
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::new(
+            Duration::from_secs(1),
+            Duration::from_secs(64),
+            Duration::from_secs(1),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::ReconnectPolicy;
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let mut policy = ReconnectPolicy::new(
+            Duration::from_secs(1),
+            Duration::from_secs(64),
+            Duration::ZERO,
+        );
+        let delays: Vec<u64> = (0..9).map(|_| policy.next_delay().as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 32, 64, 64, 64]);
+        assert_eq!(policy.attempts(), 9);
+
+        policy.reset();
+        assert_eq!(policy.next_delay(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn jitter_is_bounded() {
+        let mut policy = ReconnectPolicy::default();
+        for _ in 0..20 {
+            let backoff = policy.backoff();
+            let delay = policy.next_delay();
+            assert!(delay >= backoff && delay <= backoff + policy.jitter);
+        }
+    }
+
+    #[test]
+    fn random_fraction_is_in_unit_interval() {
+        for _ in 0..100 {
+            assert!((0.0..1.0).contains(&super::random_fraction()));
+        }
+    }
+}
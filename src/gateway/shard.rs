@@ -1,18 +1,39 @@
-use std::time::Duration;
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
 use serde::{ser::SerializeSeq, Deserialize, Serialize};
-use tokio::time::Instant;
+use tokio::{sync::mpsc::UnboundedSender, time::Instant};
 
 use crate::{
+    cache::{Cache, CacheIntentWarning, CacheSettings},
     error::{Error, Result},
-    model::gateway::{
-        dispatch::DispatchEvent,
-        event::{Event, ReceiveEventData},
-        intents::GatewayIntents,
+    model::{
+        gateway::{
+            dispatch::DispatchEvent,
+            event::{GuildMembersFilter, IdentifyProperties, IncomingEvent, ReceiveEventData},
+            intents::GatewayIntents,
+            presence::PresenceUpdate,
+            session::SessionStartLimit,
+            voice::VoiceStateUpdateData,
+        },
+        snowflake::Snowflake,
     },
 };
 
-use super::{error::Error as GatewayError, websocket::WebsocketClient};
+use super::{
+    builder::ShardBuilder,
+    error::{CloseCode, Error as GatewayError, GatewayDecodeError},
+    proxy::ProxyConfig,
+    ratelimit::IdentifyRateLimiter,
+    reconnect::{random_fraction, ReconnectPolicy},
+    url::GatewayUrl,
+    websocket::{WebSocketConfig, WebsocketClient},
+};
+
+/// The gateway allows a single identify per 5 seconds per rate limit key.
+pub const IDENTIFY_INTERVAL: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ShardInformation {
@@ -20,6 +41,19 @@ pub struct ShardInformation {
     pub total: u64,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// What is needed to resume a session, which can be saved before a process exits and
+/// resumed with [`ShardBuilder::resume_from`] once it restarts, so that the events sent
+/// in between are replayed rather than missed.
+pub struct SessionState {
+    /// The ID of the session
+    pub session_id: String,
+    /// The URL the session is resumed on
+    pub resume_url: String,
+    /// The sequence number of the last dispatch event received
+    pub sequence: u64,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ReconnectionKind {
     Identify,
@@ -31,15 +65,45 @@ pub enum ShardAction {
     Heartbeat,
     Identify,
     Reconnect(ReconnectionKind),
+    /// The session restored with [`ShardBuilder::resume_from`] should be resumed on the
+    /// current connection
+    Resume,
+    /// The session was resumed and the last presence should be sent again,
+    /// see [`Shard::reapply_presence_on_resume`]
+    ReapplyPresence,
+    /// The gateway closed the connection with a code that will fail again on reconnect
+    Disconnect(CloseCode),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Events derived by the shard from the gateway's events, rather than sent by the gateway itself.
+pub enum ShardEvent {
+    /// A guild that was unavailable, either when the session started or due to an outage,
+    /// has become available. Contains the guild ID.
+    GuildAvailable(Snowflake),
+    /// The client has joined a new guild. Contains the guild ID.
+    GuildJoined(Snowflake),
+    /// The session was resumed after the gateway replayed the events that were missed
+    Resumed {
+        /// The number of dispatch events replayed before the session was resumed
+        replayed: u64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The stage of a shard's connection to the gateway, see [`Shard::connection_stage`]
 pub enum ConnectionStage {
+    /// The websocket connection is being opened
     Connecting,
+    /// The session is ready and dispatch events are being received
     Connected,
+    /// The connection was closed, and the shard will reconnect
     Disconnected,
+    /// The websocket connection is open and waiting for the gateway to say hello
     Handshake,
+    /// An identify was sent and the shard is waiting for `READY`
     Identifying,
+    /// A resume was sent and the shard is waiting for `RESUMED`
     Resuming,
 }
 
@@ -66,48 +130,154 @@ impl<'de> Deserialize<'de> for ShardInformation {
 
 #[derive(Debug)]
 pub struct Shard {
-    websocket_url: String,
+    gateway_url: GatewayUrl,
     pub websocket: WebsocketClient,
+    /// The configuration every connection of the shard is made with, see
+    /// [`WebsocketClient::default_config`]
+    pub websocket_config: WebSocketConfig,
+    /// The proxy every connection of the shard is tunnelled through, if any
+    pub proxy: Option<ProxyConfig>,
     connection_stage: ConnectionStage,
+    stage_entered_at: Instant,
+    stage_timings: HashMap<ConnectionStage, Duration>,
     heartbeat_interval: Option<Duration>,
+    first_heartbeat_sent: bool,
+    first_heartbeat_jitter: f64,
+    /// Returns a random value in `[0, 1)` that the heartbeat interval is multiplied by
+    /// for the first heartbeat of each connection, so that many shards connecting at
+    /// once don't heartbeat in lockstep
+    pub heartbeat_jitter: fn() -> f64,
     last_heartbeat_sent: Option<Instant>,
     last_heartbeat_received: bool,
+    heartbeat_sent_at: Option<Instant>,
+    last_heartbeat_ack: Option<Instant>,
+    latency: Option<Duration>,
+    last_identify: Option<Instant>,
+    identify_backoff: Option<Duration>,
     sequence: u64,
+    dispatches_since_resume: u64,
     session_id: Option<String>,
     resume_url: Option<String>,
     pub shard_information: Option<ShardInformation>,
     token: String,
     pub intents: GatewayIntents,
+    /// The member count from which the gateway stops sending the offline members of a
+    /// guild, between 50 and 250. The gateway uses 50 when this is `None`.
+    pub large_threshold: Option<u8>,
+    /// The properties of the client sent when identifying
+    pub properties: IdentifyProperties,
+    /// The presence sent when identifying, and the last presence set with
+    /// [`Shard::update_presence`]
+    pub presence: Option<PresenceUpdate>,
+    /// Whether [`Shard::presence`] is sent again once a session is resumed, so that the
+    /// client's status can't silently revert. This is `false` by default.
+    pub reapply_presence_on_resume: bool,
+    pub reconnect_policy: ReconnectPolicy,
+    /// The session start limit from the Get Gateway Bot endpoint, used to back off
+    /// when the gateway rate limits identifies
+    pub session_start_limit: Option<SessionStartLimit>,
+    /// Shared between every shard identifying with the same rate limit key
+    pub identify_rate_limiter: IdentifyRateLimiter,
+    /// Receives the [`ShardEvent`]s derived from the gateway's events
+    pub shard_event_sender: Option<UnboundedSender<ShardEvent>>,
+    /// Receives the shard's [`ConnectionStage`] each time it changes
+    pub connection_stage_sender: Option<UnboundedSender<ConnectionStage>>,
+    unavailable_guilds: HashSet<Snowflake>,
+    /// The state received from this shard's dispatch events
+    pub cache: Cache,
 }
 
 impl Shard {
+    /// Connects a shard to the gateway at `websocket_url`. See [`Shard::builder`] for the
+    /// other connection options.
     pub async fn new(
         websocket_url: &str,
         token: &str,
         shard_information: ShardInformation,
         intents: GatewayIntents,
     ) -> Result<Self> {
-        let websocket = WebsocketClient::connect(websocket_url).await?;
-        Ok(Self {
-            websocket_url: websocket_url.to_string(),
+        Self::builder()
+            .url(websocket_url)
+            .token(token)
+            .shard_info(shard_information)
+            .intents(intents)
+            .build()
+            .await
+    }
+
+    /// Configures a shard before connecting it, see [`ShardBuilder`].
+    #[must_use]
+    pub fn builder() -> ShardBuilder {
+        ShardBuilder::default()
+    }
+
+    /// Creates a shard from a connection made by a [`ShardBuilder`]
+    pub(crate) fn with_websocket(
+        gateway_url: GatewayUrl,
+        websocket: WebsocketClient,
+        token: String,
+        shard_information: Option<ShardInformation>,
+        intents: GatewayIntents,
+    ) -> Self {
+        Self {
+            gateway_url,
             websocket,
+            websocket_config: WebsocketClient::default_config(),
+            proxy: None,
             connection_stage: ConnectionStage::Handshake,
+            stage_entered_at: Instant::now(),
+            stage_timings: HashMap::new(),
             heartbeat_interval: None,
+            first_heartbeat_sent: false,
+            first_heartbeat_jitter: 0.0,
+            heartbeat_jitter: random_fraction,
             last_heartbeat_sent: None,
-            last_heartbeat_received: false,
+            // No heartbeat is waiting to be acknowledged yet
+            last_heartbeat_received: true,
+            heartbeat_sent_at: None,
+            last_heartbeat_ack: None,
+            latency: None,
+            last_identify: None,
+            identify_backoff: None,
             sequence: 0,
+            dispatches_since_resume: 0,
             session_id: None,
             resume_url: None,
-            shard_information: Some(shard_information),
-            token: token.to_string(),
+            shard_information,
+            token,
             intents,
-        })
+            large_threshold: None,
+            properties: IdentifyProperties::default(),
+            presence: None,
+            reapply_presence_on_resume: false,
+            reconnect_policy: ReconnectPolicy::default(),
+            session_start_limit: None,
+            identify_rate_limiter: IdentifyRateLimiter::new(),
+            shard_event_sender: None,
+            connection_stage_sender: None,
+            unavailable_guilds: HashSet::new(),
+            cache: Cache::with_settings(CacheSettings::recommended_for(intents)),
+        }
     }
 
     pub async fn init(&mut self) -> Result<()> {
-        self.connection_stage = ConnectionStage::Connecting;
-        let url = self.resume_url.as_ref().unwrap_or(&self.websocket_url);
-        let client = WebsocketClient::connect(url.as_str()).await?;
+        self.set_connection_stage(ConnectionStage::Connecting);
+        // The resume URL is sent without a query, so it takes the query of the gateway URL
+        let url = match &self.resume_url {
+            Some(resume_url) => self.gateway_url.with_host(resume_url),
+            None => self.gateway_url.clone(),
+        };
+        let mut client = WebsocketClient::connect_through(
+            &url.to_string(),
+            url.compression,
+            url.encoding,
+            self.websocket_config,
+            self.proxy.as_ref(),
+        )
+        .await?;
+        client
+            .raw_byte_hook
+            .clone_from(&self.websocket.raw_byte_hook);
         self.websocket = client;
         Ok(())
     }
@@ -116,43 +286,31 @@ impl Shard {
         self.last_heartbeat_sent = Some(Instant::now());
         self.last_heartbeat_received = true;
         self.heartbeat_interval = None;
-        self.connection_stage = ConnectionStage::Disconnected;
-        self.sequence = 0;
+        self.first_heartbeat_sent = false;
+        self.set_connection_stage(ConnectionStage::Disconnected);
+        // A resume replays the events after the last sequence received
         if !resuming {
+            self.sequence = 0;
             self.session_id = None;
             self.resume_url = None;
         }
     }
 
-    pub fn handle_event(&mut self, event: Result<&Event>) -> Result<Option<ShardAction>> {
+    pub fn handle_event(&mut self, event: Result<&IncomingEvent>) -> Result<Option<ShardAction>> {
         match event {
             Ok(event) => {
-                let Some(ref data) = event.receive_data else {
+                let Some(ref data) = event.data else {
                     return Ok(None);
                 };
 
                 match data {
                     ReceiveEventData::Dispatch(data) => {
-                        match data {
-                            DispatchEvent::Ready(ready) => {
-                                self.resume_url = Some(ready.resume_gateway_url.clone());
-                                self.session_id = Some(ready.session_id.clone());
-                                self.connection_stage = ConnectionStage::Connected;
-                                self.last_heartbeat_received = true;
-                            }
-                            DispatchEvent::Resumed => {
-                                self.connection_stage = ConnectionStage::Connected;
-                                self.last_heartbeat_received = true;
-                                self.last_heartbeat_sent = Some(Instant::now());
-                            }
-                            _ => {}
-                        }
                         self.sequence = event.sequence.unwrap_or(self.sequence);
-                        Ok(None)
+                        Ok(self.handle_dispatch(data))
                     }
                     ReceiveEventData::Heartbeat => {
                         if self.connection_stage == ConnectionStage::Handshake {
-                            self.connection_stage = ConnectionStage::Identifying;
+                            self.set_connection_stage(ConnectionStage::Identifying);
                             Ok(Some(ShardAction::Identify))
                         } else {
                             Ok(Some(ShardAction::Heartbeat))
@@ -168,75 +326,1046 @@ impl Shard {
                     })),
                     ReceiveEventData::Hello { heartbeat_interval } => {
                         self.heartbeat_interval = Some(Duration::from_millis(*heartbeat_interval));
+                        self.first_heartbeat_sent = false;
+                        self.first_heartbeat_jitter = (self.heartbeat_jitter)().clamp(0.0, 1.0);
 
-                        Ok(Some(
-                            if self.connection_stage == ConnectionStage::Handshake {
-                                ShardAction::Identify
-                            } else {
-                                ShardAction::Reconnect(if self.resume_url.is_some() {
-                                    ReconnectionKind::Resume
-                                } else {
-                                    ReconnectionKind::Identify
-                                })
-                            },
-                        ))
+                        // A resume is sent as soon as we reconnect, so only a new session
+                        // that hasn't been forced to identify needs to once the gateway
+                        // says hello
+                        if matches!(
+                            self.connection_stage,
+                            ConnectionStage::Resuming | ConnectionStage::Identifying
+                        ) {
+                            Ok(None)
+                        } else if self.session_id.is_some() {
+                            // The session was restored, so it is resumed rather than
+                            // replaced, falling back to identifying if it is invalid
+                            Ok(Some(ShardAction::Resume))
+                        } else {
+                            Ok(Some(ShardAction::Identify))
+                        }
                     }
                     ReceiveEventData::HeartbeatAck => {
                         self.last_heartbeat_received = true;
+                        let now = Instant::now();
+                        self.last_heartbeat_ack = Some(now);
+                        if let Some(sent_at) = self.heartbeat_sent_at.take() {
+                            self.latency = Some(now - sent_at);
+                        }
                         Ok(None)
                     }
                 }
             }
-            // TODO: handle gateway being closed
+            Err(Error::Gateway(GatewayError::Closed(Some(CloseCode::RateLimited))))
+                if self.identified_recently() =>
+            {
+                // Reconnecting straight away would identify again and worsen the rate limit
+                self.identify_backoff = Some(self.identify_rate_limit_backoff());
+                Ok(Some(ShardAction::Reconnect(ReconnectionKind::Identify)))
+            }
+            Err(Error::Gateway(GatewayError::Closed(Some(code)))) => {
+                if !code.is_reconnectable() {
+                    return Ok(Some(ShardAction::Disconnect(code)));
+                }
+
+                let resumable = self.session_id.is_some()
+                    && !matches!(
+                        code,
+                        CloseCode::InvalidSequence | CloseCode::SessionTimedOut
+                    );
+                Ok(Some(ShardAction::Reconnect(if resumable {
+                    ReconnectionKind::Resume
+                } else {
+                    ReconnectionKind::Identify
+                })))
+            }
+            // The connection was closed without a close code, such as by the network, so
+            // nothing suggests the session is invalid
+            Err(Error::Gateway(GatewayError::Closed(None))) => {
+                Ok(Some(ShardAction::Reconnect(self.reconnection_kind())))
+            }
+            // A dispatch the models don't match is skipped, but still counted so that the
+            // session can be resumed
+            Err(Error::Gateway(GatewayError::Decode(GatewayDecodeError::InvalidDispatch(
+                event,
+                sequence,
+                err,
+            )))) => {
+                warn!(
+                    "skipping {} dispatch event that could not be decoded: {err}",
+                    event.as_deref().unwrap_or("unnamed")
+                );
+                self.sequence = sequence.unwrap_or(self.sequence);
+                Ok(None)
+            }
             Err(err) => Err(err),
         }
     }
 
+    fn handle_dispatch(&mut self, event: &DispatchEvent) -> Option<ShardAction> {
+        self.cache.update(event);
+        if *event != DispatchEvent::Resumed {
+            self.dispatches_since_resume += 1;
+        }
+        match event {
+            DispatchEvent::Ready(ready) => {
+                if ready.v != u16::from(self.gateway_url.version) {
+                    warn!(
+                        "requested gateway version {}, but the gateway is using version {}",
+                        self.gateway_url.version, ready.v
+                    );
+                }
+                self.resume_url = Some(ready.resume_gateway_url.clone());
+                self.session_id = Some(ready.session_id.clone());
+                self.set_connection_stage(ConnectionStage::Connected);
+                self.last_heartbeat_received = true;
+                self.reconnect_policy.reset();
+                self.unavailable_guilds = ready.guilds.iter().map(|guild| guild.id).collect();
+            }
+            DispatchEvent::Resumed => {
+                self.send_shard_event(ShardEvent::Resumed {
+                    replayed: self.dispatches_since_resume,
+                });
+                self.set_connection_stage(ConnectionStage::Connected);
+                self.last_heartbeat_received = true;
+                self.last_heartbeat_sent = Some(Instant::now());
+                self.reconnect_policy.reset();
+                if self.reapply_presence_on_resume && self.presence.is_some() {
+                    return Some(ShardAction::ReapplyPresence);
+                }
+            }
+            DispatchEvent::GuildCreate(guild_create) => {
+                let id = guild_create.0.id;
+                let event = if self.unavailable_guilds.remove(&id) {
+                    ShardEvent::GuildAvailable(id)
+                } else {
+                    ShardEvent::GuildJoined(id)
+                };
+                self.send_shard_event(event);
+            }
+            DispatchEvent::GuildDelete(guild_delete) if guild_delete.is_outage() => {
+                // The guild is sent again with `GUILD_CREATE` once the outage is over
+                self.unavailable_guilds.insert(guild_delete.0.id);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// The gateway API version the shard connects to, see [`ShardBuilder::gateway_version`]
+    #[must_use]
+    pub fn gateway_version(&self) -> u8 {
+        self.gateway_url.version
+    }
+
+    /// The URL the shard connects to, which resumes connect to on the session's host
+    #[must_use]
+    pub fn gateway_url(&self) -> &GatewayUrl {
+        &self.gateway_url
+    }
+
+    /// The ID of the current session, which is `None` until the gateway sends `READY`
+    #[must_use]
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    /// The URL the current session is resumed on, which is `None` until the gateway
+    /// sends `READY`
+    #[must_use]
+    pub fn resume_url(&self) -> Option<&str> {
+        self.resume_url.as_deref()
+    }
+
+    /// The state needed to resume the current session after the process restarts, which
+    /// is `None` until the gateway sends `READY`
+    #[must_use]
+    pub fn session_state(&self) -> Option<SessionState> {
+        Some(SessionState {
+            session_id: self.session_id.clone()?,
+            resume_url: self.resume_url.clone()?,
+            sequence: self.sequence,
+        })
+    }
+
+    /// Restores a session saved with [`Shard::session_state`], which is resumed once the
+    /// gateway says hello.
+    pub(crate) fn restore_session(&mut self, state: SessionState) {
+        self.session_id = Some(state.session_id);
+        self.resume_url = Some(state.resume_url);
+        self.sequence = state.sequence;
+    }
+
+    /// The number of dispatch events received since the session was last resumed, or
+    /// since the shard was created if it never was. This includes the replayed events.
+    #[must_use]
+    pub fn dispatches_since_resume(&self) -> u64 {
+        self.dispatches_since_resume
+    }
+
+    /// The stage of the connection to the gateway
+    #[must_use]
+    pub fn connection_stage(&self) -> ConnectionStage {
+        self.connection_stage
+    }
+
+    /// The time spent in each stage since the shard last started connecting, which shows
+    /// whether a slow startup is due to the handshake or to identifying. The current stage
+    /// is only included once the shard leaves it.
+    #[must_use]
+    pub fn stage_timings(&self) -> &HashMap<ConnectionStage, Duration> {
+        &self.stage_timings
+    }
+
+    fn set_connection_stage(&mut self, stage: ConnectionStage) {
+        let now = Instant::now();
+        let elapsed = now - self.stage_entered_at;
+        if stage == ConnectionStage::Connecting {
+            self.stage_timings.clear();
+        } else {
+            *self.stage_timings.entry(self.connection_stage).or_default() += elapsed;
+        }
+        if stage != self.connection_stage {
+            debug!(
+                "connection stage {:?} -> {stage:?} after {elapsed:?}",
+                self.connection_stage
+            );
+            if let Some(sender) = &self.connection_stage_sender {
+                let _ = sender.send(stage);
+            }
+        }
+        self.connection_stage = stage;
+        self.stage_entered_at = now;
+    }
+
+    fn send_shard_event(&self, event: ShardEvent) {
+        if let Some(sender) = &self.shard_event_sender {
+            let _ = sender.send(event);
+        }
+    }
+
+    fn identified_recently(&self) -> bool {
+        self.last_identify
+            .is_some_and(|last_identify| last_identify.elapsed() <= IDENTIFY_INTERVAL)
+    }
+
+    fn identify_rate_limit_backoff(&self) -> Duration {
+        match self.session_start_limit {
+            Some(limit) if limit.remaining == 0 => Duration::from_millis(limit.reset_after),
+            _ => IDENTIFY_INTERVAL,
+        }
+    }
+
+    /// The delay to wait before the next reconnect attempt. This is the identify rate limit
+    /// backoff if the gateway rate limited our last identify, otherwise it is taken from
+    /// the [`ReconnectPolicy`].
+    pub fn reconnect_delay(&mut self) -> Duration {
+        match self.identify_backoff.take() {
+            Some(backoff) => backoff.max(self.reconnect_policy.next_delay()),
+            None => self.reconnect_policy.next_delay(),
+        }
+    }
+
     pub async fn heartbeat(&mut self) -> Result<()> {
         self.websocket.send_heartbeat(Some(self.sequence)).await?;
-        self.last_heartbeat_sent = Some(Instant::now());
+        let now = Instant::now();
+        self.last_heartbeat_sent = Some(now);
+        self.heartbeat_sent_at = Some(now);
         self.last_heartbeat_received = false;
+        self.first_heartbeat_sent = true;
         Ok(())
     }
 
-    pub async fn do_heartbeat_interval(&mut self) -> bool {
-        let Some(heartbeat_interval) = self.heartbeat_interval else {
-            return true;
+    /// The round trip time of the last acknowledged heartbeat. This is kept when a
+    /// heartbeat is not acknowledged, and is `None` until the first acknowledgement.
+    #[must_use]
+    pub fn latency(&self) -> Option<Duration> {
+        self.latency
+    }
+
+    /// When the gateway last acknowledged a heartbeat
+    #[must_use]
+    pub fn last_heartbeat_ack(&self) -> Option<Instant> {
+        self.last_heartbeat_ack
+    }
+
+    /// The time to wait after the last heartbeat before sending the next one. The first
+    /// heartbeat after `HELLO` is sent after a random fraction of the interval, as
+    /// recommended by Discord.
+    #[must_use]
+    pub fn next_heartbeat_interval(&self) -> Option<Duration> {
+        let interval = self.heartbeat_interval?;
+        Some(if self.first_heartbeat_sent {
+            interval
+        } else {
+            interval.mul_f64(self.first_heartbeat_jitter)
+        })
+    }
+
+    /// Sends a heartbeat once the heartbeat interval has passed. A connection whose last
+    /// heartbeat wasn't acknowledged within the interval, or that a heartbeat can't be sent
+    /// on, is dead even if it hasn't been closed, so a reconnect is returned instead.
+    pub async fn do_heartbeat_interval(&mut self) -> Result<Option<ShardAction>> {
+        let Some(heartbeat_interval) = self.next_heartbeat_interval() else {
+            return Ok(None);
         };
 
         if let Some(last_sent) = self.last_heartbeat_sent {
             if last_sent.elapsed() <= heartbeat_interval {
-                return true;
+                return Ok(None);
             }
         }
 
         if !self.last_heartbeat_received {
-            return false;
+            return Ok(Some(ShardAction::Reconnect(self.reconnection_kind())));
         }
 
-        self.heartbeat().await.is_ok()
+        match self.heartbeat().await {
+            Err(Error::Websocket(_)) => Ok(Some(ShardAction::Reconnect(self.reconnection_kind()))),
+            result => result.map(|()| None),
+        }
+    }
+
+    /// How to reconnect after losing the connection, resuming if a session was started
+    fn reconnection_kind(&self) -> ReconnectionKind {
+        if self.session_id.is_some() {
+            ReconnectionKind::Resume
+        } else {
+            ReconnectionKind::Identify
+        }
     }
 
     pub async fn identify(&mut self) -> Result<()> {
+        for warning in self.intent_warnings() {
+            warn!("{warning}");
+        }
+        let privileged = self.intents.privileged_subset();
+        if !privileged.is_empty() {
+            warn!(
+                "identifying with the privileged intents {privileged}, which must be enabled \
+                for the application in the developer portal"
+            );
+        }
+
+        self.identify_rate_limiter.wait().await;
         self.websocket
-            .send_identify(&self.token, &self.shard_information, &self.intents)
+            .send_identify(
+                &self.token,
+                &self.shard_information,
+                &self.intents,
+                &self.presence,
+                self.large_threshold,
+                &self.properties,
+            )
             .await?;
 
         self.last_heartbeat_sent = Some(Instant::now());
-        self.connection_stage = ConnectionStage::Identifying;
+        self.last_identify = Some(Instant::now());
+        self.set_connection_stage(ConnectionStage::Identifying);
+        if let Some(limit) = self.session_start_limit.as_mut() {
+            limit.remaining = limit.remaining.saturating_sub(1);
+        }
+
+        Ok(())
+    }
+
+    /// The number of guilds the shard is in, including those that are unavailable. Guilds
+    /// that are available are only counted if guilds are cached, see [`CacheSettings`].
+    #[must_use]
+    pub fn guild_count(&self) -> usize {
+        // A guild that became unavailable due to an outage is also still cached
+        let uncached_unavailable = self
+            .unavailable_guilds
+            .iter()
+            .filter(|id| self.cache.guild(**id).is_none())
+            .count();
+        self.cache.guild_count() + uncached_unavailable
+    }
+
+    /// The caches enabled on [`Shard::cache`] that can't be filled with the requested
+    /// intents, which are logged before identifying
+    #[must_use]
+    pub fn intent_warnings(&self) -> Vec<CacheIntentWarning> {
+        self.cache.settings().intent_warnings(self.intents)
+    }
 
+    /// Updates the client's presence, which is also sent when identifying in the future.
+    pub async fn update_presence(&mut self, presence: PresenceUpdate) -> Result<()> {
+        self.websocket.send_presence_update(&presence).await?;
+        self.presence = Some(presence);
         Ok(())
     }
 
+    /// Sends [`Shard::presence`] again, if one has been set.
+    pub async fn reapply_presence(&mut self) -> Result<()> {
+        match &self.presence {
+            Some(presence) => self.websocket.send_presence_update(presence).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Requests members of a guild, which are sent back in `GUILD_MEMBERS_CHUNK` events.
+    /// Requesting all members of a guild requires the
+    /// [`GatewayIntents::GUILD_MEMBERS`] intent.
+    pub async fn request_guild_members(
+        &mut self,
+        guild_id: Snowflake,
+        filter: GuildMembersFilter,
+        presences: Option<bool>,
+        nonce: Option<String>,
+    ) -> Result<()> {
+        self.websocket
+            .send_request_guild_members(guild_id, filter, presences, nonce)
+            .await
+    }
+
+    /// Requests the soundboard sounds of a set of guilds, which are sent back in
+    /// `SOUNDBOARD_SOUNDS` events.
+    pub async fn request_soundboard_sounds(&mut self, guild_ids: Vec<Snowflake>) -> Result<()> {
+        self.websocket
+            .send_request_soundboard_sounds(guild_ids)
+            .await
+    }
+
+    /// Joins, moves between or leaves (with a `channel_id` of `None`) a voice channel.
+    pub async fn update_voice_state(&mut self, voice_state: VoiceStateUpdateData) -> Result<()> {
+        self.websocket.send_voice_state_update(&voice_state).await
+    }
+
+    /// Discards the session and reconnects to the gateway URL to identify, even if the
+    /// session could be resumed. This recovers a shard whose resumes keep failing.
+    /// The identify still waits for [`Shard::identify_rate_limiter`].
+    pub async fn force_identify(&mut self) -> Result<()> {
+        self.reset(false);
+        self.init().await?;
+        self.identify().await
+    }
+
     pub async fn resume(&mut self) -> Result<()> {
         self.init().await?;
-        self.connection_stage = ConnectionStage::Resuming;
+        self.send_resume().await
+    }
+
+    /// Resumes the session on the current connection, rather than reconnecting first like
+    /// [`Shard::resume`].
+    pub async fn send_resume(&mut self) -> Result<()> {
+        self.set_connection_stage(ConnectionStage::Resuming);
 
         let Some(ref session_id) = self.session_id else {
             return Err(Error::Gateway(GatewayError::NoSessionToResume));
         };
+        self.dispatches_since_resume = 0;
         self.websocket
             .send_resume(&self.token, session_id.as_str(), self.sequence)
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+    use tokio::{sync::mpsc::unbounded_channel, time::Instant};
+
+    use super::{
+        ConnectionStage, ReconnectionKind, SessionState, Shard, ShardAction, ShardEvent,
+        ShardInformation, IDENTIFY_INTERVAL,
+    };
+    use crate::{
+        cache::{Cache, CacheSettings},
+        error::Error,
+        gateway::{
+            compression::TransportCompression,
+            error::{CloseCode, Error as GatewayError},
+            mock::{user_payload, MockGateway},
+            ratelimit::IdentifyRateLimiter,
+            reconnect::ReconnectPolicy,
+        },
+        model::{
+            gateway::{
+                event::IncomingEvent,
+                intents::GatewayIntents,
+                presence::{Activity, PresenceUpdate, StatusType},
+                session::SessionStartLimit,
+            },
+            snowflake::Snowflake,
+        },
+    };
+
+    async fn shard(gateway: &MockGateway) -> Shard {
+        let mut shard = Shard::new(
+            &gateway.url,
+            "token",
+            ShardInformation { id: 0, total: 1 },
+            GatewayIntents::non_privileged(),
+        )
+        .await
+        .unwrap();
+        shard.reconnect_policy = ReconnectPolicy::new(
+            Duration::from_secs(1),
+            Duration::from_secs(64),
+            Duration::ZERO,
+        );
+        shard
+    }
+
+    fn closed(code: u16) -> Error {
+        Error::Gateway(GatewayError::Closed(Some(CloseCode::from(code))))
+    }
+
+    #[tokio::test]
+    async fn rate_limited_after_identify_takes_identify_backoff() {
+        let mut gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+
+        shard.identify().await.unwrap();
+        assert_eq!(gateway.next_sent_json().await.unwrap()["op"], 2);
+
+        let action = shard.handle_event(Err(closed(4008))).unwrap();
+        assert_eq!(
+            action,
+            Some(ShardAction::Reconnect(ReconnectionKind::Identify))
+        );
+        assert_eq!(shard.reconnect_delay(), IDENTIFY_INTERVAL);
+        assert_eq!(shard.reconnect_delay(), Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn rate_limited_after_identify_respects_session_start_limit() {
+        let gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+        shard.session_start_limit = Some(SessionStartLimit {
+            total: 1000,
+            remaining: 1,
+            reset_after: 30_000,
+            max_concurrency: 1,
+        });
+
+        shard.identify().await.unwrap();
+        shard.handle_event(Err(closed(4008))).unwrap();
+        assert_eq!(shard.reconnect_delay(), Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn rate_limited_without_identify_reconnects_quickly() {
+        let gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+
+        let action = shard.handle_event(Err(closed(4008))).unwrap();
+        assert_eq!(
+            action,
+            Some(ShardAction::Reconnect(ReconnectionKind::Identify))
+        );
+        assert_eq!(shard.reconnect_delay(), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn fatal_close_codes_disconnect() {
+        let gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+
+        for code in [4004, 4014] {
+            let action = shard.handle_event(Err(closed(code))).unwrap();
+            assert_eq!(action, Some(ShardAction::Disconnect(CloseCode::from(code))));
+        }
+    }
+
+    #[tokio::test]
+    async fn shards_sharing_a_limiter_identify_apart() {
+        let mut gateway = MockGateway::bind().await;
+        let limiter = IdentifyRateLimiter::new();
+        let mut shards = Vec::new();
+        for _ in 0..2 {
+            let mut shard = shard(&gateway).await;
+            shard.identify_rate_limiter = limiter.clone();
+            shards.push(shard);
+        }
+
+        // Time only passes while every task waits, so the default interval isn't waited out
+        tokio::time::pause();
+        let tasks: Vec<_> = shards
+            .into_iter()
+            .map(|mut shard| {
+                tokio::spawn(async move { shard.identify().await.map(|()| Instant::now()) })
+            })
+            .collect();
+        let mut identified = Vec::new();
+        for task in tasks {
+            identified.push(task.await.unwrap().unwrap());
+        }
+        identified.sort();
+        tokio::time::resume();
+
+        for _ in 0..2 {
+            let payload = gateway.next_sent_json().await.unwrap();
+            assert_eq!(payload["op"], 2);
+        }
+        assert!(identified[1] - identified[0] >= IDENTIFY_INTERVAL);
+    }
+
+    #[tokio::test]
+    async fn identify_sends_presence() {
+        let mut gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+        shard.presence = Some(PresenceUpdate {
+            since: None,
+            activities: vec![Activity::playing("with the gateway")],
+            status: StatusType::Idle,
+            afk: false,
+        });
+
+        shard.identify().await.unwrap();
+        let identify = gateway.next_sent_json().await.unwrap();
+        assert_eq!(identify["d"]["presence"]["status"], "idle");
+        assert_eq!(
+            identify["d"]["presence"]["activities"][0]["name"],
+            "with the gateway"
+        );
+    }
+
+    #[tokio::test]
+    async fn reset_keeps_sequence_when_resuming() {
+        let gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+        shard.sequence = 42;
+        shard.session_id = Some("session".to_string());
+
+        shard.reset(true);
+        assert_eq!(shard.sequence, 42);
+        assert_eq!(shard.session_id.as_deref(), Some("session"));
+
+        shard.reset(false);
+        assert_eq!(shard.sequence, 0);
+        assert_eq!(shard.session_id, None);
+    }
+
+    #[tokio::test]
+    async fn init_appends_the_query_to_the_resume_url() {
+        let gateway = MockGateway::bind().await;
+        let mut resume_gateway = MockGateway::bind().await;
+        let mut shard = Shard::builder()
+            .url(&gateway.url)
+            .gateway_version(9)
+            .compression(TransportCompression::ZlibStream)
+            .build()
+            .await
+            .unwrap();
+        shard.resume_url = Some(resume_gateway.url.clone());
+
+        shard.init().await.unwrap();
+        let uri = resume_gateway.next_request_uri().await.unwrap();
+        assert_eq!(uri, "/?v=9&encoding=json&compress=zlib-stream");
+    }
+
+    #[tokio::test]
+    async fn force_identify_discards_resumable_session() {
+        let mut gateway = MockGateway::bind().await;
+        let mut resume_gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+        shard.session_id = Some("session".to_string());
+        shard.resume_url = Some(resume_gateway.url.clone());
+
+        shard.force_identify().await.unwrap();
+        let identify = gateway.next_sent_json().await.unwrap();
+        assert_eq!(identify["op"], 2);
+        assert!(resume_gateway.next_sent().await.is_none());
+        assert_eq!(shard.session_id, None);
+
+        // The gateway's hello doesn't lead to a second identify
+        let hello: IncomingEvent =
+            serde_json::from_value(json!({ "op": 10, "d": { "heartbeat_interval": 40000 } }))
+                .unwrap();
+        assert_eq!(shard.handle_event(Ok(&hello)).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn warns_when_caching_members_without_guild_members() {
+        let gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+        assert!(shard.intent_warnings().is_empty());
+
+        shard.cache = Cache::with_settings(CacheSettings::default());
+        let warnings = shard.intent_warnings();
+        assert!(warnings
+            .iter()
+            .any(|warning| warning.missing == GatewayIntents::GUILD_MEMBERS));
+    }
+
+    #[tokio::test]
+    async fn update_presence_after_ready() {
+        let mut gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+
+        gateway.send_ready();
+        let ready = loop {
+            if let Some(event) = shard.websocket.receive().await.unwrap() {
+                break event;
+            }
+        };
+        shard.handle_event(Ok(&ready)).unwrap();
+
+        shard
+            .update_presence(PresenceUpdate {
+                since: None,
+                activities: Vec::new(),
+                status: StatusType::Dnd,
+                afk: false,
+            })
+            .await
+            .unwrap();
+        let update = gateway.next_sent_json().await.unwrap();
+        assert_eq!(update["op"], 3);
+        assert_eq!(update["d"]["status"], "dnd");
+        assert_eq!(shard.presence.unwrap().status, StatusType::Dnd);
+    }
+
+    #[tokio::test]
+    async fn presence_is_reapplied_after_resume() {
+        let mut gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+        let resumed: IncomingEvent = serde_json::from_value(json!({
+            "op": 0,
+            "s": 5,
+            "t": "RESUMED",
+            "d": null,
+        }))
+        .unwrap();
+
+        shard.presence = Some(PresenceUpdate {
+            since: None,
+            activities: Vec::new(),
+            status: StatusType::Idle,
+            afk: false,
+        });
+        assert_eq!(shard.handle_event(Ok(&resumed)).unwrap(), None);
+
+        shard.reapply_presence_on_resume = true;
+        assert_eq!(
+            shard.handle_event(Ok(&resumed)).unwrap(),
+            Some(ShardAction::ReapplyPresence)
+        );
+        shard.reapply_presence().await.unwrap();
+        let update = gateway.next_sent_json().await.unwrap();
+        assert_eq!(update["op"], 3);
+        assert_eq!(update["d"]["status"], "idle");
+    }
+
+    #[tokio::test]
+    async fn first_heartbeat_is_jittered() {
+        let gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+        shard.heartbeat_jitter = || 0.25;
+        let hello: IncomingEvent =
+            serde_json::from_value(json!({ "op": 10, "d": { "heartbeat_interval": 40000 } }))
+                .unwrap();
+
+        shard.handle_event(Ok(&hello)).unwrap();
+        let first = shard.next_heartbeat_interval().unwrap();
+        assert_eq!(first, Duration::from_secs(10));
+
+        shard.heartbeat().await.unwrap();
+        let steady = shard.next_heartbeat_interval().unwrap();
+        assert_eq!(steady, Duration::from_secs(40));
+        assert!(first < steady);
+    }
+
+    #[tokio::test]
+    async fn stage_timings_are_recorded_while_connecting() {
+        let mut gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+        let hello: IncomingEvent =
+            serde_json::from_value(json!({ "op": 10, "d": { "heartbeat_interval": 40000 } }))
+                .unwrap();
+        tokio::time::pause();
+
+        tokio::time::advance(Duration::from_millis(20)).await;
+        assert_eq!(
+            shard.handle_event(Ok(&hello)).unwrap(),
+            Some(ShardAction::Identify)
+        );
+        shard.identify().await.unwrap();
+        assert_eq!(gateway.next_sent_json().await.unwrap()["op"], 2);
+        assert_eq!(shard.connection_stage(), ConnectionStage::Identifying);
+
+        tokio::time::advance(Duration::from_millis(50)).await;
+        gateway.send_ready();
+        let ready = loop {
+            if let Some(event) = shard.websocket.receive().await.unwrap() {
+                break event;
+            }
+        };
+        shard.handle_event(Ok(&ready)).unwrap();
+        assert_eq!(shard.connection_stage(), ConnectionStage::Connected);
+
+        let timings = shard.stage_timings();
+        assert!(timings[&ConnectionStage::Handshake] >= Duration::from_millis(20));
+        assert!(timings[&ConnectionStage::Identifying] >= Duration::from_millis(50));
+        assert!(!timings.contains_key(&ConnectionStage::Connected));
+    }
+
+    #[tokio::test]
+    async fn connection_stages_are_sent_while_connecting() {
+        let mut gateway = MockGateway::bind().await;
+        let (stage_sender, mut stages) = unbounded_channel();
+        let mut shard = Shard::builder()
+            .url(&gateway.url)
+            .token("token")
+            .shard_info(ShardInformation { id: 0, total: 1 })
+            .proxy(None)
+            .connection_stage_sender(stage_sender)
+            .build()
+            .await
+            .unwrap();
+
+        gateway.send_json(&json!({ "op": 10, "d": { "heartbeat_interval": 40000 } }));
+        while shard.connection_stage() != ConnectionStage::Connected {
+            let Some(event) = shard.websocket.receive().await.unwrap() else {
+                continue;
+            };
+            if shard.handle_event(Ok(&event)).unwrap() == Some(ShardAction::Identify) {
+                shard.identify().await.unwrap();
+                assert_eq!(gateway.next_sent_json().await.unwrap()["op"], 2);
+                gateway.send_ready();
+            }
+        }
+        let mut received = Vec::new();
+        while let Ok(stage) = stages.try_recv() {
+            received.push(stage);
+        }
+
+        assert_eq!(
+            received,
+            [
+                ConnectionStage::Connecting,
+                ConnectionStage::Handshake,
+                ConnectionStage::Identifying,
+                ConnectionStage::Connected,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn missed_heartbeat_ack_reconnects() {
+        let mut gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+        shard.heartbeat_jitter = || 0.0;
+        shard.session_id = Some("session".to_string());
+        let hello: IncomingEvent =
+            serde_json::from_value(json!({ "op": 10, "d": { "heartbeat_interval": 1000 } }))
+                .unwrap();
+        shard.handle_event(Ok(&hello)).unwrap();
+        tokio::time::pause();
+
+        tokio::time::advance(Duration::from_millis(1)).await;
+        assert_eq!(shard.do_heartbeat_interval().await.unwrap(), None);
+        assert_eq!(gateway.next_sent_json().await.unwrap()["op"], 1);
+
+        // The interval hasn't passed yet, so the ACK may still arrive
+        tokio::time::advance(Duration::from_millis(500)).await;
+        assert_eq!(shard.do_heartbeat_interval().await.unwrap(), None);
+
+        tokio::time::advance(Duration::from_millis(501)).await;
+        assert_eq!(
+            shard.do_heartbeat_interval().await.unwrap(),
+            Some(ShardAction::Reconnect(ReconnectionKind::Resume))
+        );
+    }
+
+    #[tokio::test]
+    async fn latency_is_measured_from_heartbeat_ack() {
+        let gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+        let ack: IncomingEvent = serde_json::from_value(json!({ "op": 11, "d": null })).unwrap();
+        tokio::time::pause();
+        assert_eq!(shard.latency(), None);
+
+        shard.heartbeat().await.unwrap();
+        tokio::time::advance(Duration::from_millis(42)).await;
+        shard.handle_event(Ok(&ack)).unwrap();
+        assert_eq!(shard.latency(), Some(Duration::from_millis(42)));
+
+        // A heartbeat that is never acknowledged keeps the previous latency
+        shard.heartbeat().await.unwrap();
+        tokio::time::advance(Duration::from_millis(500)).await;
+        assert_eq!(shard.latency(), Some(Duration::from_millis(42)));
+    }
+
+    fn guild_create(id: &str) -> IncomingEvent {
+        serde_json::from_value(json!({
+            "op": 0,
+            "s": 2,
+            "t": "GUILD_CREATE",
+            "d": {
+                "id": id,
+                "name": "Guild",
+                "owner_id": "80351110224678912",
+                "premium_tier": 0,
+            },
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn guild_create_distinguishes_available_from_joined() {
+        let gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+        let (sender, mut receiver) = unbounded_channel();
+        shard.shard_event_sender = Some(sender);
+
+        let ready: IncomingEvent = serde_json::from_value(json!({
+            "op": 0,
+            "s": 1,
+            "t": "READY",
+            "d": {
+                "v": 10,
+                "user": user_payload(),
+                "guilds": [{ "id": "1", "unavailable": true }],
+                "session_id": "session",
+                "resume_gateway_url": gateway.url,
+                "shard": [0, 1],
+            },
+        }))
+        .unwrap();
+        shard.handle_event(Ok(&ready)).unwrap();
+
+        shard.handle_event(Ok(&guild_create("1"))).unwrap();
+        shard.handle_event(Ok(&guild_create("2"))).unwrap();
+
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            ShardEvent::GuildAvailable(Snowflake(1))
+        );
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            ShardEvent::GuildJoined(Snowflake(2))
+        );
+
+        // A guild that comes back after an outage is available again, not joined
+        let outage: IncomingEvent = serde_json::from_value(json!({
+            "op": 0,
+            "s": 4,
+            "t": "GUILD_DELETE",
+            "d": { "id": "2", "unavailable": true },
+        }))
+        .unwrap();
+        shard.handle_event(Ok(&outage)).unwrap();
+        shard.handle_event(Ok(&guild_create("2"))).unwrap();
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            ShardEvent::GuildAvailable(Snowflake(2))
+        );
+    }
+
+    #[tokio::test]
+    async fn session_id_is_set_by_ready() {
+        let gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+        assert_eq!(shard.session_id(), None);
+        assert_eq!(shard.resume_url(), None);
+
+        let ready: IncomingEvent = serde_json::from_value(json!({
+            "op": 0,
+            "s": 1,
+            "t": "READY",
+            "d": {
+                "v": 10,
+                "user": user_payload(),
+                "guilds": [],
+                "session_id": "session",
+                "resume_gateway_url": "wss://resume.discord.gg",
+            },
+        }))
+        .unwrap();
+        shard.handle_event(Ok(&ready)).unwrap();
+
+        assert_eq!(shard.session_id(), Some("session"));
+        assert_eq!(shard.resume_url(), Some("wss://resume.discord.gg"));
+    }
+
+    #[tokio::test]
+    async fn resumed_reports_the_replayed_events() {
+        let gateway = MockGateway::bind().await;
+        let mut shard = shard(&gateway).await;
+        let (sender, mut receiver) = unbounded_channel();
+        shard.shard_event_sender = Some(sender);
+        shard.session_id = Some("session".to_string());
+        shard.resume_url = Some(gateway.url.clone());
+
+        shard.resume().await.unwrap();
+        assert_eq!(shard.dispatches_since_resume(), 0);
+        shard.handle_event(Ok(&guild_create("1"))).unwrap();
+        shard.handle_event(Ok(&guild_create("2"))).unwrap();
+        let resumed: IncomingEvent =
+            serde_json::from_value(json!({ "op": 0, "s": 3, "t": "RESUMED", "d": null })).unwrap();
+        shard.handle_event(Ok(&resumed)).unwrap();
+
+        let events: Vec<_> = std::iter::from_fn(|| receiver.try_recv().ok()).collect();
+        assert_eq!(events.last(), Some(&ShardEvent::Resumed { replayed: 2 }));
+        assert_eq!(shard.dispatches_since_resume(), 2);
+    }
+
+    #[test]
+    fn session_state_round_trips() {
+        let state = SessionState {
+            session_id: "session".to_string(),
+            resume_url: "wss://resume.discord.gg".to_string(),
+            sequence: 42,
+        };
+        let value = serde_json::to_value(&state).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "session_id": "session",
+                "resume_url": "wss://resume.discord.gg",
+                "sequence": 42,
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<SessionState>(value).unwrap(),
+            state
+        );
+    }
+
+    #[tokio::test]
+    async fn restored_session_resumes_after_hello() {
+        let gateway = MockGateway::bind().await;
+        let mut resume_gateway = MockGateway::bind().await;
+        let mut shard = Shard::builder()
+            .url(&gateway.url)
+            .token("token")
+            .resume_from(SessionState {
+                session_id: "session".to_string(),
+                resume_url: resume_gateway.url.clone(),
+                sequence: 42,
+            })
+            .build()
+            .await
+            .unwrap();
+        assert!(resume_gateway.next_request_uri().await.is_some());
+
+        let hello: IncomingEvent = serde_json::from_value(json!({
+            "op": 10,
+            "d": { "heartbeat_interval": 45000 },
+        }))
+        .unwrap();
+        assert_eq!(
+            shard.handle_event(Ok(&hello)).unwrap(),
+            Some(ShardAction::Resume)
+        );
+
+        shard.send_resume().await.unwrap();
+        let resume = resume_gateway.next_sent_json().await.unwrap();
+        assert_eq!(resume["op"], 6);
+        assert_eq!(resume["d"]["session_id"], "session");
+        assert_eq!(resume["d"]["seq"], 42);
+
+        // A session that can't be resumed falls back to identifying
+        let invalid: IncomingEvent =
+            serde_json::from_value(json!({ "op": 9, "d": false })).unwrap();
+        assert_eq!(
+            shard.handle_event(Ok(&invalid)).unwrap(),
+            Some(ShardAction::Reconnect(ReconnectionKind::Identify))
+        );
+    }
+}
@@ -1,50 +1,1335 @@
-use crate::{error::Result, gateway::shard::ReconnectionKind, model::gateway::event::Event};
+use std::{
+    future::Future,
+    panic::resume_unwind,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering as AtomicOrdering},
+        Arc, PoisonError, RwLock,
+    },
+    task::{ready, Context, Poll},
+};
 
-use super::shard::{Shard, ShardAction};
+use futures::Stream;
+use tokio::{
+    sync::{
+        mpsc::{channel, unbounded_channel, Receiver, Sender, UnboundedReceiver, UnboundedSender},
+        watch,
+    },
+    task::{JoinHandle, JoinSet},
+};
+
+use crate::{
+    error::{Error, Result},
+    gateway::shard::ReconnectionKind,
+    model::gateway::{
+        dispatch::DispatchEvent,
+        event::{IncomingEvent, ReceiveEventData},
+        intents::GatewayIntents,
+        session::SessionStartLimit,
+    },
+};
+
+use super::{
+    error::{CloseCode, Error as GatewayError},
+    ratelimit::BucketedIdentifyLimiter,
+    shard::{Shard, ShardAction, ShardEvent, ShardInformation},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+/// A dispatch event with the shard that received it and its sequence number.
+///
+/// Buffered dispatches can be sorted back into the order each shard received them with
+/// [`SequencedDispatch::sort_key`].
+pub struct SequencedDispatch {
+    /// The ID of the shard that received the event
+    pub shard_id: u64,
+    /// The sequence number of the event within the shard's session
+    pub sequence: u64,
+    /// The event
+    pub event: DispatchEvent,
+}
+
+impl SequencedDispatch {
+    /// The shard ID, then the sequence number. Sequence numbers are only ordered within
+    /// the session of a single shard, so sorting by this keeps each shard's dispatches
+    /// together, such as with `dispatches.sort_by_key(SequencedDispatch::sort_key)`.
+    #[must_use]
+    pub fn sort_key(&self) -> (u64, u64) {
+        (self.shard_id, self.sequence)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Everything a [`ShardManager`] delivers through [`ShardManager::events`]
+pub enum Incoming {
+    /// A dispatch event sent by the gateway
+    Dispatch(SequencedDispatch),
+    /// An event derived by the shard, see [`ShardEvent`]
+    Control(ShardEvent),
+}
+
+#[derive(Debug, Clone)]
+/// Stops a running [`ShardManager`] and reads its totals, created with
+/// [`ShardManager::handle`]
+pub struct ShardHandle {
+    shutdown: Arc<watch::Sender<bool>>,
+    totals: Arc<RwLock<Vec<Arc<ShardTotals>>>>,
+}
+
+impl ShardHandle {
+    /// Makes the manager close its connection with the normal closure code, which ends
+    /// the session, and return from [`ShardManager::run`] with `Ok(())`.
+    pub fn shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    /// The number of guilds the managed shards are in, see [`ShardManager::total_guilds`].
+    /// Unlike the manager's, this is kept up to date while [`ShardManager::run`] runs.
+    #[must_use]
+    pub fn total_guilds(&self) -> usize {
+        self.totals
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|totals| totals.guilds.load(AtomicOrdering::Relaxed))
+            .sum()
+    }
+
+    /// The total number of members in the guilds cached by the managed shards, see
+    /// [`ShardManager::total_members`]. Unlike the manager's, this is kept up to date while
+    /// [`ShardManager::run`] runs.
+    #[must_use]
+    pub fn total_members(&self) -> u64 {
+        self.totals
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|totals| totals.members.load(AtomicOrdering::Relaxed))
+            .sum()
+    }
+}
+
+#[derive(Debug, Default)]
+/// The totals of a single shard, shared between its task and every [`ShardHandle`]
+struct ShardTotals {
+    guilds: AtomicUsize,
+    members: AtomicU64,
+}
+
+impl ShardTotals {
+    fn of(shard: &Shard) -> Self {
+        let totals = Self::default();
+        totals.update(shard);
+        totals
+    }
+
+    fn update(&self, shard: &Shard) {
+        self.guilds
+            .store(shard.guild_count(), AtomicOrdering::Relaxed);
+        self.members
+            .store(shard.cache.member_count(), AtomicOrdering::Relaxed);
+    }
+}
+
+/// The number of dispatch events a [`ShardEvents`] stream buffers before the shard waits
+/// for them to be consumed.
+const STREAM_BUFFER: usize = 64;
+
+/// The gateway URL the shards of [`ShardManager::new`] connect to
+pub const GATEWAY_URL: &str = "wss://gateway.discord.gg/?v=10&encoding=json";
 
 #[derive(Debug)]
+/// Runs a set of shards, each on its own task, and merges the events they receive.
 pub struct ShardManager {
-    pub shard: Shard,
+    /// The managed shards, ordered by ID. They are moved onto their tasks while
+    /// [`ShardManager::run`] runs, and moved back once it returns.
+    pub shards: Vec<Shard>,
+    /// Receives every dispatch event, while the manager keeps handling heartbeats and
+    /// reconnects itself. The manager waits for space in the channel, so a full channel
+    /// holds up the shard that received the event.
+    pub dispatch_sender: Option<Sender<SequencedDispatch>>,
+    incoming_sender: Option<UnboundedSender<Incoming>>,
+    shutdown: Arc<watch::Sender<bool>>,
+    /// The totals of each shard, updated by the shards' tasks while the manager runs
+    totals: Arc<RwLock<Vec<Arc<ShardTotals>>>>,
 }
 
 impl ShardManager {
+    /// Connects `total_shards` shards to the gateway. The shards share an identify rate
+    /// limiter, so they identify one at a time. Use
+    /// [`ShardManager::with_session_start_limit`] to identify several at once, as allowed
+    /// by the bot's session start limit.
+    pub async fn new(total_shards: u64, token: &str, intents: GatewayIntents) -> Result<Self> {
+        Self::with_url(GATEWAY_URL, total_shards, token, intents).await
+    }
+
+    /// Connects `total_shards` shards to the gateway at `url`, see [`ShardManager::new`].
+    pub async fn with_url(
+        url: &str,
+        total_shards: u64,
+        token: &str,
+        intents: GatewayIntents,
+    ) -> Result<Self> {
+        let limiter = BucketedIdentifyLimiter::new(1);
+        Self::with_limiter(url, total_shards, &limiter, None, token, intents).await
+    }
+
+    /// Connects `total_shards` shards to the gateway, letting up to `max_concurrency` of
+    /// them identify at once. Each shard identifies through its bucket of a shared
+    /// [`BucketedIdentifyLimiter`], so shards in different buckets don't wait on each other.
+    ///
+    /// `max_concurrency` is provided by the Get Gateway Bot endpoint as
+    /// [`SessionStartLimit::max_concurrency`].
+    pub async fn with_max_concurrency(
+        total_shards: u64,
+        max_concurrency: u64,
+        token: &str,
+        intents: GatewayIntents,
+    ) -> Result<Self> {
+        let limiter = BucketedIdentifyLimiter::new(max_concurrency);
+        Self::with_limiter(GATEWAY_URL, total_shards, &limiter, None, token, intents).await
+    }
+
+    /// Connects `total_shards` shards to the gateway, using the session start limit returned
+    /// by the Get Gateway Bot endpoint. The shards identify as allowed by
+    /// [`SessionStartLimit::max_concurrency`], see [`ShardManager::with_max_concurrency`],
+    /// and set their [`Shard::session_start_limit`] to back off when rate limited.
+    pub async fn with_session_start_limit(
+        total_shards: u64,
+        session_start_limit: SessionStartLimit,
+        token: &str,
+        intents: GatewayIntents,
+    ) -> Result<Self> {
+        let limiter = BucketedIdentifyLimiter::new(session_start_limit.max_concurrency);
+        Self::with_limiter(
+            GATEWAY_URL,
+            total_shards,
+            &limiter,
+            Some(session_start_limit),
+            token,
+            intents,
+        )
+        .await
+    }
+
+    async fn with_limiter(
+        url: &str,
+        total_shards: u64,
+        limiter: &BucketedIdentifyLimiter,
+        session_start_limit: Option<SessionStartLimit>,
+        token: &str,
+        intents: GatewayIntents,
+    ) -> Result<Self> {
+        let mut shards = Vec::new();
+        for id in 0..total_shards {
+            let shard_information = ShardInformation {
+                id,
+                total: total_shards,
+            };
+            let mut shard = Shard::new(url, token, shard_information, intents).await?;
+            shard.identify_rate_limiter = limiter.limiter(id);
+            shard.session_start_limit = session_start_limit;
+            shards.push(shard);
+        }
+        Ok(Self::from_shards(shards))
+    }
+
+    /// Creates a manager running `shards`, which identify through their own
+    /// [`Shard::identify_rate_limiter`].
+    #[must_use]
+    pub fn from_shards(mut shards: Vec<Shard>) -> Self {
+        sort_by_id(&mut shards);
+        let totals = shards
+            .iter()
+            .map(|shard| Arc::new(ShardTotals::of(shard)))
+            .collect();
+        Self {
+            shards,
+            dispatch_sender: None,
+            incoming_sender: None,
+            shutdown: Arc::new(watch::Sender::new(false)),
+            totals: Arc::new(RwLock::new(totals)),
+        }
+    }
+
+    /// Creates a manager running a single shard.
+    #[must_use]
+    pub fn from_shard(shard: Shard) -> Self {
+        Self::from_shards(vec![shard])
+    }
+
+    /// Returns a handle that stops the manager while it runs, such as from another task.
+    #[must_use]
+    pub fn handle(&self) -> ShardHandle {
+        ShardHandle {
+            shutdown: self.shutdown.clone(),
+            totals: self.totals.clone(),
+        }
+    }
+
+    /// Creates a manager running `shard` that forwards every dispatch event to `sender`.
+    #[must_use]
+    pub fn with_sender(shard: Shard, sender: Sender<SequencedDispatch>) -> Self {
+        let mut manager = Self::from_shard(shard);
+        manager.dispatch_sender = Some(sender);
+        manager
+    }
+
+    /// Creates a manager whose shard identifies through the bucket it belongs to in
+    /// `limiter`. Managers sharing a limiter space out their identifies as allowed by
+    /// the bot's `max_concurrency`, see [`BucketedIdentifyLimiter::new`].
+    #[must_use]
+    pub fn with_identify_limiter(mut shard: Shard, limiter: &BucketedIdentifyLimiter) -> Self {
+        if let Some(shard_information) = shard.shard_information {
+            shard.identify_rate_limiter = limiter.limiter(shard_information.id);
+        }
+        Self::from_shard(shard)
+    }
+
+    /// Returns a receiver of both the dispatch events and the [`ShardEvent`]s of every
+    /// shard, in the order each shard handled them, so that they can be consumed in a
+    /// single loop. A shard event is delivered after the dispatch event it was derived from.
+    ///
+    /// Any [`Shard::shard_event_sender`] that was already set keeps receiving shard events.
+    /// Calling this again replaces the previously returned receiver.
+    pub fn events(&mut self) -> UnboundedReceiver<Incoming> {
+        let (sender, receiver) = unbounded_channel();
+        self.incoming_sender = Some(sender);
+        receiver
+    }
+
+    /// Runs the manager in the background, returning a stream of its dispatch events.
+    /// See [`ShardEvents`].
+    #[must_use]
+    pub fn into_stream(mut self) -> ShardEvents {
+        let (sender, receiver) = channel(STREAM_BUFFER);
+        self.dispatch_sender = Some(sender);
+        ShardEvents {
+            receiver,
+            handle: self.handle(),
+            task: Some(tokio::spawn(async move { self.run().await })),
+        }
+    }
+
+    /// The number of guilds the managed shards are in, see [`Shard::guild_count`]. The
+    /// shards are moved onto their tasks while [`ShardManager::run`] runs, so use
+    /// [`ShardHandle::total_guilds`] to read this in the meantime.
+    #[must_use]
+    pub fn total_guilds(&self) -> usize {
+        self.shards.iter().map(Shard::guild_count).sum()
+    }
+
+    /// The total number of members in the guilds cached by the managed shards, as sent
+    /// with `GUILD_CREATE`. Use [`ShardHandle::total_members`] while
+    /// [`ShardManager::run`] runs.
+    #[must_use]
+    pub fn total_members(&self) -> u64 {
+        self.shards
+            .iter()
+            .map(|shard| shard.cache.member_count())
+            .sum()
+    }
+
+    /// Runs every shard on its own task until the manager is shut down, see
+    /// [`ShardManager::handle`]. An error that stops one shard shuts the others down
+    /// and is returned once they have stopped.
     pub async fn run(&mut self) -> Result<()> {
+        let mut tasks = JoinSet::new();
+        let totals: Vec<_> = self
+            .shards
+            .iter()
+            .map(|shard| Arc::new(ShardTotals::of(shard)))
+            .collect();
+        self.totals
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone_from(&totals);
+        for (shard, totals) in self.shards.drain(..).zip(totals) {
+            let mut runner = ShardRunner::new(
+                shard,
+                self.dispatch_sender.clone(),
+                self.incoming_sender.clone(),
+                self.shutdown.subscribe(),
+                totals,
+            );
+            #[cfg(feature = "tracing")]
+            let span = runner.shard.shard_information.map_or_else(
+                || tracing::info_span!("shard"),
+                |shard| {
+                    tracing::info_span!("shard", shard.id = shard.id, shard.total = shard.total)
+                },
+            );
+            let task = async move {
+                let result = runner.run().await;
+                (runner.into_shard(), result)
+            };
+            #[cfg(feature = "tracing")]
+            let task = tracing::Instrument::instrument(task, span);
+            tasks.spawn(task);
+        }
+
+        let mut first_error = None;
+        while let Some(joined) = tasks.join_next().await {
+            let (shard, result) = match joined {
+                Ok(joined) => joined,
+                Err(err) if err.is_panic() => resume_unwind(err.into_panic()),
+                Err(_) => continue,
+            };
+            self.shards.push(shard);
+            if let Err(err) = result {
+                if first_error.is_none() {
+                    self.shutdown.send_replace(true);
+                    first_error = Some(err);
+                }
+            }
+        }
+        sort_by_id(&mut self.shards);
+
+        match first_error {
+            Some(err) => {
+                // The shutdown was caused by the error, so the manager can run again
+                self.shutdown.send_replace(false);
+                Err(err)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Waits for `future`, or returns `None` once the manager is shut down
+async fn unless_shut_down<T>(
+    shutdown: &mut watch::Receiver<bool>,
+    future: impl Future<Output = T>,
+) -> Option<T> {
+    tokio::select! {
+        output = future => Some(output),
+        Ok(_) = shutdown.wait_for(|shutdown| *shutdown) => None,
+    }
+}
+
+fn sort_by_id(shards: &mut [Shard]) {
+    shards.sort_by_key(|shard| {
+        shard
+            .shard_information
+            .map(|shard_information| shard_information.id)
+    });
+}
+
+#[derive(Debug)]
+/// Runs a single shard of a [`ShardManager`] on its own task
+struct ShardRunner {
+    shard: Shard,
+    dispatch_sender: Option<Sender<SequencedDispatch>>,
+    incoming_sender: Option<UnboundedSender<Incoming>>,
+    /// Receives the shard's events while they are merged into `incoming_sender`
+    shard_events: Option<UnboundedReceiver<ShardEvent>>,
+    /// The shard's own [`Shard::shard_event_sender`], which keeps receiving its events
+    shard_event_sender: Option<UnboundedSender<ShardEvent>>,
+    shutdown: watch::Receiver<bool>,
+    /// The shard's totals, read by [`ShardHandle`]s while the shard runs
+    totals: Arc<ShardTotals>,
+}
+
+impl ShardRunner {
+    fn new(
+        mut shard: Shard,
+        dispatch_sender: Option<Sender<SequencedDispatch>>,
+        incoming_sender: Option<UnboundedSender<Incoming>>,
+        shutdown: watch::Receiver<bool>,
+        totals: Arc<ShardTotals>,
+    ) -> Self {
+        let mut shard_events = None;
+        let mut shard_event_sender = shard.shard_event_sender.clone();
+        if incoming_sender.is_some() {
+            let (sender, receiver) = unbounded_channel();
+            shard_event_sender = shard.shard_event_sender.replace(sender);
+            shard_events = Some(receiver);
+        }
+        Self {
+            shard,
+            dispatch_sender,
+            incoming_sender,
+            shard_events,
+            shard_event_sender,
+            shutdown,
+            totals,
+        }
+    }
+
+    /// Returns the shard with its own [`Shard::shard_event_sender`] restored
+    fn into_shard(mut self) -> Shard {
+        self.shard.shard_event_sender = self.shard_event_sender;
+        self.shard
+    }
+
+    async fn run(&mut self) -> Result<()> {
         loop {
-            if !self.shard.do_heartbeat_interval().await {
-                println!("heartbeat failed");
+            if *self.shutdown.borrow() {
+                return self.shut_down().await;
+            }
+            if let Some(action) = self.shard.do_heartbeat_interval().await? {
+                warn!("heartbeat was not acknowledged, reconnecting");
+                self.perform_unless_shut_down(action).await?;
+                continue;
             }
             let (event, action) = self.receive_event().await?;
 
             if event.is_some() || action.is_some() {
-                if let Some(shard_information) = self.shard.shard_information {
-                    println!(
-                        "Shard {}/{}\n-----\nEvent: {event:?}\nAction: {action:?}\n\n",
-                        shard_information.id, shard_information.total
-                    );
-                }
+                debug!("received {event:?}, action {action:?}");
+            }
+            if let Some(IncomingEvent {
+                data:
+                    Some(ReceiveEventData::Dispatch(
+                        DispatchEvent::Ready(_)
+                        | DispatchEvent::GuildCreate(_)
+                        | DispatchEvent::GuildDelete(_),
+                    )),
+                ..
+            }) = &event
+            {
+                self.totals.update(&self.shard);
+            }
+
+            self.deliver(event).await;
+
+            if let Some(action) = action {
+                self.perform_unless_shut_down(action).await?;
+            }
+        }
+    }
+
+    /// Performs `action`, unless the manager is shut down first. Reconnecting may retry for
+    /// as long as the gateway can't be reached, and identifying may wait on the rate limiter.
+    async fn perform_unless_shut_down(&mut self, action: ShardAction) -> Result<()> {
+        let mut shutdown = self.shutdown.clone();
+        unless_shut_down(&mut shutdown, self.perform(action))
+            .await
+            .unwrap_or(Ok(()))
+    }
+
+    async fn perform(&mut self, action: ShardAction) -> Result<()> {
+        match action {
+            ShardAction::Reconnect(kind) => self.reconnect(kind).await,
+            ShardAction::Heartbeat => self.shard.heartbeat().await,
+            ShardAction::Identify => self.shard.identify().await,
+            ShardAction::Resume => self.shard.send_resume().await,
+            ShardAction::ReapplyPresence => self.shard.reapply_presence().await,
+            ShardAction::Disconnect(CloseCode::DisallowedIntents) => {
+                let privileged = self.shard.intents.privileged_subset();
+                let err = GatewayError::DisallowedIntents(privileged);
+                error!("{err}");
+                Err(err)?
             }
+            ShardAction::Disconnect(code) => {
+                error!(
+                    "gateway closed the connection with {code}, which can't be reconnected from"
+                );
+                Err(GatewayError::Closed(Some(code)))?
+            }
+        }
+    }
 
-            match action {
-                Some(ShardAction::Reconnect(kind)) => {
-                    self.shard.reset(kind == ReconnectionKind::Resume);
-                    match kind {
-                        ReconnectionKind::Resume => self.shard.resume().await?,
-                        ReconnectionKind::Identify => self.shard.identify().await?,
-                    }
+    async fn shut_down(&mut self) -> Result<()> {
+        info!("shutting down");
+        self.shard.reset(false);
+        match self.shard.websocket.close().await {
+            // The connection may already be closed, which leaves nothing to shut down
+            Err(Error::Websocket(_)) | Ok(()) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn reconnect(&mut self, kind: ReconnectionKind) -> Result<()> {
+        loop {
+            let delay = self.shard.reconnect_delay();
+            warn!("reconnecting in {delay:?}");
+            tokio::time::sleep(delay).await;
+
+            self.shard.reset(kind == ReconnectionKind::Resume);
+            let result = match kind {
+                ReconnectionKind::Resume => self.shard.resume().await,
+                // The new connection identifies once the gateway says hello
+                ReconnectionKind::Identify => self.shard.init().await,
+            };
+            match result {
+                Err(err @ (Error::Websocket(_) | Error::Io(_))) => {
+                    warn!("reconnect failed: {err}");
                 }
-                Some(ShardAction::Heartbeat) => self.shard.heartbeat().await?,
-                Some(ShardAction::Identify) => self.shard.identify().await?,
-                None => {}
+                result => return result,
+            }
+        }
+    }
+
+    async fn deliver(&mut self, event: Option<IncomingEvent>) {
+        if let Some(IncomingEvent {
+            data: Some(ReceiveEventData::Dispatch(dispatch)),
+            sequence,
+            ..
+        }) = event
+        {
+            let dispatch = SequencedDispatch {
+                shard_id: self
+                    .shard
+                    .shard_information
+                    .map_or(0, |shard_information| shard_information.id),
+                sequence: sequence.unwrap_or_default(),
+                event: dispatch,
+            };
+            if let Some(sender) = &self.incoming_sender {
+                let _ = sender.send(Incoming::Dispatch(dispatch.clone()));
+            }
+            if let Some(sender) = &self.dispatch_sender {
+                // A full channel holds up the shard, but not its shutdown
+                let _ = unless_shut_down(&mut self.shutdown, sender.send(dispatch)).await;
+            }
+        }
+
+        let Some(shard_events) = self.shard_events.as_mut() else {
+            return;
+        };
+        while let Ok(shard_event) = shard_events.try_recv() {
+            if let Some(sender) = &self.shard_event_sender {
+                let _ = sender.send(shard_event.clone());
+            }
+            if let Some(sender) = &self.incoming_sender {
+                let _ = sender.send(Incoming::Control(shard_event));
             }
         }
     }
 
-    async fn receive_event(&mut self) -> Result<(Option<Event>, Option<ShardAction>)> {
-        let Some(gateway_event) = self.shard.websocket.receive().await? else {
-            return Ok((None, None));
+    async fn receive_event(&mut self) -> Result<(Option<IncomingEvent>, Option<ShardAction>)> {
+        let gateway_event = match self.shard.websocket.receive().await {
+            Ok(Some(gateway_event)) => gateway_event,
+            Ok(None) => return Ok((None, None)),
+            Err(err) => return Ok((None, self.shard.handle_event(Err(err))?)),
         };
         let action = self.shard.handle_event(Ok(&gateway_event))?;
 
         Ok((Some(gateway_event), action))
     }
 }
+
+#[derive(Debug)]
+/// A stream of the dispatch events received by a [`ShardManager`] running in the background,
+/// created with [`ShardManager::into_stream`]. Heartbeats and reconnects are handled by the
+/// manager and are not yielded.
+///
+/// An error that stops the manager, such as a close code that can't be reconnected from,
+/// is yielded as the last item of the stream. Dropping the stream shuts the manager down,
+/// which closes its connection with the normal closure code in the background.
+pub struct ShardEvents {
+    receiver: Receiver<SequencedDispatch>,
+    handle: ShardHandle,
+    task: Option<JoinHandle<Result<()>>>,
+}
+
+impl Stream for ShardEvents {
+    type Item = Result<SequencedDispatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // The channel closes once the manager stops, after every event has been received
+        if let Some(event) = ready!(self.receiver.poll_recv(cx)) {
+            return Poll::Ready(Some(Ok(event)));
+        }
+
+        let Some(task) = self.task.as_mut() else {
+            return Poll::Ready(None);
+        };
+        let result = ready!(Pin::new(task).poll(cx));
+        self.task = None;
+        match result {
+            Ok(Err(err)) => Poll::Ready(Some(Err(err))),
+            Err(err) if err.is_panic() => resume_unwind(err.into_panic()),
+            Ok(Ok(())) | Err(_) => Poll::Ready(None),
+        }
+    }
+}
+
+impl Drop for ShardEvents {
+    fn drop(&mut self) {
+        // The task keeps running until it has closed the connection, ending the session
+        if self.task.is_some() {
+            self.handle.shutdown();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+    use serde_json::json;
+    use tokio::{sync::mpsc::channel, time::timeout};
+    use tokio_tungstenite::tungstenite::{protocol::CloseFrame, Message};
+
+    use super::{Incoming, SequencedDispatch, ShardManager};
+    use crate::{
+        error::Error,
+        gateway::{
+            error::{CloseCode, Error as GatewayError},
+            mock::{user_payload, MockGateway},
+            ratelimit::{BucketedIdentifyLimiter, IdentifyRateLimiter},
+            reconnect::ReconnectPolicy,
+            shard::{Shard, ShardEvent, ShardInformation},
+        },
+        model::{
+            gateway::{
+                dispatch::DispatchEvent, event::IncomingEvent, intents::GatewayIntents,
+                session::SessionStartLimit,
+            },
+            snowflake::Snowflake,
+        },
+    };
+
+    #[tokio::test]
+    async fn run_stops_on_fatal_close_code() {
+        let gateway = MockGateway::bind().await;
+        let mut manager = ShardManager::from_shard(
+            Shard::new(
+                &gateway.url,
+                "token",
+                ShardInformation { id: 0, total: 1 },
+                GatewayIntents::GUILDS | GatewayIntents::MESSAGE_CONTENT,
+            )
+            .await
+            .unwrap(),
+        );
+
+        gateway.send(Message::Close(Some(CloseFrame {
+            code: 4014.into(),
+            reason: "Disallowed intent(s).".into(),
+        })));
+
+        let result = timeout(Duration::from_secs(5), manager.run())
+            .await
+            .expect("run loop should terminate");
+        assert!(matches!(
+            result,
+            Err(Error::Gateway(GatewayError::DisallowedIntents(intents)))
+                if intents == GatewayIntents::MESSAGE_CONTENT
+        ));
+    }
+
+    #[tokio::test]
+    async fn close_without_code_resumes() {
+        let mut gateway = MockGateway::bind().await;
+        let mut manager = ShardManager::from_shard(
+            Shard::new(
+                &gateway.url,
+                "token",
+                ShardInformation { id: 0, total: 1 },
+                GatewayIntents::non_privileged(),
+            )
+            .await
+            .unwrap(),
+        );
+        manager.shards[0].reconnect_policy =
+            ReconnectPolicy::new(Duration::ZERO, Duration::ZERO, Duration::ZERO);
+        tokio::spawn(async move { manager.run().await });
+
+        gateway.send_ready();
+        gateway.send(Message::Close(None));
+
+        let resume = gateway.next_sent_json().await.expect("a resume is sent");
+        assert_eq!(resume["op"], 6);
+        assert_eq!(resume["d"]["session_id"], "session");
+    }
+
+    #[tokio::test]
+    async fn lost_connection_resumes() {
+        let mut gateway = MockGateway::bind().await;
+        let mut manager = ShardManager::from_shard(
+            Shard::new(
+                &gateway.url,
+                "token",
+                ShardInformation { id: 0, total: 1 },
+                GatewayIntents::non_privileged(),
+            )
+            .await
+            .unwrap(),
+        );
+        manager.shards[0].reconnect_policy =
+            ReconnectPolicy::new(Duration::ZERO, Duration::ZERO, Duration::ZERO);
+        tokio::spawn(async move { manager.run().await });
+
+        gateway.send_ready();
+        gateway.disconnect();
+
+        let resume = gateway.next_sent_json().await.expect("a resume is sent");
+        assert_eq!(resume["op"], 6);
+        assert_eq!(resume["d"]["session_id"], "session");
+    }
+
+    #[tokio::test]
+    async fn shutdown_closes_the_connection_and_returns() {
+        let mut gateway = MockGateway::bind().await;
+        let mut manager = ShardManager::from_shard(
+            Shard::new(
+                &gateway.url,
+                "token",
+                ShardInformation { id: 0, total: 1 },
+                GatewayIntents::non_privileged(),
+            )
+            .await
+            .unwrap(),
+        );
+        let handle = manager.handle();
+        let mut events = manager.events();
+        let run = tokio::spawn(async move { manager.run().await });
+
+        gateway.send_ready();
+        let ready = timeout(Duration::from_secs(5), events.recv())
+            .await
+            .unwrap();
+        assert!(matches!(
+            ready,
+            Some(Incoming::Dispatch(SequencedDispatch {
+                event: DispatchEvent::Ready(_),
+                ..
+            }))
+        ));
+
+        handle.shutdown();
+        let result = timeout(Duration::from_secs(5), run)
+            .await
+            .expect("run loop should return")
+            .unwrap();
+        assert!(result.is_ok());
+        let Some(Message::Close(Some(frame))) = gateway.next_sent().await else {
+            panic!("expected a close frame");
+        };
+        assert_eq!(u16::from(frame.code), 1000);
+    }
+
+    #[tokio::test]
+    async fn events_merges_dispatch_and_shard_events() {
+        let gateway = MockGateway::bind().await;
+        let mut manager = ShardManager::from_shard(
+            Shard::new(
+                &gateway.url,
+                "token",
+                ShardInformation { id: 3, total: 4 },
+                GatewayIntents::non_privileged(),
+            )
+            .await
+            .unwrap(),
+        );
+        let mut events = manager.events();
+        tokio::spawn(async move { manager.run().await });
+
+        gateway.send_ready();
+        gateway.send_json(&json!({
+            "op": 0,
+            "s": 2,
+            "t": "GUILD_CREATE",
+            "d": { "id": "1", "name": "Guild", "owner_id": "2", "premium_tier": 0 },
+        }));
+
+        let mut received = Vec::new();
+        while received.len() < 3 {
+            let incoming = timeout(Duration::from_secs(5), events.recv()).await;
+            received.push(incoming.unwrap().unwrap());
+        }
+        assert!(matches!(
+            received[0],
+            Incoming::Dispatch(SequencedDispatch {
+                shard_id: 3,
+                event: DispatchEvent::Ready(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            received[1],
+            Incoming::Dispatch(SequencedDispatch {
+                shard_id: 3,
+                event: DispatchEvent::GuildCreate(_),
+                ..
+            })
+        ));
+        assert_eq!(
+            received[2],
+            Incoming::Control(ShardEvent::GuildJoined(Snowflake(1)))
+        );
+    }
+
+    #[tokio::test]
+    async fn dispatch_events_carry_their_sequence() {
+        let gateway = MockGateway::bind().await;
+        let mut manager = ShardManager::from_shard(
+            Shard::new(
+                &gateway.url,
+                "token",
+                ShardInformation { id: 0, total: 1 },
+                GatewayIntents::non_privileged(),
+            )
+            .await
+            .unwrap(),
+        );
+        let mut events = manager.events();
+        tokio::spawn(async move { manager.run().await });
+
+        gateway.send_ready();
+        for (sequence, id) in [(2, "1"), (3, "2")] {
+            gateway.send_json(&json!({
+                "op": 0,
+                "s": sequence,
+                "t": "GUILD_CREATE",
+                "d": { "id": id, "name": "Guild", "owner_id": "2", "premium_tier": 0 },
+            }));
+        }
+
+        let mut dispatches = Vec::new();
+        while dispatches.len() < 3 {
+            let incoming = timeout(Duration::from_secs(5), events.recv()).await;
+            if let Incoming::Dispatch(dispatch) = incoming.unwrap().unwrap() {
+                dispatches.push(dispatch);
+            }
+        }
+        let sequences: Vec<_> = dispatches
+            .iter()
+            .map(|dispatch| dispatch.sequence)
+            .collect();
+        assert_eq!(sequences, [1, 2, 3]);
+
+        // Another shard's first dispatch is sorted after every dispatch of this shard
+        let other_shard = SequencedDispatch {
+            shard_id: 4,
+            sequence: 1,
+            event: DispatchEvent::Resumed,
+        };
+        let mut buffered = vec![
+            dispatches[2].clone(),
+            other_shard.clone(),
+            dispatches[0].clone(),
+            dispatches[1].clone(),
+        ];
+        buffered.sort_by_key(SequencedDispatch::sort_key);
+        dispatches.push(other_shard);
+        assert_eq!(buffered, dispatches);
+    }
+
+    fn dispatch(sequence: u64, kind: &str, data: &serde_json::Value) -> serde_json::Value {
+        json!({ "op": 0, "s": sequence, "t": kind, "d": data })
+    }
+
+    fn ready(guild_ids: &[&str]) -> serde_json::Value {
+        let guilds: Vec<_> = guild_ids
+            .iter()
+            .map(|id| json!({ "id": id, "unavailable": true }))
+            .collect();
+        dispatch(
+            1,
+            "READY",
+            &json!({
+                "v": 10,
+                "user": user_payload(),
+                "guilds": guilds,
+                "session_id": "session",
+                "resume_gateway_url": "wss://gateway.discord.gg",
+            }),
+        )
+    }
+
+    fn guild_create(sequence: u64, id: &str, member_count: u64) -> serde_json::Value {
+        dispatch(
+            sequence,
+            "GUILD_CREATE",
+            &json!({
+                "id": id,
+                "name": "Guild",
+                "owner_id": "2",
+                "premium_tier": 0,
+                "member_count": member_count,
+            }),
+        )
+    }
+
+    #[tokio::test]
+    async fn total_guilds_counts_each_guild_once() {
+        let gateway = MockGateway::bind().await;
+        let mut manager =
+            ShardManager::with_url(&gateway.url, 2, "token", GatewayIntents::non_privileged())
+                .await
+                .unwrap();
+
+        let events = [
+            vec![ready(&["1", "2"]), guild_create(2, "1", 10)],
+            // A guild sent twice during the initial burst is only counted once
+            vec![
+                ready(&["3"]),
+                guild_create(2, "3", 5),
+                guild_create(3, "3", 5),
+            ],
+        ];
+        for (shard, events) in manager.shards.iter_mut().zip(events) {
+            for event in events {
+                let event: IncomingEvent = serde_json::from_value(event).unwrap();
+                shard.handle_event(Ok(&event)).unwrap();
+            }
+        }
+
+        // Guild 2 is still unavailable, but counted
+        assert_eq!(manager.total_guilds(), 3);
+        assert_eq!(manager.total_members(), 15);
+    }
+
+    #[tokio::test]
+    async fn handle_reads_totals_while_running() {
+        let gateways = [MockGateway::bind().await, MockGateway::bind().await];
+        let mut shards = Vec::new();
+        for (id, gateway) in (0..).zip(&gateways) {
+            let shard_information = ShardInformation { id, total: 2 };
+            let shard = Shard::new(
+                &gateway.url,
+                "token",
+                shard_information,
+                GatewayIntents::non_privileged(),
+            )
+            .await
+            .unwrap();
+            shards.push(shard);
+        }
+        let mut manager = ShardManager::from_shards(shards);
+        let handle = manager.handle();
+        let run = tokio::spawn(async move { manager.run().await });
+
+        for (gateway, guilds) in gateways.iter().zip([["1", "2"], ["3", "4"]]) {
+            for event in [
+                ready(&guilds),
+                guild_create(2, guilds[0], 10),
+                guild_create(3, guilds[1], 5),
+            ] {
+                gateway.send_json(&event);
+            }
+        }
+
+        timeout(Duration::from_secs(5), async {
+            while handle.total_guilds() < 4 || handle.total_members() < 30 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("the totals should be updated while the manager runs");
+        assert_eq!(handle.total_guilds(), 4);
+        assert_eq!(handle.total_members(), 30);
+        assert!(!run.is_finished());
+
+        handle.shutdown();
+        timeout(Duration::from_secs(5), run)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn shutdown_interrupts_a_failing_reconnect() {
+        let gateway = MockGateway::bind().await;
+        let mut manager = ShardManager::from_shard(
+            Shard::new(
+                &gateway.url,
+                "token",
+                ShardInformation { id: 0, total: 1 },
+                GatewayIntents::non_privileged(),
+            )
+            .await
+            .unwrap(),
+        );
+        manager.shards[0].reconnect_policy =
+            ReconnectPolicy::new(Duration::ZERO, Duration::ZERO, Duration::ZERO);
+        let handle = manager.handle();
+        let run = tokio::spawn(async move { manager.run().await });
+
+        // Nothing listens on the resume URL, so every attempt to resume fails
+        let unreachable = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("ws://{}", listener.local_addr().unwrap())
+        };
+        let mut ready = ready(&[]);
+        ready["d"]["resume_gateway_url"] = json!(unreachable);
+        gateway.send_json(&ready);
+        gateway.disconnect();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!run.is_finished());
+
+        handle.shutdown();
+        let result = timeout(Duration::from_secs(5), run)
+            .await
+            .expect("shutting down should stop the reconnect")
+            .unwrap();
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn shards_in_different_buckets_identify_together() {
+        let gateway = MockGateway::bind_handshaking().await;
+        let limiter = BucketedIdentifyLimiter::new(2);
+        let mut manager = ShardManager::with_limiter(
+            &gateway.url,
+            2,
+            &limiter,
+            None,
+            "token",
+            GatewayIntents::non_privileged(),
+        )
+        .await
+        .unwrap();
+        let mut events = manager.events();
+        let handle = manager.handle();
+        let run = tokio::spawn(async move { manager.run().await });
+
+        // Shards in the same bucket would identify 5 seconds apart
+        let mut ready_shards = 0;
+        while ready_shards < 2 {
+            let incoming = timeout(Duration::from_secs(2), events.recv())
+                .await
+                .expect("both shards should identify at once")
+                .unwrap();
+            if let Incoming::Dispatch(SequencedDispatch {
+                event: DispatchEvent::Ready(_),
+                ..
+            }) = incoming
+            {
+                ready_shards += 1;
+            }
+        }
+
+        handle.shutdown();
+        timeout(Duration::from_secs(5), run)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn shards_back_off_with_the_session_start_limit() {
+        let gateway = MockGateway::bind().await;
+        let session_start_limit = SessionStartLimit {
+            total: 1000,
+            remaining: 998,
+            reset_after: 60_000,
+            max_concurrency: 2,
+        };
+        let limiter = BucketedIdentifyLimiter::new(session_start_limit.max_concurrency);
+        let manager = ShardManager::with_limiter(
+            &gateway.url,
+            2,
+            &limiter,
+            Some(session_start_limit),
+            "token",
+            GatewayIntents::non_privileged(),
+        )
+        .await
+        .unwrap();
+
+        for shard in &manager.shards {
+            assert_eq!(shard.session_start_limit, Some(session_start_limit));
+        }
+    }
+
+    #[tokio::test]
+    async fn every_shard_becomes_ready() {
+        let gateway = MockGateway::bind_handshaking().await;
+        let mut manager =
+            ShardManager::with_url(&gateway.url, 2, "token", GatewayIntents::non_privileged())
+                .await
+                .unwrap();
+        let limiter = IdentifyRateLimiter::with_interval(Duration::from_millis(100));
+        for shard in &mut manager.shards {
+            shard.identify_rate_limiter = limiter.clone();
+        }
+        let mut events = manager.events();
+        let handle = manager.handle();
+        let run = tokio::spawn(async move { manager.run().await.map(|()| manager) });
+
+        let mut ready_shards = Vec::new();
+        while ready_shards.len() < 2 {
+            let incoming = timeout(Duration::from_secs(5), events.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            if let Incoming::Dispatch(SequencedDispatch {
+                shard_id,
+                event: DispatchEvent::Ready(ready),
+                ..
+            }) = incoming
+            {
+                assert_eq!(ready.shard, Some((shard_id, 2)));
+                ready_shards.push(shard_id);
+            }
+        }
+        ready_shards.sort_unstable();
+        assert_eq!(ready_shards, [0, 1]);
+
+        handle.shutdown();
+        let manager = timeout(Duration::from_secs(5), run)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let ids: Vec<_> = manager
+            .shards
+            .iter()
+            .map(|shard| shard.shard_information.unwrap().id)
+            .collect();
+        assert_eq!(ids, [0, 1]);
+    }
+
+    #[tokio::test]
+    async fn dispatch_events_are_sent_to_the_channel() {
+        let gateway = MockGateway::bind().await;
+        let (sender, mut receiver) = channel(16);
+        let mut manager = ShardManager::with_sender(
+            Shard::new(
+                &gateway.url,
+                "token",
+                ShardInformation { id: 0, total: 1 },
+                GatewayIntents::non_privileged(),
+            )
+            .await
+            .unwrap(),
+            sender,
+        );
+        tokio::spawn(async move { manager.run().await });
+
+        // Control frames are handled by the manager and not forwarded
+        gateway.send_json(&json!({ "op": 11, "d": null }));
+        gateway.send_ready();
+
+        let dispatch = timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(dispatch.sort_key(), (0, 1));
+        assert!(matches!(dispatch.event, DispatchEvent::Ready(_)));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn malformed_dispatch_events_are_skipped() {
+        let mut gateway = MockGateway::bind().await;
+        let (sender, mut receiver) = channel(16);
+        let mut manager = ShardManager::with_sender(
+            Shard::new(
+                &gateway.url,
+                "token",
+                ShardInformation { id: 0, total: 1 },
+                GatewayIntents::non_privileged(),
+            )
+            .await
+            .unwrap(),
+            sender,
+        );
+        let handle = manager.handle();
+        let run = tokio::spawn(async move { manager.run().await });
+
+        gateway.send_ready();
+        gateway.send_json(&dispatch(2, "MESSAGE_CREATE", &json!({ "id": "1" })));
+        // The skipped event's sequence number is sent with the next heartbeat
+        gateway.send_json(&json!({ "op": 1, "d": null }));
+        gateway.send_json(&guild_create(3, "1", 10));
+
+        for expected in [(0, 1), (0, 3)] {
+            let dispatch = timeout(Duration::from_secs(5), receiver.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(dispatch.sort_key(), expected);
+        }
+        let heartbeat = gateway.next_sent_json().await.unwrap();
+        assert_eq!(heartbeat, json!({ "op": 1, "d": 2 }));
+
+        handle.shutdown();
+        let result = timeout(Duration::from_secs(5), run).await.unwrap().unwrap();
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn stream_yields_dispatch_events_then_the_fatal_error() {
+        let gateway = MockGateway::bind().await;
+        let events = ShardManager::from_shard(
+            Shard::new(
+                &gateway.url,
+                "token",
+                ShardInformation { id: 0, total: 1 },
+                GatewayIntents::non_privileged(),
+            )
+            .await
+            .unwrap(),
+        )
+        .into_stream();
+
+        gateway.send_ready();
+        gateway.send_json(&json!({
+            "op": 0,
+            "s": 2,
+            "t": "GUILD_CREATE",
+            "d": { "id": "1", "name": "Guild", "owner_id": "2", "premium_tier": 0 },
+        }));
+        gateway.send(Message::Close(Some(CloseFrame {
+            code: 4004.into(),
+            reason: "Authentication failed.".into(),
+        })));
+
+        let events: Vec<_> = timeout(Duration::from_secs(5), events.collect())
+            .await
+            .expect("stream should end");
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            events[0],
+            Ok(SequencedDispatch {
+                event: DispatchEvent::Ready(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            events[1],
+            Ok(SequencedDispatch {
+                event: DispatchEvent::GuildCreate(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            events[2],
+            Err(Error::Gateway(GatewayError::Closed(Some(
+                CloseCode::AuthenticationFailed
+            ))))
+        ));
+    }
+
+    #[tokio::test]
+    async fn dropping_the_stream_closes_the_connection() {
+        let mut gateway = MockGateway::bind().await;
+        let mut events = ShardManager::from_shard(
+            Shard::new(
+                &gateway.url,
+                "token",
+                ShardInformation { id: 0, total: 1 },
+                GatewayIntents::non_privileged(),
+            )
+            .await
+            .unwrap(),
+        )
+        .into_stream();
+
+        gateway.send_ready();
+        let ready = timeout(Duration::from_secs(5), events.next())
+            .await
+            .unwrap();
+        assert!(matches!(
+            ready,
+            Some(Ok(SequencedDispatch {
+                event: DispatchEvent::Ready(_),
+                ..
+            }))
+        ));
+        drop(events);
+
+        let Some(Message::Close(Some(frame))) = gateway.next_sent().await else {
+            panic!("expected a close frame");
+        };
+        assert_eq!(u16::from(frame.code), 1000);
+    }
+}
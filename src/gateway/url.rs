@@ -0,0 +1,131 @@
+//! The url module builds the URLs shards connect to from their components.
+
+use std::fmt::Display;
+
+use super::{
+    builder::GATEWAY_VERSION,
+    compression::TransportCompression,
+    encoding::{query_parameter, set_query_parameter, Encoding},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A gateway URL built from its host and the query parameters the gateway expects, so
+/// that no parameter can be forgotten. Display the URL to get the string to connect to.
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway#connecting)
+pub struct GatewayUrl {
+    /// The scheme and host, such as `wss://gateway.discord.gg`
+    pub host: String,
+    /// The gateway API version to connect to
+    pub version: u8,
+    /// The format payloads are encoded with
+    pub encoding: Encoding,
+    /// How the gateway compresses the messages it sends
+    pub compression: TransportCompression,
+}
+
+impl GatewayUrl {
+    /// Creates a URL for `host`, such as `wss://gateway.discord.gg`, connecting to
+    /// [`GATEWAY_VERSION`] with the default encoding and compression.
+    #[must_use]
+    pub fn new(host: &str) -> Self {
+        Self {
+            host: host.trim_end_matches('/').to_string(),
+            version: GATEWAY_VERSION,
+            encoding: Encoding::default(),
+            compression: TransportCompression::default(),
+        }
+    }
+
+    /// Creates a URL from a full gateway URL, keeping the version in its query if any.
+    /// The encoding and compression in the query are replaced by those of the URL.
+    #[must_use]
+    pub fn parse(url: &str) -> Self {
+        let (host, _) = url.split_once('?').unwrap_or((url, ""));
+        let mut gateway_url = Self::new(host);
+        if let Some(version) = query_parameter(url, "v").and_then(|version| version.parse().ok()) {
+            gateway_url.version = version;
+        }
+        gateway_url
+    }
+
+    /// The gateway API version to connect to
+    #[must_use]
+    pub fn version(mut self, version: u8) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// The format payloads are encoded with
+    #[must_use]
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// How the gateway compresses the messages it sends
+    #[must_use]
+    pub fn compression(mut self, compression: TransportCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// The same URL on another host, such as the `resume_gateway_url` of a session, which
+    /// is sent without any query parameters.
+    #[must_use]
+    pub fn with_host(&self, host: &str) -> Self {
+        Self {
+            host: host.trim_end_matches('/').to_string(),
+            ..self.clone()
+        }
+    }
+}
+
+impl Display for GatewayUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let url = set_query_parameter(&self.host, "v", &self.version.to_string());
+        let url = self
+            .compression
+            .apply_to_url(&self.encoding.apply_to_url(&url));
+        f.write_str(&url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GatewayUrl;
+    use crate::gateway::compression::TransportCompression;
+
+    #[test]
+    fn builds_the_query_from_components() {
+        let url = GatewayUrl::new("wss://gateway.discord.gg")
+            .version(9)
+            .compression(TransportCompression::ZlibStream);
+        assert_eq!(
+            url.to_string(),
+            "wss://gateway.discord.gg/?v=9&encoding=json&compress=zlib-stream"
+        );
+    }
+
+    #[test]
+    fn parse_keeps_the_version() {
+        let url = GatewayUrl::parse("wss://gateway.discord.gg/?v=9&encoding=json");
+        assert_eq!(url.host, "wss://gateway.discord.gg");
+        assert_eq!(url.version, 9);
+        assert_eq!(
+            GatewayUrl::parse("ws://127.0.0.1:8080").to_string(),
+            "ws://127.0.0.1:8080/?v=10&encoding=json"
+        );
+    }
+
+    #[test]
+    fn resume_url_gets_the_query_appended() {
+        let url = GatewayUrl::new("wss://gateway.discord.gg")
+            .compression(TransportCompression::ZlibStream)
+            .with_host("wss://gateway-us-east1-b.discord.gg");
+        assert_eq!(
+            url.to_string(),
+            "wss://gateway-us-east1-b.discord.gg/?v=10&encoding=json&compress=zlib-stream"
+        );
+    }
+}
@@ -1,113 +1,559 @@
-use std::{io::Read, time::Duration};
+use std::{
+    fmt::Debug,
+    io::Read,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use flate2::read::ZlibDecoder;
-use futures::{stream::FusedStream, SinkExt, StreamExt};
-use serde_json::{from_str, to_string};
-use tokio::{net::TcpStream, time::timeout};
+use futures::{
+    stream::{Fuse, FusedStream, SplitSink, SplitStream},
+    SinkExt, StreamExt,
+};
+use tokio::{net::TcpStream, sync::Mutex, time::timeout};
 use tokio_tungstenite::{
-    connect_async_with_config,
-    tungstenite::{protocol::WebSocketConfig, Message},
+    client_async_tls_with_config, connect_async_with_config,
+    tungstenite::{
+        client::IntoClientRequest,
+        error::ProtocolError,
+        protocol::{frame::coding::CloseCode as WebsocketCloseCode, CloseFrame},
+        Error as WebsocketError, Message,
+    },
     MaybeTlsStream, WebSocketStream,
 };
 
 use crate::{
     error::Result,
-    model::gateway::{
-        event::{Event, IdentifyProperties, OpCode, SendEventData},
-        intents::GatewayIntents,
+    model::{
+        gateway::{
+            event::{
+                GuildMembersFilter, IdentifyProperties, IncomingEvent, OutgoingEvent, SendEventData,
+            },
+            intents::GatewayIntents,
+            presence::PresenceUpdate,
+            voice::VoiceStateUpdateData,
+        },
+        snowflake::Snowflake,
     },
 };
 
-use super::{error::Error as GatewayError, shard::ShardInformation};
+use super::{
+    compression::{StreamDecoder, TransportCompression},
+    encoding::Encoding,
+    error::{CloseCode, Error as GatewayError},
+    proxy::ProxyConfig,
+    shard::ShardInformation,
+};
+
+pub use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+
+/// The largest message accepted by [`WebsocketClient::default_config`]. The `GUILD_CREATE`
+/// of a large guild can exceed tungstenite's default limit of 64 MiB.
+pub const MAX_MESSAGE_SIZE: usize = 256 << 20;
+
+/// Invoked with every payload received from the gateway, after decompression and
+/// before it is parsed.
+pub type RawByteHook = Arc<dyn Fn(&[u8]) + Send + Sync>;
 
-#[derive(Debug)]
-pub struct WebsocketClient(WebSocketStream<MaybeTlsStream<TcpStream>>);
+type Stream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+pub struct WebsocketClient {
+    /// Every message is sent through this lock so that concurrent sends, such as heartbeats
+    /// sent from another task, can never interleave their frames.
+    sink: Arc<Mutex<SplitSink<Stream, Message>>>,
+    stream: Fuse<SplitStream<Stream>>,
+    compression: TransportCompression,
+    encoding: Encoding,
+    stream_decoder: Option<StreamDecoder>,
+    last_received: Instant,
+    /// Receives the exact bytes of every payload, such as for archiving the gateway stream.
+    /// This is `None` by default, and is kept when the shard reconnects.
+    pub raw_byte_hook: Option<RawByteHook>,
+}
+
+impl Debug for WebsocketClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebsocketClient")
+            .field("sink", &self.sink)
+            .field("stream", &self.stream)
+            .field("compression", &self.compression)
+            .field("encoding", &self.encoding)
+            .field("stream_decoder", &self.stream_decoder)
+            .field("last_received", &self.last_received)
+            .field("raw_byte_hook", &self.raw_byte_hook.is_some())
+            .finish()
+    }
+}
 
 impl WebsocketClient {
     pub async fn connect(url: &str) -> Result<Self> {
-        let config = WebSocketConfig::default();
+        Self::connect_with(url, TransportCompression::default(), Encoding::default()).await
+    }
 
-        let (stream, _) = connect_async_with_config(url, Some(config), false).await?;
+    pub async fn connect_with(
+        url: &str,
+        compression: TransportCompression,
+        encoding: Encoding,
+    ) -> Result<Self> {
+        Self::connect_with_config(url, compression, encoding, Self::default_config()).await
+    }
 
-        Ok(Self(stream))
+    /// Connects like [`WebsocketClient::connect_with`], with the limits and buffers of the
+    /// connection set by `config`.
+    pub async fn connect_with_config(
+        url: &str,
+        compression: TransportCompression,
+        encoding: Encoding,
+        config: WebSocketConfig,
+    ) -> Result<Self> {
+        Self::connect_through(url, compression, encoding, config, None).await
     }
 
-    pub async fn receive(&mut self) -> Result<Option<Event>> {
-        if self.0.is_terminated() {
+    /// Connects like [`WebsocketClient::connect_with_config`], tunnelling the connection
+    /// through `proxy` if one is given.
+    pub async fn connect_through(
+        url: &str,
+        compression: TransportCompression,
+        encoding: Encoding,
+        config: WebSocketConfig,
+        proxy: Option<&ProxyConfig>,
+    ) -> Result<Self> {
+        let url = compression.apply_to_url(&encoding.apply_to_url(url));
+
+        let (stream, _) = match proxy {
+            Some(proxy) => {
+                let request = url.into_client_request()?;
+                let uri = request.uri();
+                let host = uri.host().unwrap_or_default().to_string();
+                let port = uri
+                    .port_u16()
+                    .unwrap_or(if uri.scheme_str() == Some("wss") {
+                        443
+                    } else {
+                        80
+                    });
+                let host = host.trim_start_matches('[').trim_end_matches(']');
+                let tunnel = proxy.connect(host, port).await?;
+                client_async_tls_with_config(request, tunnel, Some(config), None).await?
+            }
+            None => connect_async_with_config(url, Some(config), false).await?,
+        };
+        let (sink, stream) = stream.split();
+
+        Ok(Self {
+            sink: Arc::new(Mutex::new(sink)),
+            stream: stream.fuse(),
+            compression,
+            encoding,
+            stream_decoder: compression.stream_decoder()?,
+            last_received: Instant::now(),
+            raw_byte_hook: None,
+        })
+    }
+
+    /// The configuration connections are made with unless another is given, which accepts
+    /// messages and frames of up to [`MAX_MESSAGE_SIZE`].
+    #[must_use]
+    pub fn default_config() -> WebSocketConfig {
+        WebSocketConfig {
+            max_message_size: Some(MAX_MESSAGE_SIZE),
+            max_frame_size: Some(MAX_MESSAGE_SIZE),
+            ..WebSocketConfig::default()
+        }
+    }
+
+    /// Receives the next event, returning `Ok(None)` when none arrives in time or a message
+    /// holds no event. A connection that was closed, with or without a close frame, is
+    /// returned as [`GatewayError::Closed`].
+    pub async fn receive(&mut self) -> Result<Option<IncomingEvent>> {
+        if self.stream.is_terminated() {
             return Err(GatewayError::Closed(None))?;
         }
 
-        let message = match timeout(Duration::from_millis(500), self.0.next()).await {
+        let message = match timeout(Duration::from_millis(500), self.stream.next()).await {
             Ok(Some(Ok(message))) => message,
+            // The connection was lost without a close frame, such as by the network
+            Ok(
+                Some(Err(
+                    WebsocketError::ConnectionClosed
+                    | WebsocketError::AlreadyClosed
+                    | WebsocketError::Protocol(ProtocolError::ResetWithoutClosingHandshake),
+                ))
+                | None,
+            ) => return Err(GatewayError::Closed(None))?,
             Ok(Some(Err(err))) => return Err(err)?,
-            Ok(None) | Err(_) => return Ok(None),
+            // Nothing was received yet, which leaves time for heartbeats
+            Err(_) => return Ok(None),
         };
 
+        self.last_received = Instant::now();
         let value = match message {
             Message::Binary(bytes) => {
-                let mut decompressed = String::with_capacity(bytes.len() * 3);
-                ZlibDecoder::new(&bytes[..]).read_to_string(&mut decompressed)?;
-                from_str(decompressed.as_str())?
+                let decompressed = if let Some(decoder) = self.stream_decoder.as_mut() {
+                    let Some(decompressed) = decoder.push(&bytes)? else {
+                        return Ok(None);
+                    };
+                    decompressed
+                } else if self.encoding.is_uncompressed(&bytes) {
+                    bytes
+                } else {
+                    let mut decompressed = Vec::with_capacity(bytes.len() * 3);
+                    ZlibDecoder::new(&bytes[..]).read_to_end(&mut decompressed)?;
+                    decompressed
+                };
+                self.call_raw_byte_hook(&decompressed);
+                self.encoding.decode(&decompressed)?
+            }
+            Message::Text(text) => {
+                self.call_raw_byte_hook(text.as_bytes());
+                self.encoding.decode(text.as_bytes())?
+            }
+            Message::Close(frame) => {
+                return Err(GatewayError::Closed(frame.as_ref().map(CloseCode::from)))?
+            }
+            // tungstenite reassembles fragmented messages into a single `Text` or `Binary`
+            // message before returning them. It also answers every ping with a pong, which
+            // is sent the next time the connection is read from or written to.
+            Message::Ping(payload) => {
+                debug!("received a ping of {} bytes", payload.len());
+                return Ok(None);
+            }
+            Message::Pong(payload) => {
+                debug!("received a pong of {} bytes", payload.len());
+                return Ok(None);
+            }
+            Message::Frame(frame) => {
+                // Raw frames are only produced when writing, so this is never expected
+                warn!("ignoring unexpected raw frame: {:?}", frame.header());
+                return Ok(None);
             }
-            Message::Text(text) => from_str(text.as_str())?,
-            Message::Close(frame) => return Err(GatewayError::Closed(frame))?,
-            _ => return Ok(None),
         };
 
         Ok(Some(value))
     }
 
-    pub async fn send(&mut self, message: &impl serde::Serialize) -> Result<()> {
-        let message = to_string(message).map(Message::Text)?;
-        self.0.send(message).await?;
+    /// When the last message of any kind, including a ping or pong, was received, or when
+    /// the connection was made if none has been
+    #[must_use]
+    pub fn last_received(&self) -> Instant {
+        self.last_received
+    }
+
+    /// How the gateway compresses the messages it sends on this connection
+    #[must_use]
+    pub fn compression(&self) -> TransportCompression {
+        self.compression
+    }
+
+    /// The format payloads are encoded with on this connection
+    #[must_use]
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    fn call_raw_byte_hook(&self, bytes: &[u8]) {
+        if let Some(hook) = &self.raw_byte_hook {
+            hook(bytes);
+        }
+    }
+
+    pub async fn send(&self, message: &impl serde::Serialize) -> Result<()> {
+        let message = self.encoding.encode(message)?;
+        self.sink.lock().await.send(message).await?;
         Ok(())
     }
 
-    pub async fn send_heartbeat(&mut self, sequence: Option<u64>) -> Result<()> {
-        self.send(&Event {
-            op: OpCode::Heartbeat,
-            send_data: Some(SendEventData::Heartbeat(sequence)),
-            ..Default::default()
-        })
-        .await
+    /// Closes the connection with the normal closure code, which ends the session.
+    pub async fn close(&self) -> Result<()> {
+        let frame = CloseFrame {
+            code: WebsocketCloseCode::Normal,
+            reason: "".into(),
+        };
+        self.sink
+            .lock()
+            .await
+            .send(Message::Close(Some(frame)))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn send_heartbeat(&self, sequence: Option<u64>) -> Result<()> {
+        self.send(&OutgoingEvent::from(SendEventData::Heartbeat(sequence)))
+            .await
     }
 
     pub async fn send_identify(
-        &mut self,
+        &self,
         token: &str,
         shard_information: &Option<ShardInformation>,
         intents: &GatewayIntents,
+        presence: &Option<PresenceUpdate>,
+        large_threshold: Option<u8>,
+        properties: &IdentifyProperties,
     ) -> Result<()> {
-        self.send(&Event {
-            op: OpCode::Identify,
-            send_data: Some(SendEventData::Identify {
-                token: token.to_string(),
-                properties: IdentifyProperties::default(),
-                compress: None,
-                large_threshold: None,
-                shard: *shard_information,
-                intents: *intents,
-            }),
-            ..Default::default()
-        })
+        self.send(&OutgoingEvent::from(SendEventData::Identify {
+            token: token.to_string(),
+            properties: properties.clone(),
+            compress: None,
+            large_threshold,
+            shard: *shard_information,
+            presence: presence.clone(),
+            intents: *intents,
+        }))
         .await
     }
 
-    pub async fn send_resume(
-        &mut self,
-        token: &str,
-        session_id: &str,
-        sequence: u64,
+    pub async fn send_presence_update(&self, presence: &PresenceUpdate) -> Result<()> {
+        self.send(&OutgoingEvent::from(SendEventData::PresenceUpdate(
+            presence.clone(),
+        )))
+        .await
+    }
+
+    pub async fn send_request_guild_members(
+        &self,
+        guild_id: Snowflake,
+        filter: GuildMembersFilter,
+        presences: Option<bool>,
+        nonce: Option<String>,
     ) -> Result<()> {
-        self.send(&Event {
-            op: OpCode::Resume,
-            send_data: Some(SendEventData::Resume {
-                token: token.to_string(),
-                session_id: session_id.to_string(),
-                sequence,
-            }),
-            ..Default::default()
-        })
+        self.send(&OutgoingEvent::from(SendEventData::RequestGuildMembers {
+            guild_id,
+            filter,
+            presences,
+            nonce,
+        }))
+        .await
+    }
+
+    pub async fn send_request_soundboard_sounds(&self, guild_ids: Vec<Snowflake>) -> Result<()> {
+        self.send(&OutgoingEvent::from(
+            SendEventData::RequestSoundboardSounds { guild_ids },
+        ))
+        .await
+    }
+
+    pub async fn send_voice_state_update(&self, voice_state: &VoiceStateUpdateData) -> Result<()> {
+        self.send(&OutgoingEvent::from(SendEventData::VoiceStateUpdate(
+            *voice_state,
+        )))
+        .await
+    }
+
+    pub async fn send_resume(&self, token: &str, session_id: &str, sequence: u64) -> Result<()> {
+        self.send(&OutgoingEvent::from(SendEventData::Resume {
+            token: token.to_string(),
+            session_id: session_id.to_string(),
+            sequence,
+        }))
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        sync::{Arc, Mutex},
+    };
+
+    use flate2::{write::ZlibEncoder, Compression};
+    use tokio_tungstenite::tungstenite::{
+        protocol::frame::{
+            coding::{Data, OpCode as FrameOpCode},
+            Frame,
+        },
+        Message,
+    };
+
+    use super::{WebSocketConfig, WebsocketClient};
+    use crate::{
+        error::Error,
+        gateway::{
+            compression::TransportCompression, encoding::Encoding, error::Error as GatewayError,
+            mock::MockGateway,
+        },
+        model::gateway::event::OpCode,
+    };
+
+    #[tokio::test]
+    async fn lost_connection_is_closed() {
+        let gateway = MockGateway::bind().await;
+        let mut client = WebsocketClient::connect(&gateway.url).await.unwrap();
+
+        // Idle polls don't report the connection as closed
+        assert!(client.receive().await.unwrap().is_none());
+
+        gateway.disconnect();
+        assert!(matches!(
+            client.receive().await,
+            Err(Error::Gateway(GatewayError::Closed(None)))
+        ));
+        assert!(matches!(
+            client.receive().await,
+            Err(Error::Gateway(GatewayError::Closed(None)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn messages_over_the_configured_size_are_rejected() {
+        let gateway = MockGateway::bind().await;
+        let config = WebSocketConfig {
+            max_message_size: Some(64),
+            ..WebsocketClient::default_config()
+        };
+        let mut client = WebsocketClient::connect_with_config(
+            &gateway.url,
+            TransportCompression::default(),
+            Encoding::default(),
+            config,
+        )
+        .await
+        .unwrap();
+
+        gateway.send(Message::Text(r#"{"op":11,"d":null}"#.to_string()));
+        assert!(client.receive().await.unwrap().is_some());
+
+        let large = format!(r#"{{"op":11,"d":null,"padding":"{}"}}"#, "a".repeat(64));
+        gateway.send(Message::Text(large));
+        assert!(matches!(client.receive().await, Err(Error::Websocket(_))));
+    }
+
+    #[tokio::test]
+    async fn pings_are_answered_with_a_pong() {
+        let mut gateway = MockGateway::bind().await;
+        let mut client = WebsocketClient::connect(&gateway.url).await.unwrap();
+        let connected_at = client.last_received();
+
+        gateway.send(Message::Ping(b"ping".to_vec()));
+        assert!(client.receive().await.unwrap().is_none());
+        assert!(client.last_received() > connected_at);
+        gateway.send(Message::Text(r#"{"op":11,"d":null}"#.to_string()));
+        assert!(client.receive().await.unwrap().is_some());
+
+        let pong = gateway.next_sent().await.unwrap();
+        assert_eq!(pong, Message::Pong(b"ping".to_vec()));
+        assert_eq!(gateway.next_sent().await, None);
+    }
+
+    #[tokio::test]
+    async fn pongs_refresh_the_last_received_time() {
+        let gateway = MockGateway::bind().await;
+        let mut client = WebsocketClient::connect(&gateway.url).await.unwrap();
+        let connected_at = client.last_received();
+
+        gateway.send(Message::Pong(Vec::new()));
+        assert!(client.receive().await.unwrap().is_none());
+        assert!(client.last_received() > connected_at);
+    }
+
+    #[tokio::test]
+    async fn fragmented_messages_are_reassembled() {
+        let gateway = MockGateway::bind().await;
+        let mut client = WebsocketClient::connect(&gateway.url).await.unwrap();
+
+        let payload = br#"{"op":11,"d":null}"#;
+        let (first, last) = payload.split_at(8);
+        gateway.send(Message::Frame(Frame::message(
+            first.to_vec(),
+            FrameOpCode::Data(Data::Text),
+            false,
+        )));
+        // Control frames may be interleaved with the fragments of a message
+        gateway.send(Message::Ping(Vec::new()));
+        gateway.send(Message::Frame(Frame::message(
+            last.to_vec(),
+            FrameOpCode::Data(Data::Continue),
+            true,
+        )));
+
+        let event = loop {
+            if let Some(event) = client.receive().await.unwrap() {
+                break event;
+            }
+        };
+        assert_eq!(event.op, OpCode::HeartbeatACK);
+    }
+
+    #[tokio::test]
+    async fn raw_byte_hook_receives_payloads() {
+        let gateway = MockGateway::bind().await;
+        let mut client = WebsocketClient::connect(&gateway.url).await.unwrap();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let hook_received = received.clone();
+        client.raw_byte_hook = Some(Arc::new(move |bytes: &[u8]| {
+            hook_received.lock().unwrap().push(bytes.to_vec());
+        }));
+
+        let text = r#"{"op":11,"d":null}"#;
+        let compressed = r#"{"op":1,"d":null}"#;
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(compressed.as_bytes()).unwrap();
+        gateway.send(Message::Text(text.to_string()));
+        gateway.send(Message::Binary(encoder.finish().unwrap()));
+
+        while received.lock().unwrap().len() < 2 {
+            client.receive().await.unwrap();
+        }
+        assert_eq!(
+            *received.lock().unwrap(),
+            [text.as_bytes().to_vec(), compressed.as_bytes().to_vec()]
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrent_sends_do_not_interleave() {
+        let mut gateway = MockGateway::bind().await;
+        let client = WebsocketClient::connect(&gateway.url).await.unwrap();
+
+        let sends = (0..50).map(|sequence| client.send_heartbeat(Some(sequence)));
+        for result in futures::future::join_all(sends).await {
+            result.unwrap();
+        }
+
+        let mut sequences = Vec::new();
+        while sequences.len() < 50 {
+            let heartbeat = gateway.next_sent_json().await.unwrap();
+            assert_eq!(heartbeat["op"], 1);
+            sequences.push(heartbeat["d"].as_u64().unwrap());
+        }
+        sequences.sort_unstable();
+        assert_eq!(sequences, (0..50).collect::<Vec<_>>());
+    }
+
+    #[cfg(feature = "etf")]
+    #[tokio::test]
+    async fn etf_is_sent_and_received_as_binary() {
+        use serde_json::json;
+
+        use crate::{
+            gateway::{compression::TransportCompression, encoding::Encoding, etf},
+            model::gateway::event::OpCode,
+        };
+
+        let mut gateway = MockGateway::bind().await;
+        let mut client = WebsocketClient::connect_with(
+            &gateway.url,
+            TransportCompression::Payload,
+            Encoding::Etf,
+        )
         .await
+        .unwrap();
+
+        client.send_heartbeat(Some(7)).await.unwrap();
+        let Some(Message::Binary(bytes)) = gateway.next_sent().await else {
+            panic!("expected a binary message");
+        };
+        assert_eq!(etf::from_slice(&bytes).unwrap(), json!({ "op": 1, "d": 7 }));
+
+        gateway.send(Message::Binary(etf::to_vec(
+            &json!({ "op": 11, "d": null }),
+        )));
+        let event = loop {
+            if let Some(event) = client.receive().await.unwrap() {
+                break event;
+            }
+        };
+        assert_eq!(event.op, OpCode::HeartbeatACK);
     }
 }
@@ -0,0 +1,449 @@
+//! The handler module routes dispatch events to the methods of an [`EventHandler`].
+
+use std::{future::Future, pin::pin};
+
+use serde_json::Value;
+
+use crate::{
+    error::Result,
+    gateway::{
+        shard::ShardInformation,
+        shard_manager::{Incoming, SequencedDispatch, ShardManager},
+    },
+    model::{
+        channel::{
+            message::{Message, PartialMessage},
+            Channel,
+        },
+        emoji::Emoji,
+        gateway::{
+            dispatch::{
+                AutoModerationActionExecutionEvent, DispatchEvent, GuildCreateEvent,
+                GuildDeleteEvent, GuildMemberEvent, GuildMembersChunkEvent, GuildUpdateEvent,
+                IntegrationEvent, InviteCreateEvent, MessageReactionAddEvent,
+                MessageReactionRemoveEmojiEvent, MessageReactionRemoveEvent, ReadyEvent,
+                SoundboardSoundsEvent, ThreadListSyncEvent, ThreadMemberUpdateEvent,
+                ThreadMembersUpdateEvent, TypingStartEvent,
+            },
+            presence::Presence,
+        },
+        guild::{
+            audit_log::AuditLogEntry, auto_moderation::AutoModerationRule, role::Role,
+            scheduled_event::ScheduledEvent,
+        },
+        interaction::Interaction,
+        snowflake::Snowflake,
+        soundboard::SoundboardSound,
+        sticker::Sticker,
+        timestamp::Timestamp,
+        user::User,
+        voice::VoiceState,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Information about where an event passed to an [`EventHandler`] was received
+pub struct Context {
+    /// The shard that received the event
+    pub shard: ShardInformation,
+}
+
+/// Calls the macro `$callback` with every dispatch event, the fields it is destructured
+/// into and the [`EventHandler`] method it is routed to. Adding an event only requires
+/// adding its variant and method here.
+macro_rules! dispatch_events {
+    ($callback:ident) => {
+        $callback! {
+            /// Called when a session has started
+            Ready(ready) => on_ready(ready: Box<ReadyEvent>);
+            /// Called when a session has been resumed
+            Resumed => on_resumed();
+            /// Called when a guild becomes available or the client joins a guild
+            GuildCreate(guild) => on_guild_create(guild: Box<GuildCreateEvent>);
+            /// Called when a guild is updated
+            GuildUpdate(guild) => on_guild_update(guild: Box<GuildUpdateEvent>);
+            /// Called when a guild becomes unavailable or the client leaves a guild
+            GuildDelete(guild) => on_guild_delete(guild: GuildDeleteEvent);
+            /// Called with the soundboard sounds of a guild that were requested
+            SoundboardSounds(sounds) => on_soundboard_sounds(sounds: SoundboardSoundsEvent);
+            /// Called when a soundboard sound is created
+            GuildSoundboardSoundCreate(sound) => on_guild_soundboard_sound_create(sound: Box<SoundboardSound>);
+            /// Called when a soundboard sound is updated
+            GuildSoundboardSoundUpdate(sound) => on_guild_soundboard_sound_update(sound: Box<SoundboardSound>);
+            /// Called when a soundboard sound is deleted
+            GuildSoundboardSoundDelete { sound_id, guild_id }
+                => on_guild_soundboard_sound_delete(sound_id: Snowflake, guild_id: Snowflake);
+            /// Called when several soundboard sounds of a guild are updated at once
+            GuildSoundboardSoundsUpdate(sounds) => on_guild_soundboard_sounds_update(sounds: SoundboardSoundsEvent);
+            /// Called when a guild's emojis are updated
+            GuildEmojisUpdate { guild_id, emojis } => on_guild_emojis_update(guild_id: Snowflake, emojis: Vec<Emoji>);
+            /// Called when a guild's stickers are updated
+            GuildStickersUpdate { guild_id, stickers }
+                => on_guild_stickers_update(guild_id: Snowflake, stickers: Vec<Sticker>);
+            /// Called when a channel is created
+            ChannelCreate(channel) => on_channel_create(channel: Box<Channel>);
+            /// Called when a channel is updated
+            ChannelUpdate(channel) => on_channel_update(channel: Box<Channel>);
+            /// Called when a channel is deleted
+            ChannelDelete(channel) => on_channel_delete(channel: Box<Channel>);
+            /// Called when a message is pinned or unpinned in a channel
+            ChannelPinsUpdate { guild_id, channel_id, last_pin_timestamp } => on_channel_pins_update(
+                guild_id: Option<Snowflake>, channel_id: Snowflake, last_pin_timestamp: Option<Timestamp>
+            );
+            /// Called when a thread is created
+            ThreadCreate(thread) => on_thread_create(thread: Box<Channel>);
+            /// Called when a thread is updated
+            ThreadUpdate(thread) => on_thread_update(thread: Box<Channel>);
+            /// Called when a thread is deleted
+            ThreadDelete(thread) => on_thread_delete(thread: Box<Channel>);
+            /// Called when the client gains access to a channel, with its active threads
+            ThreadListSync(sync) => on_thread_list_sync(sync: Box<ThreadListSyncEvent>);
+            /// Called when the client's thread member is updated
+            ThreadMemberUpdate(member) => on_thread_member_update(member: Box<ThreadMemberUpdateEvent>);
+            /// Called when users are added to or removed from a thread
+            ThreadMembersUpdate(update) => on_thread_members_update(update: Box<ThreadMembersUpdateEvent>);
+            /// Called when a guild role is created
+            GuildRoleCreate { guild_id, role } => on_guild_role_create(guild_id: Snowflake, role: Box<Role>);
+            /// Called when a guild role is updated
+            GuildRoleUpdate { guild_id, role } => on_guild_role_update(guild_id: Snowflake, role: Box<Role>);
+            /// Called when a guild role is deleted
+            GuildRoleDelete { guild_id, role_id } => on_guild_role_delete(guild_id: Snowflake, role_id: Snowflake);
+            /// Called when a user joins a guild
+            GuildMemberAdd(member) => on_guild_member_add(member: Box<GuildMemberEvent>);
+            /// Called when a guild member is updated
+            GuildMemberUpdate(member) => on_guild_member_update(member: Box<GuildMemberEvent>);
+            /// Called with a chunk of the members of a guild that were requested
+            GuildMembersChunk(chunk) => on_guild_members_chunk(chunk: Box<GuildMembersChunkEvent>);
+            /// Called when a user leaves or is removed from a guild
+            GuildMemberRemove { guild_id, user } => on_guild_member_remove(guild_id: Snowflake, user: Box<User>);
+            /// Called when a user is banned from a guild
+            GuildBanAdd { guild_id, user } => on_guild_ban_add(guild_id: Snowflake, user: Box<User>);
+            /// Called when a user is unbanned from a guild
+            GuildBanRemove { guild_id, user } => on_guild_ban_remove(guild_id: Snowflake, user: Box<User>);
+            /// Called when an entry is added to a guild's audit log
+            GuildAuditLogEntryCreate(entry) => on_guild_audit_log_entry_create(entry: Box<AuditLogEntry>);
+            /// Called when the integrations of a guild are updated
+            GuildIntegrationsUpdate { guild_id } => on_guild_integrations_update(guild_id: Snowflake);
+            /// Called when an integration is created
+            IntegrationCreate(integration) => on_integration_create(integration: Box<IntegrationEvent>);
+            /// Called when an integration is updated
+            IntegrationUpdate(integration) => on_integration_update(integration: Box<IntegrationEvent>);
+            /// Called when an integration is deleted
+            IntegrationDelete { id, guild_id, application_id }
+                => on_integration_delete(id: Snowflake, guild_id: Snowflake, application_id: Option<Snowflake>);
+            /// Called when a webhook of a channel is created, updated or deleted
+            WebhooksUpdate { guild_id, channel_id }
+                => on_webhooks_update(guild_id: Snowflake, channel_id: Snowflake);
+            /// Called when an invite is created
+            InviteCreate(invite) => on_invite_create(invite: Box<InviteCreateEvent>);
+            /// Called when an invite is deleted
+            InviteDelete { channel_id, guild_id, code }
+                => on_invite_delete(channel_id: Snowflake, guild_id: Option<Snowflake>, code: String);
+            /// Called when an event is scheduled in a guild
+            GuildScheduledEventCreate(event) => on_guild_scheduled_event_create(event: Box<ScheduledEvent>);
+            /// Called when a scheduled event is updated
+            GuildScheduledEventUpdate(event) => on_guild_scheduled_event_update(event: Box<ScheduledEvent>);
+            /// Called when a scheduled event is deleted
+            GuildScheduledEventDelete(event) => on_guild_scheduled_event_delete(event: Box<ScheduledEvent>);
+            /// Called when a user subscribes to a scheduled event
+            GuildScheduledEventUserAdd { guild_scheduled_event_id, user_id, guild_id }
+                => on_guild_scheduled_event_user_add(guild_scheduled_event_id: Snowflake, user_id: Snowflake, guild_id: Snowflake);
+            /// Called when a user unsubscribes from a scheduled event
+            GuildScheduledEventUserRemove { guild_scheduled_event_id, user_id, guild_id }
+                => on_guild_scheduled_event_user_remove(guild_scheduled_event_id: Snowflake, user_id: Snowflake, guild_id: Snowflake);
+            /// Called when an auto moderation rule is created
+            AutoModerationRuleCreate(rule) => on_auto_moderation_rule_create(rule: Box<AutoModerationRule>);
+            /// Called when an auto moderation rule is updated
+            AutoModerationRuleUpdate(rule) => on_auto_moderation_rule_update(rule: Box<AutoModerationRule>);
+            /// Called when an auto moderation rule is deleted
+            AutoModerationRuleDelete(rule) => on_auto_moderation_rule_delete(rule: Box<AutoModerationRule>);
+            /// Called when an auto moderation rule is triggered and an action is taken
+            AutoModerationActionExecution(execution)
+                => on_auto_moderation_action_execution(execution: Box<AutoModerationActionExecutionEvent>);
+            /// Called when a user uses a command, component or modal of the application
+            InteractionCreate(interaction) => on_interaction_create(interaction: Box<Interaction>);
+            /// Called when a message is created
+            MessageCreate(message) => on_message_create(message: Box<Message>);
+            /// Called when a message is edited
+            MessageUpdate(message) => on_message_update(message: Box<PartialMessage>);
+            /// Called when a message is deleted
+            MessageDelete { id, channel_id, guild_id }
+                => on_message_delete(id: Snowflake, channel_id: Snowflake, guild_id: Option<Snowflake>);
+            /// Called when multiple messages are deleted at once
+            MessageDeleteBulk { ids, channel_id, guild_id }
+                => on_message_delete_bulk(ids: Vec<Snowflake>, channel_id: Snowflake, guild_id: Option<Snowflake>);
+            /// Called when a user reacts to a message
+            MessageReactionAdd(reaction) => on_message_reaction_add(reaction: Box<MessageReactionAddEvent>);
+            /// Called when a user removes a reaction from a message
+            MessageReactionRemove(reaction)
+                => on_message_reaction_remove(reaction: Box<MessageReactionRemoveEvent>);
+            /// Called when all reactions are removed from a message
+            MessageReactionRemoveAll { channel_id, message_id, guild_id }
+                => on_message_reaction_remove_all(channel_id: Snowflake, message_id: Snowflake, guild_id: Option<Snowflake>);
+            /// Called when all reactions with an emoji are removed from a message
+            MessageReactionRemoveEmoji(reaction)
+                => on_message_reaction_remove_emoji(reaction: Box<MessageReactionRemoveEmojiEvent>);
+            /// Called when a user starts typing in a channel
+            TypingStart(typing) => on_typing_start(typing: Box<TypingStartEvent>);
+            /// Called when a user's presence is updated
+            PresenceUpdate(presence) => on_presence_update(presence: Box<Presence>);
+            /// Called when a user's voice state is updated, including joining and leaving voice
+            VoiceStateUpdate(voice_state) => on_voice_state_update(voice_state: Box<VoiceState>);
+            /// Called when the voice server of a guild is set or changes
+            VoiceServerUpdate { token, guild_id, endpoint }
+                => on_voice_server_update(token: String, guild_id: Snowflake, endpoint: Option<String>);
+            /// Called with events that are not modelled by this library
+            Unknown { event_type, data } => on_unknown(event_type: String, data: Value);
+        }
+    };
+}
+
+/// Generates the [`EventHandler`] trait, with a default-empty method for each dispatch
+/// event listed by [`dispatch_events`].
+macro_rules! event_handler {
+    ($(
+        $(#[$meta:meta])*
+        $variant:ident $(($($value:ident),*))? $({$($field:ident),*})?
+            => $method:ident($($argument:ident: $type:ty),*);
+    )*) => {
+        /// Receives the dispatch events of a shard, see [`dispatch_to_handler`]. Every
+        /// method does nothing by default, so only the events of interest need to be
+        /// implemented, for example with `async fn on_message_create(&self, ...)`.
+        ///
+        /// Methods take `&self`, so state kept by a handler needs interior mutability.
+        pub trait EventHandler: Send + Sync {
+            $(
+                $(#[$meta])*
+                #[allow(unused_variables, clippy::boxed_local)]
+                fn $method(
+                    &self,
+                    ctx: Context,
+                    $($argument: $type),*
+                ) -> impl Future<Output = ()> + Send {
+                    async {}
+                }
+            )*
+        }
+    };
+}
+
+/// Generates [`dispatch_to_handler`], which calls the method matching an event. The match
+/// fails to compile until every variant listed by [`dispatch_events`] is routed.
+macro_rules! route_dispatch_events {
+    ($(
+        $(#[$meta:meta])*
+        $variant:ident $(($($value:ident),*))? $({$($field:ident),*})?
+            => $method:ident($($argument:ident: $type:ty),*);
+    )*) => {
+        /// Calls the method of `handler` matching `event`.
+        pub async fn dispatch_to_handler(
+            event: DispatchEvent,
+            handler: &impl EventHandler,
+            ctx: Context,
+        ) {
+            match event {
+                $(
+                    DispatchEvent::$variant $(($($value),*))? $({$($field),*})?
+                        => handler.$method(ctx, $($argument),*).await,
+                )*
+            }
+        }
+    };
+}
+
+dispatch_events!(event_handler);
+dispatch_events!(route_dispatch_events);
+
+/// Runs `manager` until it stops, see [`ShardManager::run`], calling the method of
+/// `handler` matching each dispatch event received by its shards. Events are handled one
+/// at a time in the order they were received, while the shards keep running.
+///
+/// This replaces the receiver returned by an earlier call to [`ShardManager::events`].
+pub async fn run_handler(manager: &mut ShardManager, handler: impl EventHandler) -> Result<()> {
+    let total = manager
+        .shards
+        .iter()
+        .find_map(|shard| shard.shard_information)
+        .map_or(1, |shard_information| shard_information.total);
+    let mut events = manager.events();
+
+    let result = {
+        let mut run = pin!(manager.run());
+        loop {
+            tokio::select! {
+                result = &mut run => break result,
+                Some(incoming) = events.recv() => handle_incoming(incoming, &handler, total).await,
+            }
+        }
+    };
+    // Events received before the manager stopped are still handled
+    while let Ok(incoming) = events.try_recv() {
+        handle_incoming(incoming, &handler, total).await;
+    }
+    result
+}
+
+async fn handle_incoming(incoming: Incoming, handler: &impl EventHandler, total: u64) {
+    if let Incoming::Dispatch(SequencedDispatch {
+        shard_id, event, ..
+    }) = incoming
+    {
+        let ctx = Context {
+            shard: ShardInformation {
+                id: shard_id,
+                total,
+            },
+        };
+        dispatch_to_handler(event, handler, ctx).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+
+    use serde_json::json;
+    use tokio::time::timeout;
+
+    use super::{dispatch_to_handler, run_handler, Context, EventHandler};
+    use crate::{
+        gateway::{
+            mock::MockGateway,
+            shard::{Shard, ShardInformation},
+            shard_manager::ShardManager,
+        },
+        model::{
+            channel::message::Message,
+            gateway::{
+                dispatch::{DispatchEvent, ReadyEvent},
+                intents::GatewayIntents,
+            },
+        },
+    };
+
+    #[derive(Debug, Default)]
+    struct Recorder {
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl EventHandler for Recorder {
+        async fn on_ready(&self, _ctx: Context, _ready: Box<ReadyEvent>) {
+            self.calls.lock().unwrap().push("ready".to_string());
+        }
+
+        async fn on_message_create(&self, ctx: Context, message: Box<Message>) {
+            self.calls.lock().unwrap().push(format!(
+                "message {} on shard {}",
+                message.content, ctx.shard.id
+            ));
+        }
+    }
+
+    /// Counts the messages created, holding the count behind an [`Arc`] so that it can
+    /// be read while the handler runs
+    #[derive(Debug, Default)]
+    struct MessageCounter {
+        messages: Arc<AtomicUsize>,
+    }
+
+    impl EventHandler for MessageCounter {
+        async fn on_message_create(&self, _ctx: Context, _message: Box<Message>) {
+            self.messages.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn message_create_payload() -> serde_json::Value {
+        json!({
+            "id": "334385199974967042",
+            "channel_id": "290926798999357250",
+            "author": {
+                "id": "53908099506183680",
+                "username": "Mason",
+                "discriminator": "0",
+                "global_name": "Mason",
+                "avatar": null,
+            },
+            "content": "Supa Hot",
+            "timestamp": "2017-07-11T17:27:07.299000+00:00",
+            "edited_timestamp": null,
+            "tts": false,
+            "mention_everyone": false,
+            "mentions": [],
+            "attachments": [],
+            "embeds": [],
+            "pinned": false,
+        })
+    }
+
+    #[tokio::test]
+    async fn run_handler_counts_message_creates() {
+        let gateway = MockGateway::bind().await;
+        let mut manager = ShardManager::from_shard(
+            Shard::new(
+                &gateway.url,
+                "token",
+                ShardInformation { id: 0, total: 1 },
+                GatewayIntents::non_privileged(),
+            )
+            .await
+            .unwrap(),
+        );
+        let handle = manager.handle();
+        let counter = MessageCounter::default();
+        let messages = counter.messages.clone();
+        let run = tokio::spawn(async move { run_handler(&mut manager, counter).await });
+
+        gateway.send_ready();
+        for sequence in 2..5 {
+            gateway.send_json(&json!({
+                "op": 0,
+                "s": sequence,
+                "t": "MESSAGE_CREATE",
+                "d": message_create_payload(),
+            }));
+        }
+        timeout(Duration::from_secs(5), async {
+            while messages.load(Ordering::SeqCst) < 3 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("every message should be handled");
+
+        handle.shutdown();
+        let result = timeout(Duration::from_secs(5), run)
+            .await
+            .expect("run_handler should return once shut down")
+            .unwrap();
+        assert!(result.is_ok());
+        assert_eq!(messages.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn message_create_routes_to_on_message_create() {
+        let message_create: DispatchEvent = serde_json::from_value(json!({
+            "t": "MESSAGE_CREATE",
+            "d": message_create_payload(),
+        }))
+        .unwrap();
+        let handler = Recorder::default();
+        let ctx = Context {
+            shard: ShardInformation { id: 1, total: 2 },
+        };
+
+        dispatch_to_handler(message_create, &handler, ctx).await;
+        // Events without a method implemented are ignored
+        dispatch_to_handler(DispatchEvent::Resumed, &handler, ctx).await;
+
+        assert_eq!(
+            *handler.calls.lock().unwrap(),
+            ["message Supa Hot on shard 1"]
+        );
+    }
+}
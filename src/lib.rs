@@ -13,41 +13,35 @@
 //!
 //! A new in-development Discord library written in Rust
 
+#[macro_use]
+mod macros;
+
+pub mod cache;
 pub mod error;
 pub mod gateway;
+pub mod handler;
 pub mod model;
 
 #[tokio::test]
+#[ignore = "connects to Discord with DISCORD_TOKEN and never returns"]
 async fn test() {
-    let _ = dotenvy::dotenv();
-    use gateway::{
-        shard::{Shard, ShardInformation},
-        shard_manager::ShardManager,
-    };
+    use gateway::shard_manager::ShardManager;
     use model::gateway::intents::GatewayIntents;
 
+    let _ = dotenvy::dotenv();
     let total_shards = 1;
-    for i in 0..total_shards {
-        tokio::task::spawn(async move {
-            ShardManager {
-                shard: Shard::new(
-                    "wss://gateway.discord.gg/?v=10&encoding=json",
-                    std::env::var("DISCORD_TOKEN").unwrap().as_str(),
-                    ShardInformation {
-                        id: i,
-                        total: total_shards,
-                    },
-                    GatewayIntents::non_privileged(),
-                )
-                .await
-                .unwrap(),
-            }
-            .run()
-            .await
-            .unwrap();
-        });
-        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-    }
+    tokio::task::spawn(async move {
+        ShardManager::new(
+            total_shards,
+            std::env::var("DISCORD_TOKEN").unwrap().as_str(),
+            GatewayIntents::non_privileged(),
+        )
+        .await
+        .unwrap()
+        .run()
+        .await
+        .unwrap();
+    });
     loop {
         tokio::time::sleep(std::time::Duration::from_secs(1)).await;
     }
@@ -0,0 +1,237 @@
+//! The message module contains all the message-related structs and enums.
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{
+    snowflake::Snowflake,
+    sticker::{Sticker, StickerItem},
+    timestamp::Timestamp,
+    user::User,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// A file attached to a message
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/message#attachment-object)
+pub struct Attachment {
+    /// The attachment ID
+    pub id: Snowflake,
+    /// The name of the attached file
+    pub filename: String,
+    /// The attachment's [media type](https://en.wikipedia.org/wiki/Media_type)
+    pub content_type: Option<String>,
+    /// The size of the file in bytes
+    pub size: u64,
+    /// The source URL of the file
+    pub url: String,
+    /// A proxied URL of the file
+    pub proxy_url: String,
+    /// The height of the file, if it is an image
+    pub height: Option<u32>,
+    /// The width of the file, if it is an image
+    pub width: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// A field shown in an [`Embed`]
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/message#embed-object-embed-field-structure)
+pub struct EmbedField {
+    /// The name of the field
+    pub name: String,
+    /// The value of the field
+    pub value: String,
+    /// Whether the field is displayed inline with other inline fields
+    #[serde(default)]
+    pub inline: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+/// Rich content shown in a message
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/message#embed-object)
+pub struct Embed {
+    /// The title of the embed
+    pub title: Option<String>,
+    /// The type of the embed, which is always `rich` for embeds sent by bots
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    /// The description of the embed
+    pub description: Option<String>,
+    /// The URL of the embed
+    pub url: Option<String>,
+    /// The timestamp shown in the embed's footer
+    pub timestamp: Option<Timestamp>,
+    /// The color code of the embed
+    pub color: Option<u32>,
+    /// The fields of the embed
+    #[serde(default)]
+    pub fields: Vec<EmbedField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// A message sent in a channel
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/message#message-object)
+pub struct Message {
+    /// The message ID
+    pub id: Snowflake,
+    /// The ID of the channel the message was sent in
+    pub channel_id: Snowflake,
+    /// The ID of the guild the message was sent in, only sent with `MESSAGE_CREATE` and
+    /// `MESSAGE_UPDATE` events
+    pub guild_id: Option<Snowflake>,
+    /// The author of the message. This is a webhook rather than a user if the message was
+    /// sent by a webhook.
+    pub author: User,
+    /// The contents of the message
+    ///
+    /// *Note: This is empty without the `MESSAGE_CONTENT` privileged intent, unless the
+    /// message mentions or was sent to the client, or was sent by the client itself.*
+    pub content: String,
+    /// When the message was sent
+    pub timestamp: Timestamp,
+    /// When the message was last edited, if it has been
+    pub edited_timestamp: Option<Timestamp>,
+    /// Whether the message was sent as a text-to-speech message
+    pub tts: bool,
+    /// Whether the message mentions everyone
+    pub mention_everyone: bool,
+    /// The users mentioned in the message
+    pub mentions: Vec<User>,
+    /// The files attached to the message
+    pub attachments: Vec<Attachment>,
+    /// The embeds in the message
+    pub embeds: Vec<Embed>,
+    /// Whether the message is pinned
+    pub pinned: bool,
+    /// The stickers sent with the message
+    #[serde(default)]
+    pub sticker_items: Vec<StickerItem>,
+    /// The full stickers sent with the message
+    #[deprecated = "Discord no longer sends this, use `sticker_items` instead"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stickers: Vec<Sticker>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// A message with only the fields that were sent, such as with `MESSAGE_UPDATE` events
+/// where only the edited fields are guaranteed to be present
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#message-update)
+pub struct PartialMessage {
+    /// The message ID
+    pub id: Snowflake,
+    /// The ID of the channel the message was sent in
+    pub channel_id: Snowflake,
+    /// The ID of the guild the message was sent in
+    pub guild_id: Option<Snowflake>,
+    /// The author of the message
+    pub author: Option<User>,
+    /// The contents of the message
+    pub content: Option<String>,
+    /// When the message was sent
+    pub timestamp: Option<Timestamp>,
+    /// When the message was last edited, if it has been
+    pub edited_timestamp: Option<Timestamp>,
+    /// Whether the message was sent as a text-to-speech message
+    pub tts: Option<bool>,
+    /// Whether the message mentions everyone
+    pub mention_everyone: Option<bool>,
+    /// The users mentioned in the message
+    pub mentions: Option<Vec<User>>,
+    /// The files attached to the message
+    pub attachments: Option<Vec<Attachment>>,
+    /// The embeds in the message
+    pub embeds: Option<Vec<Embed>>,
+    /// Whether the message is pinned
+    pub pinned: Option<bool>,
+    /// The stickers sent with the message
+    pub sticker_items: Option<Vec<StickerItem>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::Message;
+    use crate::model::{snowflake::Snowflake, sticker::StickerFormatType};
+
+    #[test]
+    fn deserialize_message_create() {
+        let message: Message = serde_json::from_value(json!({
+            "type": 0,
+            "tts": false,
+            "timestamp": "2017-07-11T17:27:07.299000+00:00",
+            "pinned": false,
+            "mentions": [],
+            "mention_roles": [],
+            "mention_everyone": false,
+            "member": {
+                "roles": [],
+                "mute": false,
+                "joined_at": "2017-03-13T19:19:14.040000+00:00",
+                "hoisted_role": null,
+                "deaf": false,
+            },
+            "id": "334385199974967042",
+            "flags": 0,
+            "embeds": [{ "type": "rich", "title": "Hello", "fields": [] }],
+            "edited_timestamp": null,
+            "content": "Supa Hot",
+            "components": [],
+            "channel_id": "290926798999357250",
+            "author": {
+                "username": "Mason",
+                "public_flags": 131_328,
+                "id": "53908099506183680",
+                "global_name": "Mason",
+                "discriminator": "0",
+                "avatar": "a_bab14f271d565501444b2ca3be944b25",
+            },
+            "attachments": [],
+            "guild_id": "290926798999357250",
+        }))
+        .unwrap();
+
+        assert_eq!(message.id, Snowflake(334_385_199_974_967_042));
+        assert_eq!(message.author.username, "Mason");
+        assert_eq!(message.content, "Supa Hot");
+        assert_eq!(message.embeds[0].title.as_deref(), Some("Hello"));
+        assert_eq!(message.edited_timestamp, None);
+    }
+
+    #[test]
+    fn deserialize_message_with_sticker_items() {
+        let message: Message = serde_json::from_value(json!({
+            "id": "334385199974967042",
+            "channel_id": "290926798999357250",
+            "author": {
+                "id": "53908099506183680",
+                "username": "Mason",
+                "discriminator": "0",
+                "global_name": "Mason",
+                "avatar": null,
+            },
+            "content": "",
+            "timestamp": "2017-07-11T17:27:07.299000+00:00",
+            "edited_timestamp": null,
+            "tts": false,
+            "mention_everyone": false,
+            "mentions": [],
+            "attachments": [],
+            "embeds": [],
+            "pinned": false,
+            "sticker_items": [{ "id": "749054660769218631", "name": "Wave", "format_type": 3 }],
+        }))
+        .unwrap();
+
+        let sticker = &message.sticker_items[0];
+        assert_eq!(sticker.id, Snowflake(749_054_660_769_218_631));
+        assert_eq!(sticker.name, "Wave");
+        assert_eq!(sticker.format_type, StickerFormatType::Lottie);
+        let serialized = serde_json::to_value(&message).unwrap();
+        assert_eq!(serialized["sticker_items"][0]["format_type"], 3);
+        assert!(serialized.get("stickers").is_none());
+    }
+}
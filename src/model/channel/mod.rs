@@ -0,0 +1,316 @@
+//! The channel module contains all the channel-related structs and enums.
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    guild::member::GuildMember, permissions::Permissions, snowflake::Snowflake,
+    timestamp::Timestamp,
+};
+
+pub mod message;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+#[non_exhaustive]
+/// The type of a channel
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/channel#channel-object-channel-types)
+pub enum ChannelType {
+    /// A text channel within a guild
+    GuildText,
+    /// A direct message between users
+    Dm,
+    /// A voice channel within a guild
+    GuildVoice,
+    /// A direct message between multiple users
+    GroupDm,
+    /// An organizational category that contains up to 50 channels
+    GuildCategory,
+    /// A channel that users can follow and crosspost into their own guild
+    GuildAnnouncement,
+    /// A temporary sub-channel within a [`ChannelType::GuildAnnouncement`] channel
+    AnnouncementThread,
+    /// A temporary sub-channel within a [`ChannelType::GuildText`] or
+    /// [`ChannelType::GuildForum`] channel
+    PublicThread,
+    /// A temporary sub-channel within a [`ChannelType::GuildText`] channel that is only
+    /// viewable by those invited and those with the `MANAGE_THREADS` permission
+    PrivateThread,
+    /// A voice channel for hosting events with an audience
+    GuildStageVoice,
+    /// The channel in a hub containing the listed servers
+    GuildDirectory,
+    /// A channel that can only contain threads
+    GuildForum,
+    /// A channel that can only contain threads, similar to [`ChannelType::GuildForum`]
+    GuildMedia,
+    /// A channel type that is not yet documented
+    Unknown(u8),
+}
+
+impl From<ChannelType> for u8 {
+    fn from(value: ChannelType) -> Self {
+        match value {
+            ChannelType::GuildText => 0,
+            ChannelType::Dm => 1,
+            ChannelType::GuildVoice => 2,
+            ChannelType::GroupDm => 3,
+            ChannelType::GuildCategory => 4,
+            ChannelType::GuildAnnouncement => 5,
+            ChannelType::AnnouncementThread => 10,
+            ChannelType::PublicThread => 11,
+            ChannelType::PrivateThread => 12,
+            ChannelType::GuildStageVoice => 13,
+            ChannelType::GuildDirectory => 14,
+            ChannelType::GuildForum => 15,
+            ChannelType::GuildMedia => 16,
+            ChannelType::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for ChannelType {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => ChannelType::GuildText,
+            1 => ChannelType::Dm,
+            2 => ChannelType::GuildVoice,
+            3 => ChannelType::GroupDm,
+            4 => ChannelType::GuildCategory,
+            5 => ChannelType::GuildAnnouncement,
+            10 => ChannelType::AnnouncementThread,
+            11 => ChannelType::PublicThread,
+            12 => ChannelType::PrivateThread,
+            13 => ChannelType::GuildStageVoice,
+            14 => ChannelType::GuildDirectory,
+            15 => ChannelType::GuildForum,
+            16 => ChannelType::GuildMedia,
+            _ => ChannelType::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+/// Whether a permission overwrite applies to a role or a member
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/channel#overwrite-object)
+pub enum OverwriteType {
+    /// The overwrite applies to a role
+    Role,
+    /// The overwrite applies to a member
+    Member,
+    /// An overwrite type that is not yet documented
+    Unknown(u8),
+}
+
+impl From<OverwriteType> for u8 {
+    fn from(value: OverwriteType) -> Self {
+        match value {
+            OverwriteType::Role => 0,
+            OverwriteType::Member => 1,
+            OverwriteType::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for OverwriteType {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => OverwriteType::Role,
+            1 => OverwriteType::Member,
+            _ => OverwriteType::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Permissions explicitly allowed or denied to a role or member in a channel
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/channel#overwrite-object)
+pub struct PermissionOverwrite {
+    /// The ID of the role or user
+    pub id: Snowflake,
+    /// Whether `id` is a role or a user
+    #[serde(rename = "type")]
+    pub kind: OverwriteType,
+    /// The permissions that are allowed
+    pub allow: Permissions,
+    /// The permissions that are denied
+    pub deny: Permissions,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// The fields of a thread that are not needed by other channels
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/channel#thread-metadata-object)
+pub struct ThreadMetadata {
+    /// Whether the thread is archived
+    pub archived: bool,
+    /// The number of minutes of inactivity after which the thread is archived
+    pub auto_archive_duration: u32,
+    /// When the thread was last archived or unarchived
+    pub archive_timestamp: Timestamp,
+    /// Whether the thread is locked, so only members with `MANAGE_THREADS` can unarchive it
+    pub locked: bool,
+    /// Whether non-moderators can add other non-moderators to a private thread
+    pub invitable: Option<bool>,
+    /// When the thread was created, which is only sent for threads created after 2022-01-09
+    pub create_timestamp: Option<Timestamp>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// A user that has joined a thread
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/channel#thread-member-object)
+pub struct ThreadMember {
+    /// The ID of the thread, which is missing from the member sent with a thread in
+    /// `GUILD_CREATE`
+    pub id: Option<Snowflake>,
+    /// The ID of the user, which is missing from the member sent with a thread in
+    /// `GUILD_CREATE`
+    pub user_id: Option<Snowflake>,
+    /// When the user last joined the thread
+    pub join_timestamp: Timestamp,
+    /// The user's thread flags, which are only used for notifications
+    pub flags: u64,
+    /// The guild member of the user, only sent with `THREAD_MEMBERS_UPDATE`
+    #[serde(default)]
+    pub member: Option<GuildMember>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// The channel object, representing a guild or DM channel
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/channel#channel-object)
+pub struct Channel {
+    /// The channel ID
+    pub id: Snowflake,
+    /// The type of channel
+    #[serde(rename = "type")]
+    pub kind: ChannelType,
+    /// The ID of the guild the channel is in. This is missing from channels sent as part of
+    /// a guild, such as in `GUILD_CREATE`, and from DM channels.
+    #[serde(default)]
+    pub guild_id: Option<Snowflake>,
+    /// The sorting position of the channel
+    #[serde(default)]
+    pub position: Option<i32>,
+    /// The permission overwrites of the channel's members and roles
+    #[serde(default)]
+    pub permission_overwrites: Vec<PermissionOverwrite>,
+    /// The channel name (1-100 characters)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The channel topic (0-4096 characters for forum and media channels, 0-1024 characters
+    /// for all others)
+    #[serde(default)]
+    pub topic: Option<String>,
+    /// Whether the channel is age-restricted
+    #[serde(default)]
+    pub nsfw: bool,
+    /// The ID of the parent category for a guild channel, or the channel a thread was
+    /// created in
+    #[serde(default)]
+    pub parent_id: Option<Snowflake>,
+    /// The ID of the creator of a thread
+    #[serde(default)]
+    pub owner_id: Option<Snowflake>,
+    /// The number of messages in a thread, excluding the initial message and deleted messages
+    #[serde(default)]
+    pub message_count: Option<u32>,
+    /// An approximate count of the users in a thread, which stops counting at 50
+    #[serde(default)]
+    pub member_count: Option<u32>,
+    /// The thread-specific fields of a thread
+    #[serde(default)]
+    pub thread_metadata: Option<ThreadMetadata>,
+    /// The client's thread member, if the client has joined the thread
+    #[serde(default)]
+    pub member: Option<ThreadMember>,
+}
+
+impl Channel {
+    /// Whether the channel is a thread
+    #[must_use]
+    pub fn is_thread(&self) -> bool {
+        matches!(
+            self.kind,
+            ChannelType::AnnouncementThread
+                | ChannelType::PublicThread
+                | ChannelType::PrivateThread
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Channel, ChannelType, OverwriteType};
+    use crate::model::{permissions::Permissions, snowflake::Snowflake};
+
+    #[test]
+    fn deserialize_text_channel() {
+        let channel: Channel = serde_json::from_value(json!({
+            "id": "41771983423143937",
+            "guild_id": "41771983423143937",
+            "name": "general",
+            "type": 0,
+            "position": 6,
+            "permission_overwrites": [{
+                "id": "41771983423143937",
+                "type": 0,
+                "allow": "0",
+                "deny": "1024",
+            }],
+            "rate_limit_per_user": 2,
+            "nsfw": true,
+            "topic": "24/7 chat about how to gank Mike #2",
+            "last_message_id": "155117677105512449",
+            "parent_id": "399942396007890945",
+            "default_auto_archive_duration": 60,
+        }))
+        .unwrap();
+
+        assert_eq!(channel.kind, ChannelType::GuildText);
+        assert_eq!(channel.position, Some(6));
+        assert_eq!(channel.permission_overwrites[0].kind, OverwriteType::Role);
+        assert_eq!(
+            channel.permission_overwrites[0].deny,
+            Permissions::VIEW_CHANNEL
+        );
+        assert!(channel.nsfw);
+        assert_eq!(channel.parent_id, Some(Snowflake(399_942_396_007_890_945)));
+    }
+
+    #[test]
+    fn deserialize_voice_channel() {
+        let channel: Channel = serde_json::from_value(json!({
+            "id": "155101607195836416",
+            "last_message_id": "174629835082649376",
+            "type": 2,
+            "name": "ROCKET CHEESE",
+            "position": 5,
+            "parent_id": null,
+            "bitrate": 64000,
+            "user_limit": 0,
+            "rtc_region": null,
+            "guild_id": "41771983423143937",
+            "permission_overwrites": [],
+            "rate_limit_per_user": 0,
+            "nsfw": false,
+        }))
+        .unwrap();
+
+        assert_eq!(channel.kind, ChannelType::GuildVoice);
+        assert_eq!(channel.topic, None);
+        assert_eq!(channel.parent_id, None);
+    }
+
+    #[test]
+    fn unknown_channel_type() {
+        assert_eq!(ChannelType::from(99), ChannelType::Unknown(99));
+        assert_eq!(u8::from(ChannelType::Unknown(99)), 99);
+    }
+}
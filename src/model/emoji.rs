@@ -0,0 +1,27 @@
+//! The emoji module contains all the emoji-related structs and enums.
+
+use serde::{Deserialize, Serialize};
+
+use super::snowflake::Snowflake;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// A custom or unicode emoji
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/emoji#emoji-object)
+pub struct Emoji {
+    /// The emoji ID, or `None` for unicode emojis
+    pub id: Option<Snowflake>,
+    /// The name of a custom emoji or the unicode character of a unicode emoji. This may be
+    /// `None` for custom emojis in reactions if the emoji was deleted.
+    pub name: Option<String>,
+    /// Whether the custom emoji is animated
+    pub animated: Option<bool>,
+}
+
+impl Emoji {
+    /// Whether this is a unicode emoji rather than a custom one
+    #[must_use]
+    pub fn is_unicode(&self) -> bool {
+        self.id.is_none()
+    }
+}
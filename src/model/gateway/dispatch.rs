@@ -2,18 +2,46 @@
 //! updates and other information, paramount to the functionality of the
 //! client.
 
-use serde::Deserialize;
+use std::{collections::HashMap, fmt};
 
-use crate::model::{guild::UnavailableGuild, user::User};
+use serde::{
+    de::{value::MapDeserializer, Error as DeError},
+    Deserialize, Deserializer,
+};
+use serde_json::{Map, Value};
+
+use crate::model::{
+    channel::{
+        message::{Message, PartialMessage},
+        Channel, ThreadMember,
+    },
+    emoji::Emoji,
+    gateway::presence::Presence,
+    guild::{
+        audit_log::AuditLogEntry,
+        auto_moderation::{AutoModerationAction, AutoModerationRule, AutoModerationTriggerType},
+        integration::Integration,
+        member::GuildMember,
+        role::Role,
+        scheduled_event::ScheduledEvent,
+        Guild, UnavailableGuild,
+    },
+    interaction::Interaction,
+    snowflake::Snowflake,
+    soundboard::SoundboardSound,
+    sticker::Sticker,
+    timestamp::Timestamp,
+    user::User,
+    voice::VoiceState,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
 /// `READY` is sent from the gateway
 ///
 /// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#ready)
 pub struct ReadyEvent {
-    /// The gateway API version. By default, this value is 6, but we should
-    /// attempt to connect to the latest version, which at the time of writing
-    /// is 10.
+    /// The gateway API version, which is the version requested when connecting, see
+    /// [`ShardBuilder::gateway_version`](crate::gateway::builder::ShardBuilder::gateway_version)
     pub v: u16,
     /// Our current user, this includes the email address.
     pub user: User,
@@ -31,15 +59,305 @@ pub struct ReadyEvent {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
-pub struct GuildCreateEvent {}
+/// `GUILD_CREATE` is sent when a guild becomes available again after being unavailable,
+/// including the initial burst after `READY`, or when the client joins a new guild.
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#guild-create)
+pub struct GuildCreateEvent(pub Guild);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+/// `GUILD_UPDATE` is sent when a guild is updated. Unlike `GUILD_CREATE`, the guild
+/// doesn't include its channels or members.
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#guild-update)
+pub struct GuildUpdateEvent(pub Guild);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+/// `GUILD_DELETE` is sent when a guild becomes unavailable due to an outage, or when the
+/// client leaves or is removed from a guild.
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#guild-delete)
+pub struct GuildDeleteEvent(pub UnavailableGuild);
+
+impl GuildDeleteEvent {
+    /// Whether the guild became unavailable due to an outage, rather than the client
+    /// leaving or being removed from it
+    #[must_use]
+    pub fn is_outage(&self) -> bool {
+        self.0.unavailable
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+/// `GUILD_MEMBER_ADD` and `GUILD_MEMBER_UPDATE` are sent with the member's fields
+/// alongside the ID of their guild
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#guild-member-add)
+pub struct GuildMemberEvent {
+    /// The ID of the guild
+    pub guild_id: Snowflake,
+    /// The member that joined or was updated
+    #[serde(flatten)]
+    pub member: GuildMember,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+/// `INTEGRATION_CREATE` and `INTEGRATION_UPDATE` are sent with the integration's fields
+/// alongside the ID of its guild
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#integration-create)
+pub struct IntegrationEvent {
+    /// The ID of the guild
+    pub guild_id: Snowflake,
+    /// The integration that was created or updated
+    #[serde(flatten)]
+    pub integration: Integration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+/// `INVITE_CREATE` is sent when an invite to a channel is created
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#invite-create)
+pub struct InviteCreateEvent {
+    /// The ID of the channel the invite is for
+    pub channel_id: Snowflake,
+    /// The unique invite code
+    pub code: String,
+    /// When the invite was created
+    pub created_at: Timestamp,
+    /// The ID of the guild of the invite, if any
+    pub guild_id: Option<Snowflake>,
+    /// The user who created the invite
+    pub inviter: Option<User>,
+    /// How long the invite is valid for in seconds, or 0 if it never expires
+    pub max_age: u32,
+    /// The maximum number of times the invite can be used, or 0 if there is no limit
+    pub max_uses: u32,
+    /// Whether the invite only grants temporary membership
+    pub temporary: bool,
+    /// How many times the invite has been used, which is always 0
+    pub uses: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+/// `AUTO_MODERATION_ACTION_EXECUTION` is sent when a rule is triggered and an action is
+/// taken, once for every action of the rule
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#auto-moderation-action-execution)
+pub struct AutoModerationActionExecutionEvent {
+    /// The ID of the guild
+    pub guild_id: Snowflake,
+    /// The action that was taken
+    pub action: AutoModerationAction,
+    /// The ID of the rule that was triggered
+    pub rule_id: Snowflake,
+    /// What triggered the rule
+    pub rule_trigger_type: AutoModerationTriggerType,
+    /// The ID of the user whose content triggered the rule
+    pub user_id: Snowflake,
+    /// The ID of the channel the content was sent in, if any
+    pub channel_id: Option<Snowflake>,
+    /// The ID of the message, unless it was blocked
+    pub message_id: Option<Snowflake>,
+    /// The ID of the alert message sent by the action, if any
+    pub alert_system_message_id: Option<Snowflake>,
+    /// The content that triggered the rule, which is empty without the
+    /// `MESSAGE_CONTENT` privileged intent
+    pub content: Option<String>,
+    /// The keyword that was matched
+    pub matched_keyword: Option<String>,
+    /// The part of the content that matched the keyword, which is empty without the
+    /// `MESSAGE_CONTENT` privileged intent
+    pub matched_content: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+/// `THREAD_LIST_SYNC` is sent when the client gains access to a channel, with the active
+/// threads in it that the client can view
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#thread-list-sync)
+pub struct ThreadListSyncEvent {
+    /// The ID of the guild
+    pub guild_id: Snowflake,
+    /// The IDs of the parent channels whose threads are being synced. If this is `None`,
+    /// the threads of every channel in the guild are being synced.
+    #[serde(default)]
+    pub channel_ids: Option<Vec<Snowflake>>,
+    /// The active threads in the channels
+    pub threads: Vec<Channel>,
+    /// The client's thread members for the threads it has joined
+    pub members: Vec<ThreadMember>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+/// `THREAD_MEMBER_UPDATE` is sent when the client's thread member is updated, with the
+/// member's fields alongside the ID of its guild
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#thread-member-update)
+pub struct ThreadMemberUpdateEvent {
+    /// The ID of the guild
+    pub guild_id: Snowflake,
+    /// The client's thread member
+    #[serde(flatten)]
+    pub member: ThreadMember,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+/// `THREAD_MEMBERS_UPDATE` is sent when users are added to or removed from a thread
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#thread-members-update)
+pub struct ThreadMembersUpdateEvent {
+    /// The ID of the thread
+    pub id: Snowflake,
+    /// The ID of the guild
+    pub guild_id: Snowflake,
+    /// An approximate count of the users in the thread, which stops counting at 50
+    pub member_count: u32,
+    /// The users that were added to the thread
+    #[serde(default)]
+    pub added_members: Vec<ThreadMember>,
+    /// The IDs of the users that were removed from the thread
+    #[serde(default)]
+    pub removed_member_ids: Vec<Snowflake>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+/// `TYPING_START` is sent when a user starts typing in a channel
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#typing-start)
+pub struct TypingStartEvent {
+    /// The ID of the channel
+    pub channel_id: Snowflake,
+    /// The ID of the guild, if the channel is in one
+    pub guild_id: Option<Snowflake>,
+    /// The ID of the user
+    pub user_id: Snowflake,
+    /// The unix time in seconds of when the user started typing
+    pub timestamp: u64,
+    /// The member who started typing, if the channel is in a guild
+    pub member: Option<GuildMember>,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
-pub struct GuildUpdateEvent {}
+/// `MESSAGE_REACTION_ADD` is sent when a user adds a reaction to a message
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#message-reaction-add)
+pub struct MessageReactionAddEvent {
+    /// The ID of the user who reacted
+    pub user_id: Snowflake,
+    /// The ID of the channel
+    pub channel_id: Snowflake,
+    /// The ID of the message
+    pub message_id: Snowflake,
+    /// The ID of the guild, if the channel is in one
+    pub guild_id: Option<Snowflake>,
+    /// The member who reacted, if the channel is in a guild
+    pub member: Option<GuildMember>,
+    /// The emoji used to react
+    pub emoji: Emoji,
+    /// The ID of the user who sent the message
+    pub message_author_id: Option<Snowflake>,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
-pub struct GuildDeleteEvent {}
+/// `MESSAGE_REACTION_REMOVE` is sent when a user removes a reaction from a message
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#message-reaction-remove)
+pub struct MessageReactionRemoveEvent {
+    /// The ID of the user whose reaction was removed
+    pub user_id: Snowflake,
+    /// The ID of the channel
+    pub channel_id: Snowflake,
+    /// The ID of the message
+    pub message_id: Snowflake,
+    /// The ID of the guild, if the channel is in one
+    pub guild_id: Option<Snowflake>,
+    /// The emoji that was removed
+    pub emoji: Emoji,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+/// `MESSAGE_REACTION_REMOVE_EMOJI` is sent when all reactions with an emoji are removed
+/// from a message
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#message-reaction-remove-emoji)
+pub struct MessageReactionRemoveEmojiEvent {
+    /// The ID of the channel
+    pub channel_id: Snowflake,
+    /// The ID of the guild, if the channel is in one
+    pub guild_id: Option<Snowflake>,
+    /// The ID of the message
+    pub message_id: Snowflake,
+    /// The emoji that was removed
+    pub emoji: Emoji,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+/// `GUILD_MEMBERS_CHUNK` is sent in response to requesting the members of a guild, in as
+/// many chunks as needed to send every member that was requested. See
+/// [`GuildMembersChunkCollector`] for reassembling them.
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#guild-members-chunk)
+pub struct GuildMembersChunkEvent {
+    /// The ID of the guild
+    pub guild_id: Snowflake,
+    /// The members in this chunk
+    pub members: Vec<GuildMember>,
+    /// The index of this chunk, starting at 0
+    pub chunk_index: u32,
+    /// The number of chunks sent for the request
+    pub chunk_count: u32,
+    /// The requested user IDs that aren't members of the guild
+    pub not_found: Option<Vec<Snowflake>>,
+    /// The presences of the members, if they were requested
+    pub presences: Option<Vec<Presence>>,
+    /// The nonce sent with the request
+    pub nonce: Option<String>,
+}
+
+#[derive(Debug, Default)]
+/// Buffers the chunks of each guild members request until every chunk was received.
+/// Requests to the same guild are told apart by their nonce, so a request without a nonce
+/// shouldn't be made while another one to the same guild is in progress.
+pub struct GuildMembersChunkCollector {
+    pending: HashMap<(Snowflake, Option<String>), (u32, Vec<GuildMember>)>,
+}
+
+impl GuildMembersChunkCollector {
+    /// Adds a chunk, returning every member sent for its request once this was the last
+    /// chunk to arrive.
+    pub fn push(&mut self, chunk: GuildMembersChunkEvent) -> Option<Vec<GuildMember>> {
+        let key = (chunk.guild_id, chunk.nonce);
+        let (received, members) = self.pending.entry(key.clone()).or_default();
+        *received += 1;
+        members.extend(chunk.members);
+        if *received < chunk.chunk_count {
+            return None;
+        }
+        self.pending.remove(&key).map(|(_, members)| members)
+    }
+
+    /// The number of requests with chunks that haven't all been received
+    #[must_use]
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+/// `SOUNDBOARD_SOUNDS` is sent in response to requesting a guild's soundboard sounds, and
+/// `GUILD_SOUNDBOARD_SOUNDS_UPDATE` when several of them are updated at once
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#soundboard-sounds)
+pub struct SoundboardSoundsEvent {
+    /// The guild's soundboard sounds
+    pub soundboard_sounds: Vec<SoundboardSound>,
+    /// The ID of the guild
+    pub guild_id: Snowflake,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(remote = "Self")]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[serde(tag = "t", content = "d")]
 /// The type of event that is dispatched by the gateway.
@@ -47,12 +365,1286 @@ pub struct GuildDeleteEvent {}
 /// A list of all the events can be found [here](https://discord.com/developers/docs/events/gateway-events#receive-events).
 ///
 /// *Note: Some items in this list are not provided via a Dispatch event, but through other events.*
+///
+/// Large events are boxed so that moving a `DispatchEvent` stays cheap. Events that
+/// are not modelled yet are decoded as [`DispatchEvent::Unknown`] rather than failing.
 pub enum DispatchEvent {
     /// Contains the initial state information
-    Ready(ReadyEvent),
+    Ready(Box<ReadyEvent>),
     /// Sent when we resume a session
     Resumed,
-    GuildCreate(GuildCreateEvent),
-    GuildUpdate(GuildUpdateEvent),
+    GuildCreate(Box<GuildCreateEvent>),
+    /// Sent when a guild is updated
+    GuildUpdate(Box<GuildUpdateEvent>),
+    /// Sent when a guild becomes unavailable, or the client leaves or is removed from it
     GuildDelete(GuildDeleteEvent),
+    /// Sent in response to requesting a guild's soundboard sounds
+    SoundboardSounds(SoundboardSoundsEvent),
+    /// Sent when a soundboard sound is created, requires [`GatewayIntents::GUILD_EXPRESSIONS`]
+    ///
+    /// [`GatewayIntents::GUILD_EXPRESSIONS`]: crate::model::gateway::intents::GatewayIntents::GUILD_EXPRESSIONS
+    GuildSoundboardSoundCreate(Box<SoundboardSound>),
+    /// Sent when a soundboard sound is updated, requires [`GatewayIntents::GUILD_EXPRESSIONS`]
+    ///
+    /// [`GatewayIntents::GUILD_EXPRESSIONS`]: crate::model::gateway::intents::GatewayIntents::GUILD_EXPRESSIONS
+    GuildSoundboardSoundUpdate(Box<SoundboardSound>),
+    /// Sent when a soundboard sound is deleted, requires [`GatewayIntents::GUILD_EXPRESSIONS`]
+    ///
+    /// [`GatewayIntents::GUILD_EXPRESSIONS`]: crate::model::gateway::intents::GatewayIntents::GUILD_EXPRESSIONS
+    GuildSoundboardSoundDelete {
+        /// The ID of the sound
+        sound_id: Snowflake,
+        /// The ID of the guild
+        guild_id: Snowflake,
+    },
+    /// Sent when several soundboard sounds of a guild are updated at once, requires
+    /// [`GatewayIntents::GUILD_EXPRESSIONS`]
+    ///
+    /// [`GatewayIntents::GUILD_EXPRESSIONS`]: crate::model::gateway::intents::GatewayIntents::GUILD_EXPRESSIONS
+    GuildSoundboardSoundsUpdate(SoundboardSoundsEvent),
+    /// Sent when a guild's emojis are updated, with the full list of the guild's emojis.
+    /// Requires [`GatewayIntents::GUILD_EXPRESSIONS`]
+    ///
+    /// [`GatewayIntents::GUILD_EXPRESSIONS`]: crate::model::gateway::intents::GatewayIntents::GUILD_EXPRESSIONS
+    GuildEmojisUpdate {
+        /// The ID of the guild
+        guild_id: Snowflake,
+        /// The guild's emojis
+        emojis: Vec<Emoji>,
+    },
+    /// Sent when a guild's stickers are updated, with the full list of the guild's stickers.
+    /// Requires [`GatewayIntents::GUILD_EXPRESSIONS`]
+    ///
+    /// [`GatewayIntents::GUILD_EXPRESSIONS`]: crate::model::gateway::intents::GatewayIntents::GUILD_EXPRESSIONS
+    GuildStickersUpdate {
+        /// The ID of the guild
+        guild_id: Snowflake,
+        /// The guild's stickers
+        stickers: Vec<Sticker>,
+    },
+    /// Sent when a channel is created
+    ChannelCreate(Box<Channel>),
+    /// Sent when a channel is updated
+    ChannelUpdate(Box<Channel>),
+    /// Sent when a channel is deleted
+    ChannelDelete(Box<Channel>),
+    /// Sent when a message is pinned or unpinned in a channel
+    ChannelPinsUpdate {
+        /// The ID of the guild, or `None` for DM channels
+        #[serde(default)]
+        guild_id: Option<Snowflake>,
+        /// The ID of the channel
+        channel_id: Snowflake,
+        /// When the most recent message was pinned, or `None` if no messages are pinned
+        #[serde(default)]
+        last_pin_timestamp: Option<Timestamp>,
+    },
+    /// Sent when a thread is created, or when the client is added to a private thread
+    ThreadCreate(Box<Channel>),
+    /// Sent when a thread is updated
+    ThreadUpdate(Box<Channel>),
+    /// Sent when a thread is deleted. Only the ID, guild ID, parent ID and type of the
+    /// thread are sent.
+    ThreadDelete(Box<Channel>),
+    /// Sent when the client gains access to a channel, with its active threads
+    ThreadListSync(Box<ThreadListSyncEvent>),
+    /// Sent when the client's thread member is updated
+    ThreadMemberUpdate(Box<ThreadMemberUpdateEvent>),
+    /// Sent when users are added to or removed from a thread. Without the
+    /// [`GatewayIntents::GUILD_MEMBERS`] privileged intent, this is only sent when the client
+    /// is added or removed.
+    ///
+    /// [`GatewayIntents::GUILD_MEMBERS`]: crate::model::gateway::intents::GatewayIntents::GUILD_MEMBERS
+    ThreadMembersUpdate(Box<ThreadMembersUpdateEvent>),
+    /// Sent when a guild role is created
+    GuildRoleCreate {
+        /// The ID of the guild
+        guild_id: Snowflake,
+        /// The role that was created
+        role: Box<Role>,
+    },
+    /// Sent when a guild role is updated
+    GuildRoleUpdate {
+        /// The ID of the guild
+        guild_id: Snowflake,
+        /// The role that was updated
+        role: Box<Role>,
+    },
+    /// Sent when a guild role is deleted
+    GuildRoleDelete {
+        /// The ID of the guild
+        guild_id: Snowflake,
+        /// The ID of the role that was deleted
+        role_id: Snowflake,
+    },
+    /// Sent when a user joins a guild, requires [`GatewayIntents::GUILD_MEMBERS`]
+    ///
+    /// [`GatewayIntents::GUILD_MEMBERS`]: crate::model::gateway::intents::GatewayIntents::GUILD_MEMBERS
+    GuildMemberAdd(Box<GuildMemberEvent>),
+    /// Sent when a guild member is updated, requires [`GatewayIntents::GUILD_MEMBERS`]
+    ///
+    /// [`GatewayIntents::GUILD_MEMBERS`]: crate::model::gateway::intents::GatewayIntents::GUILD_MEMBERS
+    GuildMemberUpdate(Box<GuildMemberEvent>),
+    /// Sent in response to requesting the members of a guild
+    GuildMembersChunk(Box<GuildMembersChunkEvent>),
+    /// Sent when a user leaves or is removed from a guild, requires
+    /// [`GatewayIntents::GUILD_MEMBERS`]
+    ///
+    /// [`GatewayIntents::GUILD_MEMBERS`]: crate::model::gateway::intents::GatewayIntents::GUILD_MEMBERS
+    GuildMemberRemove {
+        /// The ID of the guild
+        guild_id: Snowflake,
+        /// The user who was removed
+        user: Box<User>,
+    },
+    /// Sent when a user is banned from a guild, requires [`GatewayIntents::GUILD_MODERATION`]
+    ///
+    /// [`GatewayIntents::GUILD_MODERATION`]: crate::model::gateway::intents::GatewayIntents::GUILD_MODERATION
+    GuildBanAdd {
+        /// The ID of the guild
+        guild_id: Snowflake,
+        /// The user who was banned
+        user: Box<User>,
+    },
+    /// Sent when a user is unbanned from a guild, requires
+    /// [`GatewayIntents::GUILD_MODERATION`]
+    ///
+    /// [`GatewayIntents::GUILD_MODERATION`]: crate::model::gateway::intents::GatewayIntents::GUILD_MODERATION
+    GuildBanRemove {
+        /// The ID of the guild
+        guild_id: Snowflake,
+        /// The user who was unbanned
+        user: Box<User>,
+    },
+    /// Sent when an entry is added to a guild's audit log, requires
+    /// [`GatewayIntents::GUILD_MODERATION`] and the `VIEW_AUDIT_LOG` permission
+    ///
+    /// [`GatewayIntents::GUILD_MODERATION`]: crate::model::gateway::intents::GatewayIntents::GUILD_MODERATION
+    GuildAuditLogEntryCreate(Box<AuditLogEntry>),
+    /// Sent when the integrations of a guild are updated, requires
+    /// [`GatewayIntents::GUILD_INTEGRATIONS`]
+    ///
+    /// [`GatewayIntents::GUILD_INTEGRATIONS`]: crate::model::gateway::intents::GatewayIntents::GUILD_INTEGRATIONS
+    GuildIntegrationsUpdate {
+        /// The ID of the guild
+        guild_id: Snowflake,
+    },
+    /// Sent when an integration is created, requires [`GatewayIntents::GUILD_INTEGRATIONS`]
+    ///
+    /// [`GatewayIntents::GUILD_INTEGRATIONS`]: crate::model::gateway::intents::GatewayIntents::GUILD_INTEGRATIONS
+    IntegrationCreate(Box<IntegrationEvent>),
+    /// Sent when an integration is updated, requires [`GatewayIntents::GUILD_INTEGRATIONS`]
+    ///
+    /// [`GatewayIntents::GUILD_INTEGRATIONS`]: crate::model::gateway::intents::GatewayIntents::GUILD_INTEGRATIONS
+    IntegrationUpdate(Box<IntegrationEvent>),
+    /// Sent when an integration is deleted, requires [`GatewayIntents::GUILD_INTEGRATIONS`]
+    ///
+    /// [`GatewayIntents::GUILD_INTEGRATIONS`]: crate::model::gateway::intents::GatewayIntents::GUILD_INTEGRATIONS
+    IntegrationDelete {
+        /// The ID of the integration
+        id: Snowflake,
+        /// The ID of the guild
+        guild_id: Snowflake,
+        /// The ID of the bot or `OAuth2` application of the integration, if any
+        application_id: Option<Snowflake>,
+    },
+    /// Sent when a webhook of a channel is created, updated or deleted, requires
+    /// [`GatewayIntents::GUILD_WEBHOOKS`]
+    ///
+    /// [`GatewayIntents::GUILD_WEBHOOKS`]: crate::model::gateway::intents::GatewayIntents::GUILD_WEBHOOKS
+    WebhooksUpdate {
+        /// The ID of the guild
+        guild_id: Snowflake,
+        /// The ID of the channel
+        channel_id: Snowflake,
+    },
+    /// Sent when an invite is created, requires [`GatewayIntents::GUILD_INVITES`]
+    ///
+    /// [`GatewayIntents::GUILD_INVITES`]: crate::model::gateway::intents::GatewayIntents::GUILD_INVITES
+    InviteCreate(Box<InviteCreateEvent>),
+    /// Sent when an invite is deleted, requires [`GatewayIntents::GUILD_INVITES`]
+    ///
+    /// [`GatewayIntents::GUILD_INVITES`]: crate::model::gateway::intents::GatewayIntents::GUILD_INVITES
+    InviteDelete {
+        /// The ID of the channel the invite was for
+        channel_id: Snowflake,
+        /// The ID of the guild of the invite, if any
+        guild_id: Option<Snowflake>,
+        /// The unique invite code
+        code: String,
+    },
+    /// Sent when an event is scheduled in a guild, requires
+    /// [`GatewayIntents::GUILD_SCHEDULED_EVENTS`]
+    ///
+    /// [`GatewayIntents::GUILD_SCHEDULED_EVENTS`]: crate::model::gateway::intents::GatewayIntents::GUILD_SCHEDULED_EVENTS
+    GuildScheduledEventCreate(Box<ScheduledEvent>),
+    /// Sent when a scheduled event is updated, including when it starts or ends, requires
+    /// [`GatewayIntents::GUILD_SCHEDULED_EVENTS`]
+    ///
+    /// [`GatewayIntents::GUILD_SCHEDULED_EVENTS`]: crate::model::gateway::intents::GatewayIntents::GUILD_SCHEDULED_EVENTS
+    GuildScheduledEventUpdate(Box<ScheduledEvent>),
+    /// Sent when a scheduled event is deleted, requires
+    /// [`GatewayIntents::GUILD_SCHEDULED_EVENTS`]
+    ///
+    /// [`GatewayIntents::GUILD_SCHEDULED_EVENTS`]: crate::model::gateway::intents::GatewayIntents::GUILD_SCHEDULED_EVENTS
+    GuildScheduledEventDelete(Box<ScheduledEvent>),
+    /// Sent when a user subscribes to a scheduled event, requires
+    /// [`GatewayIntents::GUILD_SCHEDULED_EVENTS`]
+    ///
+    /// [`GatewayIntents::GUILD_SCHEDULED_EVENTS`]: crate::model::gateway::intents::GatewayIntents::GUILD_SCHEDULED_EVENTS
+    GuildScheduledEventUserAdd {
+        /// The ID of the scheduled event
+        guild_scheduled_event_id: Snowflake,
+        /// The ID of the user
+        user_id: Snowflake,
+        /// The ID of the guild
+        guild_id: Snowflake,
+    },
+    /// Sent when a user unsubscribes from a scheduled event, requires
+    /// [`GatewayIntents::GUILD_SCHEDULED_EVENTS`]
+    ///
+    /// [`GatewayIntents::GUILD_SCHEDULED_EVENTS`]: crate::model::gateway::intents::GatewayIntents::GUILD_SCHEDULED_EVENTS
+    GuildScheduledEventUserRemove {
+        /// The ID of the scheduled event
+        guild_scheduled_event_id: Snowflake,
+        /// The ID of the user
+        user_id: Snowflake,
+        /// The ID of the guild
+        guild_id: Snowflake,
+    },
+    /// Sent when an auto moderation rule is created, requires
+    /// [`GatewayIntents::AUTO_MODERATION_CONFIGURATION`]
+    ///
+    /// [`GatewayIntents::AUTO_MODERATION_CONFIGURATION`]: crate::model::gateway::intents::GatewayIntents::AUTO_MODERATION_CONFIGURATION
+    AutoModerationRuleCreate(Box<AutoModerationRule>),
+    /// Sent when an auto moderation rule is updated, requires
+    /// [`GatewayIntents::AUTO_MODERATION_CONFIGURATION`]
+    ///
+    /// [`GatewayIntents::AUTO_MODERATION_CONFIGURATION`]: crate::model::gateway::intents::GatewayIntents::AUTO_MODERATION_CONFIGURATION
+    AutoModerationRuleUpdate(Box<AutoModerationRule>),
+    /// Sent when an auto moderation rule is deleted, requires
+    /// [`GatewayIntents::AUTO_MODERATION_CONFIGURATION`]
+    ///
+    /// [`GatewayIntents::AUTO_MODERATION_CONFIGURATION`]: crate::model::gateway::intents::GatewayIntents::AUTO_MODERATION_CONFIGURATION
+    AutoModerationRuleDelete(Box<AutoModerationRule>),
+    /// Sent when an auto moderation rule is triggered and an action is taken, requires
+    /// [`GatewayIntents::AUTO_MODERATION_EXECUTION`]
+    ///
+    /// [`GatewayIntents::AUTO_MODERATION_EXECUTION`]: crate::model::gateway::intents::GatewayIntents::AUTO_MODERATION_EXECUTION
+    AutoModerationActionExecution(Box<AutoModerationActionExecutionEvent>),
+    /// Sent when a user uses a command, component or modal of the application
+    InteractionCreate(Box<Interaction>),
+    /// Sent when a message is created
+    MessageCreate(Box<Message>),
+    /// Sent when a message is edited
+    MessageUpdate(Box<PartialMessage>),
+    /// Sent when a message is deleted
+    MessageDelete {
+        /// The ID of the message
+        id: Snowflake,
+        /// The ID of the channel
+        channel_id: Snowflake,
+        /// The ID of the guild, if the channel is in one
+        guild_id: Option<Snowflake>,
+    },
+    /// Sent when multiple messages are deleted at once
+    MessageDeleteBulk {
+        /// The IDs of the messages
+        ids: Vec<Snowflake>,
+        /// The ID of the channel
+        channel_id: Snowflake,
+        /// The ID of the guild, if the channel is in one
+        guild_id: Option<Snowflake>,
+    },
+    /// Sent when a user reacts to a message
+    MessageReactionAdd(Box<MessageReactionAddEvent>),
+    /// Sent when a user removes a reaction from a message
+    MessageReactionRemove(Box<MessageReactionRemoveEvent>),
+    /// Sent when all reactions are removed from a message
+    MessageReactionRemoveAll {
+        /// The ID of the channel
+        channel_id: Snowflake,
+        /// The ID of the message
+        message_id: Snowflake,
+        /// The ID of the guild, if the channel is in one
+        guild_id: Option<Snowflake>,
+    },
+    /// Sent when all reactions with an emoji are removed from a message
+    MessageReactionRemoveEmoji(Box<MessageReactionRemoveEmojiEvent>),
+    /// Sent when a user starts typing in a channel, requires
+    /// [`GatewayIntents::GUILD_MESSAGE_TYPING`] or [`GatewayIntents::DIRECT_MESSAGE_TYPING`]
+    ///
+    /// [`GatewayIntents::GUILD_MESSAGE_TYPING`]: crate::model::gateway::intents::GatewayIntents::GUILD_MESSAGE_TYPING
+    /// [`GatewayIntents::DIRECT_MESSAGE_TYPING`]: crate::model::gateway::intents::GatewayIntents::DIRECT_MESSAGE_TYPING
+    TypingStart(Box<TypingStartEvent>),
+    /// Sent when a user's presence is updated, requires [`GatewayIntents::GUILD_PRESENCES`]
+    ///
+    /// [`GatewayIntents::GUILD_PRESENCES`]: crate::model::gateway::intents::GatewayIntents::GUILD_PRESENCES
+    PresenceUpdate(Box<Presence>),
+    /// Sent when a user joins, leaves or moves between voice channels, or their voice
+    /// state is updated, such as by muting. Requires [`GatewayIntents::GUILD_VOICE_STATES`]
+    ///
+    /// [`GatewayIntents::GUILD_VOICE_STATES`]: crate::model::gateway::intents::GatewayIntents::GUILD_VOICE_STATES
+    VoiceStateUpdate(Box<VoiceState>),
+    /// Sent when the voice server of a guild is set or changes, with what is needed to
+    /// connect to it after the client's voice state was updated
+    VoiceServerUpdate {
+        /// The voice connection token
+        token: String,
+        /// The ID of the guild
+        guild_id: Snowflake,
+        /// The voice server host, or `None` while the voice server is being reallocated,
+        /// in which case the client should wait for another `VOICE_SERVER_UPDATE`
+        endpoint: Option<String>,
+    },
+    /// An event that is not modelled by this library, such as one added to the gateway
+    /// after this version was released
+    #[serde(skip)]
+    Unknown {
+        /// The name of the event, such as `GUILD_MEMBER_ADD`
+        event_type: String,
+        /// The undecoded data of the event
+        data: Value,
+    },
+}
+
+#[derive(Deserialize)]
+/// The name and data of a dispatch, read before the data is decoded
+struct RawDispatch {
+    t: String,
+    #[serde(default)]
+    d: Value,
+}
+
+#[derive(Debug)]
+/// The error found when decoding only the name of a dispatch, which records whether the
+/// name is one of the variants of [`DispatchEvent`]
+struct EventTypeProbe {
+    unknown: bool,
+}
+
+impl fmt::Display for EventTypeProbe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.unknown {
+            write!(f, "unknown dispatch event type")
+        } else {
+            write!(f, "known dispatch event type")
+        }
+    }
+}
+
+impl std::error::Error for EventTypeProbe {}
+
+impl DeError for EventTypeProbe {
+    fn custom<T: fmt::Display>(_: T) -> Self {
+        Self { unknown: false }
+    }
+
+    fn unknown_variant(_: &str, _: &'static [&'static str]) -> Self {
+        Self { unknown: true }
+    }
+}
+
+impl<'de> Deserialize<'de> for DispatchEvent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let RawDispatch { t, d } = RawDispatch::deserialize(deserializer)?;
+
+        // Decoding the name without any data fails either way, unless the event has no
+        // data, but only fails with an unknown variant if the name isn't modelled
+        let name = MapDeserializer::new(std::iter::once(("t", t.as_str())));
+        if let Err(EventTypeProbe { unknown: true }) = DispatchEvent::deserialize(name) {
+            return Ok(DispatchEvent::Unknown {
+                event_type: t,
+                data: d,
+            });
+        }
+
+        let event = Map::from_iter([("t".to_string(), Value::String(t)), ("d".to_string(), d)]);
+        DispatchEvent::deserialize(Value::Object(event)).map_err(D::Error::custom)
+    }
+}
+
+impl DispatchEvent {
+    /// Whether this event is part of establishing or resuming a session rather than
+    /// an ongoing update. This is `READY`, `RESUMED` and `GUILD_CREATE`, which is
+    /// sent for every guild in the initial burst after `READY`.
+    ///
+    /// *Note: `GUILD_CREATE` is also sent when the client joins a new guild.*
+    #[must_use]
+    pub fn is_lifecycle(&self) -> bool {
+        matches!(
+            self,
+            DispatchEvent::Ready(_) | DispatchEvent::Resumed | DispatchEvent::GuildCreate(_)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{
+        DispatchEvent, GuildDeleteEvent, GuildMembersChunkCollector, GuildMembersChunkEvent,
+        TypingStartEvent,
+    };
+    use crate::gateway::mock::user_payload;
+    use crate::model::{
+        guild::{
+            audit_log::AuditLogEvent,
+            auto_moderation::{
+                AutoModerationActionType, AutoModerationEventType, AutoModerationTriggerType,
+            },
+            scheduled_event::{ScheduledEventEntityType, ScheduledEventStatus},
+        },
+        snowflake::Snowflake,
+    };
+
+    #[test]
+    fn ready_and_resumed_are_lifecycle() {
+        let ready: DispatchEvent = serde_json::from_value(json!({
+            "t": "READY",
+            "d": {
+                "v": 10,
+                "user": user_payload(),
+                "guilds": [],
+                "session_id": "session",
+                "resume_gateway_url": "wss://gateway.discord.gg",
+                "shard": [0, 1],
+            },
+        }))
+        .unwrap();
+
+        assert!(ready.is_lifecycle());
+        assert!(DispatchEvent::Resumed.is_lifecycle());
+    }
+
+    #[test]
+    fn unmodelled_events_are_unknown() {
+        let event: DispatchEvent = serde_json::from_value(json!({
+            "t": "STAGE_INSTANCE_CREATE",
+            "d": { "id": "840647391636226060", "topic": "Testing Testing, 123" },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            event,
+            DispatchEvent::Unknown {
+                event_type: "STAGE_INSTANCE_CREATE".to_string(),
+                data: json!({ "id": "840647391636226060", "topic": "Testing Testing, 123" }),
+            }
+        );
+        assert!(!event.is_lifecycle());
+    }
+
+    #[test]
+    fn modelled_events_with_invalid_data_still_fail() {
+        let result = serde_json::from_value::<DispatchEvent>(json!({
+            "t": "GUILD_ROLE_DELETE",
+            "d": { "guild_id": "41771983423143937" },
+        }));
+        assert!(result.unwrap_err().to_string().contains("role_id"));
+
+        let resumed: DispatchEvent =
+            serde_json::from_value(json!({ "t": "RESUMED", "d": null })).unwrap();
+        assert_eq!(resumed, DispatchEvent::Resumed);
+    }
+
+    #[test]
+    fn dispatch_event_stays_small() {
+        assert!(std::mem::size_of::<DispatchEvent>() <= 64);
+    }
+
+    fn guild_delete(data: &serde_json::Value) -> GuildDeleteEvent {
+        let event = serde_json::from_value(json!({ "t": "GUILD_DELETE", "d": data })).unwrap();
+        let DispatchEvent::GuildDelete(guild_delete) = event else {
+            panic!("expected a GUILD_DELETE event");
+        };
+        guild_delete
+    }
+
+    #[test]
+    fn guild_delete_after_removal() {
+        let guild_delete = guild_delete(&json!({ "id": "41771983423143937" }));
+        assert_eq!(guild_delete.0.id, Snowflake(41_771_983_423_143_937));
+        assert!(!guild_delete.is_outage());
+        assert!(!DispatchEvent::GuildDelete(guild_delete).is_lifecycle());
+    }
+
+    #[test]
+    fn guild_delete_during_outage() {
+        let guild_delete = guild_delete(&json!({ "id": "41771983423143937", "unavailable": true }));
+        assert!(guild_delete.is_outage());
+    }
+
+    #[test]
+    fn guild_update_is_not_lifecycle() {
+        let guild_update: DispatchEvent = serde_json::from_value(json!({
+            "t": "GUILD_UPDATE",
+            "d": {
+                "id": "41771983423143937",
+                "name": "Renamed",
+                "owner_id": "80351110224678912",
+                "premium_tier": 1,
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::GuildUpdate(ref update) = guild_update else {
+            panic!("expected a GUILD_UPDATE event");
+        };
+        assert_eq!(update.0.name, "Renamed");
+        assert!(!guild_update.is_lifecycle());
+    }
+
+    #[test]
+    fn deserialize_guild_emojis_update() {
+        let emojis_update: DispatchEvent = serde_json::from_value(json!({
+            "t": "GUILD_EMOJIS_UPDATE",
+            "d": {
+                "guild_id": "41771983423143937",
+                "emojis": [
+                    { "id": "41771983429993937", "name": "LUL", "animated": false },
+                    { "id": "41771983429993938", "name": "blobdance", "animated": true },
+                ],
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::GuildEmojisUpdate { guild_id, emojis } = emojis_update else {
+            panic!("expected a GUILD_EMOJIS_UPDATE event");
+        };
+        assert_eq!(guild_id, Snowflake(41_771_983_423_143_937));
+        assert_eq!(
+            emojis
+                .iter()
+                .map(|emoji| (emoji.id, emoji.animated))
+                .collect::<Vec<_>>(),
+            [
+                (Some(Snowflake(41_771_983_429_993_937)), Some(false)),
+                (Some(Snowflake(41_771_983_429_993_938)), Some(true)),
+            ]
+        );
+    }
+
+    #[test]
+    fn deserialize_thread_create() {
+        let thread_create: DispatchEvent = serde_json::from_value(json!({
+            "t": "THREAD_CREATE",
+            "d": {
+                "id": "41771983423143937",
+                "guild_id": "41771983423143936",
+                "parent_id": "41771983423143935",
+                "owner_id": "80351110224678912",
+                "name": "don't buy dota-2",
+                "type": 11,
+                "last_message_id": "41771983423143937",
+                "message_count": 1,
+                "member_count": 5,
+                "rate_limit_per_user": 2,
+                "thread_metadata": {
+                    "archived": false,
+                    "auto_archive_duration": 1440,
+                    "archive_timestamp": "2021-04-12T23:40:39.855793+00:00",
+                    "locked": false,
+                },
+                "member": {
+                    "join_timestamp": "2021-04-12T23:40:39.855793+00:00",
+                    "flags": 0,
+                },
+                "total_message_sent": 1,
+                "newly_created": true,
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::ThreadCreate(thread) = thread_create else {
+            panic!("expected a THREAD_CREATE event");
+        };
+        assert!(thread.is_thread());
+        assert_eq!(thread.owner_id, Some(Snowflake(80_351_110_224_678_912)));
+        assert_eq!(thread.message_count, Some(1));
+        assert_eq!(thread.member_count, Some(5));
+        let metadata = thread.thread_metadata.unwrap();
+        assert!(!metadata.archived);
+        assert_eq!(metadata.auto_archive_duration, 1440);
+        assert_eq!(thread.member.unwrap().user_id, None);
+    }
+
+    #[test]
+    fn deserialize_guild_role_delete() {
+        let role_delete: DispatchEvent = serde_json::from_value(json!({
+            "t": "GUILD_ROLE_DELETE",
+            "d": { "guild_id": "41771983423143937", "role_id": "41771983423143936" },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            role_delete,
+            DispatchEvent::GuildRoleDelete {
+                guild_id: Snowflake(41_771_983_423_143_937),
+                role_id: Snowflake(41_771_983_423_143_936),
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_guild_member_add() {
+        let member_add: DispatchEvent = serde_json::from_value(json!({
+            "t": "GUILD_MEMBER_ADD",
+            "d": {
+                "guild_id": "41771983423143937",
+                "user": user_payload(),
+                "nick": null,
+                "roles": [],
+                "joined_at": "2015-04-26T06:26:56.936000+00:00",
+                "deaf": false,
+                "mute": false,
+                "flags": 0,
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::GuildMemberAdd(member_add) = member_add else {
+            panic!("expected a GUILD_MEMBER_ADD event");
+        };
+        assert_eq!(member_add.guild_id, Snowflake(41_771_983_423_143_937));
+        assert_eq!(member_add.member.user.unwrap().username, "Nelly");
+        assert_eq!(member_add.member.nick, None);
+    }
+
+    #[test]
+    fn deserialize_guild_member_remove() {
+        let member_remove: DispatchEvent = serde_json::from_value(json!({
+            "t": "GUILD_MEMBER_REMOVE",
+            "d": {
+                "guild_id": "41771983423143937",
+                "user": user_payload(),
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::GuildMemberRemove { guild_id, user } = member_remove else {
+            panic!("expected a GUILD_MEMBER_REMOVE event");
+        };
+        assert_eq!(guild_id, Snowflake(41_771_983_423_143_937));
+        assert_eq!(user.id, Snowflake(80_351_110_224_678_912));
+    }
+
+    #[test]
+    fn deserialize_guild_ban_add() {
+        let ban_add: DispatchEvent = serde_json::from_value(json!({
+            "t": "GUILD_BAN_ADD",
+            "d": {
+                "guild_id": "41771983423143937",
+                "user": user_payload(),
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::GuildBanAdd { guild_id, user } = ban_add else {
+            panic!("expected a GUILD_BAN_ADD event");
+        };
+        assert_eq!(guild_id, Snowflake(41_771_983_423_143_937));
+        assert_eq!(user.id, Snowflake(80_351_110_224_678_912));
+    }
+
+    #[test]
+    fn deserialize_guild_audit_log_entry_create() {
+        let entry_create: DispatchEvent = serde_json::from_value(json!({
+            "t": "GUILD_AUDIT_LOG_ENTRY_CREATE",
+            "d": {
+                "id": "1101603981519609856",
+                "action_type": 22,
+                "user_id": "80351110224678912",
+                "target_id": "53908099506183680",
+                "reason": "Spamming",
+                "changes": [],
+                "guild_id": "41771983423143937",
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::GuildAuditLogEntryCreate(entry) = entry_create else {
+            panic!("expected a GUILD_AUDIT_LOG_ENTRY_CREATE event");
+        };
+        assert_eq!(entry.action_type, AuditLogEvent::MemberBanAdd);
+        assert_eq!(entry.user_id, Some(Snowflake(80_351_110_224_678_912)));
+        assert_eq!(entry.target_id, Some(Snowflake(53_908_099_506_183_680)));
+        assert_eq!(entry.reason.as_deref(), Some("Spamming"));
+        assert_eq!(entry.guild_id, Snowflake(41_771_983_423_143_937));
+    }
+
+    #[test]
+    fn message_create_is_not_lifecycle() {
+        let message_create: DispatchEvent = serde_json::from_value(json!({
+            "t": "MESSAGE_CREATE",
+            "d": {
+                "id": "334385199974967042",
+                "channel_id": "290926798999357250",
+                "author": {
+                    "id": "53908099506183680",
+                    "username": "Mason",
+                    "discriminator": "0",
+                    "global_name": "Mason",
+                    "avatar": null,
+                },
+                "content": "Supa Hot",
+                "timestamp": "2017-07-11T17:27:07.299000+00:00",
+                "edited_timestamp": null,
+                "tts": false,
+                "mention_everyone": false,
+                "mentions": [],
+                "attachments": [],
+                "embeds": [],
+                "pinned": false,
+            },
+        }))
+        .unwrap();
+
+        assert!(matches!(message_create, DispatchEvent::MessageCreate(_)));
+        assert!(!message_create.is_lifecycle());
+    }
+
+    #[test]
+    fn deserialize_message_update() {
+        let message_update: DispatchEvent = serde_json::from_value(json!({
+            "t": "MESSAGE_UPDATE",
+            "d": {
+                "id": "334385199974967042",
+                "channel_id": "290926798999357250",
+                "guild_id": "41771983423143937",
+                "content": "Supa Hot (edited)",
+                "edited_timestamp": "2017-07-11T17:28:07.299000+00:00",
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::MessageUpdate(message) = message_update else {
+            panic!("expected a MESSAGE_UPDATE event");
+        };
+        assert_eq!(message.id, Snowflake(334_385_199_974_967_042));
+        assert_eq!(message.content.as_deref(), Some("Supa Hot (edited)"));
+        assert_eq!(message.author, None);
+    }
+
+    #[test]
+    fn deserialize_message_delete() {
+        let message_delete: DispatchEvent = serde_json::from_value(json!({
+            "t": "MESSAGE_DELETE",
+            "d": { "id": "334385199974967042", "channel_id": "290926798999357250" },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            message_delete,
+            DispatchEvent::MessageDelete {
+                id: Snowflake(334_385_199_974_967_042),
+                channel_id: Snowflake(290_926_798_999_357_250),
+                guild_id: None,
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_message_delete_bulk() {
+        let message_delete_bulk: DispatchEvent = serde_json::from_value(json!({
+            "t": "MESSAGE_DELETE_BULK",
+            "d": {
+                "ids": ["334385199974967042", "334385199974967043"],
+                "channel_id": "290926798999357250",
+                "guild_id": "41771983423143937",
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            message_delete_bulk,
+            DispatchEvent::MessageDeleteBulk {
+                ids: vec![
+                    Snowflake(334_385_199_974_967_042),
+                    Snowflake(334_385_199_974_967_043)
+                ],
+                channel_id: Snowflake(290_926_798_999_357_250),
+                guild_id: Some(Snowflake(41_771_983_423_143_937)),
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_unicode_reaction_add() {
+        let reaction_add: DispatchEvent = serde_json::from_value(json!({
+            "t": "MESSAGE_REACTION_ADD",
+            "d": {
+                "user_id": "80351110224678912",
+                "channel_id": "290926798999357250",
+                "message_id": "334385199974967042",
+                "emoji": { "id": null, "name": "🔥" },
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::MessageReactionAdd(reaction_add) = reaction_add else {
+            panic!("expected a MESSAGE_REACTION_ADD event");
+        };
+        assert!(reaction_add.emoji.is_unicode());
+        assert_eq!(reaction_add.emoji.name.as_deref(), Some("🔥"));
+        assert_eq!(reaction_add.guild_id, None);
+        assert_eq!(reaction_add.member, None);
+    }
+
+    #[test]
+    fn deserialize_custom_reaction_remove() {
+        let reaction_remove: DispatchEvent = serde_json::from_value(json!({
+            "t": "MESSAGE_REACTION_REMOVE",
+            "d": {
+                "user_id": "80351110224678912",
+                "channel_id": "290926798999357250",
+                "message_id": "334385199974967042",
+                "guild_id": "41771983423143937",
+                "emoji": { "id": "41771983429993937", "name": "LUL", "animated": true },
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::MessageReactionRemove(reaction_remove) = reaction_remove else {
+            panic!("expected a MESSAGE_REACTION_REMOVE event");
+        };
+        assert!(!reaction_remove.emoji.is_unicode());
+        assert_eq!(
+            reaction_remove.emoji.id,
+            Some(Snowflake(41_771_983_429_993_937))
+        );
+        assert_eq!(reaction_remove.emoji.animated, Some(true));
+    }
+
+    fn typing_start(data: &serde_json::Value) -> TypingStartEvent {
+        let event = serde_json::from_value(json!({ "t": "TYPING_START", "d": data })).unwrap();
+        let DispatchEvent::TypingStart(typing_start) = event else {
+            panic!("expected a TYPING_START event");
+        };
+        *typing_start
+    }
+
+    #[test]
+    fn deserialize_guild_typing_start() {
+        let typing_start = typing_start(&json!({
+            "channel_id": "290926798999357250",
+            "guild_id": "41771983423143937",
+            "user_id": "80351110224678912",
+            "timestamp": 1_499_794_027,
+            "member": {
+                "user": user_payload(),
+                "roles": [],
+                "joined_at": "2015-04-26T06:26:56.936000+00:00",
+                "deaf": false,
+                "mute": false,
+            },
+        }));
+
+        assert_eq!(
+            typing_start.guild_id,
+            Some(Snowflake(41_771_983_423_143_937))
+        );
+        assert_eq!(typing_start.timestamp, 1_499_794_027);
+        assert!(typing_start.member.is_some());
+    }
+
+    #[test]
+    fn deserialize_direct_message_typing_start() {
+        let typing_start = typing_start(&json!({
+            "channel_id": "290926798999357250",
+            "user_id": "80351110224678912",
+            "timestamp": 1_499_794_027,
+        }));
+
+        assert_eq!(typing_start.user_id, Snowflake(80_351_110_224_678_912));
+        assert_eq!(typing_start.guild_id, None);
+        assert_eq!(typing_start.member, None);
+    }
+
+    fn voice_state_update(channel_id: Option<&str>) -> DispatchEvent {
+        serde_json::from_value(json!({
+            "t": "VOICE_STATE_UPDATE",
+            "d": {
+                "guild_id": "41771983423143937",
+                "channel_id": channel_id,
+                "user_id": "80351110224678912",
+                "member": {
+                    "user": user_payload(),
+                    "roles": [],
+                    "joined_at": "2015-04-26T06:26:56.936000+00:00",
+                    "deaf": false,
+                    "mute": false,
+                },
+                "session_id": "90326bd25d71d39b9ef95b299e3872ff",
+                "deaf": false,
+                "mute": false,
+                "self_deaf": false,
+                "self_mute": true,
+                "self_video": false,
+                "suppress": false,
+                "request_to_speak_timestamp": null,
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn deserialize_voice_state_update_join() {
+        let DispatchEvent::VoiceStateUpdate(voice_state) =
+            voice_state_update(Some("127121515262115840"))
+        else {
+            panic!("expected a VOICE_STATE_UPDATE event");
+        };
+        assert!(voice_state.is_connected());
+        assert_eq!(
+            voice_state.channel_id,
+            Some(Snowflake(127_121_515_262_115_840))
+        );
+        assert_eq!(voice_state.user_id, Snowflake(80_351_110_224_678_912));
+        assert!(voice_state.self_mute);
+        assert_eq!(voice_state.self_stream, None);
+        assert!(voice_state.member.is_some());
+    }
+
+    #[test]
+    fn deserialize_voice_state_update_leave() {
+        let DispatchEvent::VoiceStateUpdate(voice_state) = voice_state_update(None) else {
+            panic!("expected a VOICE_STATE_UPDATE event");
+        };
+        assert!(!voice_state.is_connected());
+        assert_eq!(voice_state.channel_id, None);
+    }
+
+    #[test]
+    fn deserialize_voice_server_update() {
+        let voice_server_update: DispatchEvent = serde_json::from_value(json!({
+            "t": "VOICE_SERVER_UPDATE",
+            "d": {
+                "token": "my_token",
+                "guild_id": "41771983423143937",
+                "endpoint": "sweetwater-12345.discord.media:2048",
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            voice_server_update,
+            DispatchEvent::VoiceServerUpdate {
+                token: "my_token".to_string(),
+                guild_id: Snowflake(41_771_983_423_143_937),
+                endpoint: Some("sweetwater-12345.discord.media:2048".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_voice_server_update_while_reallocating() {
+        let voice_server_update: DispatchEvent = serde_json::from_value(json!({
+            "t": "VOICE_SERVER_UPDATE",
+            "d": { "token": "my_token", "guild_id": "41771983423143937", "endpoint": null },
+        }))
+        .unwrap();
+
+        let DispatchEvent::VoiceServerUpdate { endpoint, .. } = voice_server_update else {
+            panic!("expected a VOICE_SERVER_UPDATE event");
+        };
+        assert_eq!(endpoint, None);
+    }
+
+    fn guild_members_chunk(index: u32, count: u32, user_id: &str) -> GuildMembersChunkEvent {
+        let mut user = user_payload();
+        user["id"] = json!(user_id);
+        let event = serde_json::from_value(json!({
+            "t": "GUILD_MEMBERS_CHUNK",
+            "d": {
+                "guild_id": "41771983423143937",
+                "members": [{
+                    "user": user,
+                    "roles": [],
+                    "joined_at": "2015-04-26T06:26:56.936000+00:00",
+                    "deaf": false,
+                    "mute": false,
+                }],
+                "chunk_index": index,
+                "chunk_count": count,
+                "not_found": ["41771983423143936"],
+                "nonce": "members",
+            },
+        }))
+        .unwrap();
+        let DispatchEvent::GuildMembersChunk(chunk) = event else {
+            panic!("expected a GUILD_MEMBERS_CHUNK event");
+        };
+        *chunk
+    }
+
+    #[test]
+    fn deserialize_guild_members_chunk() {
+        let chunk = guild_members_chunk(0, 1, "80351110224678912");
+        assert_eq!(chunk.guild_id, Snowflake(41_771_983_423_143_937));
+        assert_eq!(chunk.members.len(), 1);
+        assert_eq!(
+            chunk.not_found,
+            Some(vec![Snowflake(41_771_983_423_143_936)])
+        );
+        assert_eq!(chunk.presences, None);
+        assert_eq!(chunk.nonce.as_deref(), Some("members"));
+
+        let mut collector = GuildMembersChunkCollector::default();
+        assert_eq!(collector.push(chunk).map(|members| members.len()), Some(1));
+        assert_eq!(collector.pending(), 0);
+    }
+
+    #[test]
+    fn collector_waits_for_every_chunk() {
+        let mut collector = GuildMembersChunkCollector::default();
+        assert!(collector
+            .push(guild_members_chunk(0, 2, "80351110224678912"))
+            .is_none());
+        assert_eq!(collector.pending(), 1);
+
+        let members = collector
+            .push(guild_members_chunk(1, 2, "80351110224678913"))
+            .unwrap();
+        assert_eq!(members.len(), 2);
+        assert_eq!(collector.pending(), 0);
+    }
+
+    #[test]
+    fn deserialize_presence_update() {
+        let presence_update: DispatchEvent = serde_json::from_value(json!({
+            "t": "PRESENCE_UPDATE",
+            "d": {
+                "user": { "id": "80351110224678912" },
+                "guild_id": "41771983423143937",
+                "status": "online",
+                "activities": [],
+                "client_status": { "web": "online" },
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::PresenceUpdate(presence) = presence_update else {
+            panic!("expected a PRESENCE_UPDATE event");
+        };
+        assert_eq!(presence.user.id, Snowflake(80_351_110_224_678_912));
+        assert_eq!(presence.guild_id, Some(Snowflake(41_771_983_423_143_937)));
+    }
+
+    #[test]
+    fn deserialize_integration_create() {
+        let integration_create: DispatchEvent = serde_json::from_value(json!({
+            "t": "INTEGRATION_CREATE",
+            "d": {
+                "guild_id": "41771983423143937",
+                "id": "33590653072239123",
+                "name": "A Name",
+                "type": "twitch",
+                "enabled": true,
+                "account": { "id": "1234567", "name": "A Name" },
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::IntegrationCreate(integration_create) = integration_create else {
+            panic!("expected an INTEGRATION_CREATE event");
+        };
+        assert_eq!(integration_create.integration.kind, "twitch");
+        assert_eq!(integration_create.integration.account.id, "1234567");
+    }
+
+    #[test]
+    fn deserialize_integration_delete() {
+        let integration_delete: DispatchEvent = serde_json::from_value(json!({
+            "t": "INTEGRATION_DELETE",
+            "d": { "id": "33590653072239123", "guild_id": "41771983423143937" },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            integration_delete,
+            DispatchEvent::IntegrationDelete {
+                id: Snowflake(33_590_653_072_239_123),
+                guild_id: Snowflake(41_771_983_423_143_937),
+                application_id: None,
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_webhooks_update() {
+        let webhooks_update: DispatchEvent = serde_json::from_value(json!({
+            "t": "WEBHOOKS_UPDATE",
+            "d": { "guild_id": "41771983423143937", "channel_id": "127121515262115840" },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            webhooks_update,
+            DispatchEvent::WebhooksUpdate {
+                guild_id: Snowflake(41_771_983_423_143_937),
+                channel_id: Snowflake(127_121_515_262_115_840),
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_invite_create() {
+        let invite_create: DispatchEvent = serde_json::from_value(json!({
+            "t": "INVITE_CREATE",
+            "d": {
+                "channel_id": "127121515262115840",
+                "code": "0vCdhLbwjZZTWZLD",
+                "created_at": "2016-03-31T19:15:39.954000+00:00",
+                "guild_id": "41771983423143937",
+                "inviter": user_payload(),
+                "max_age": 86400,
+                "max_uses": 0,
+                "temporary": false,
+                "uses": 0,
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::InviteCreate(invite) = invite_create else {
+            panic!("expected an INVITE_CREATE event");
+        };
+        assert_eq!(invite.code, "0vCdhLbwjZZTWZLD");
+        assert_eq!(invite.created_at.unix(), 1_459_451_739);
+        assert_eq!(invite.inviter.unwrap().username, "Nelly");
+        assert_eq!(invite.max_age, 86400);
+    }
+
+    #[test]
+    fn deserialize_invite_delete() {
+        let invite_delete: DispatchEvent = serde_json::from_value(json!({
+            "t": "INVITE_DELETE",
+            "d": { "channel_id": "127121515262115840", "code": "0vCdhLbwjZZTWZLD" },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            invite_delete,
+            DispatchEvent::InviteDelete {
+                channel_id: Snowflake(127_121_515_262_115_840),
+                guild_id: None,
+                code: "0vCdhLbwjZZTWZLD".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_guild_scheduled_event_create() {
+        let event_create: DispatchEvent = serde_json::from_value(json!({
+            "t": "GUILD_SCHEDULED_EVENT_CREATE",
+            "d": {
+                "id": "912702226335272960",
+                "guild_id": "41771983423143937",
+                "channel_id": null,
+                "creator_id": "80351110224678912",
+                "name": "Meetup",
+                "description": null,
+                "scheduled_start_time": "2021-11-25T18:00:00+00:00",
+                "scheduled_end_time": "2021-11-25T20:00:00+00:00",
+                "privacy_level": 2,
+                "status": 1,
+                "entity_type": 3,
+                "entity_id": null,
+                "entity_metadata": { "location": "The park" },
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::GuildScheduledEventCreate(event) = event_create else {
+            panic!("expected a GUILD_SCHEDULED_EVENT_CREATE event");
+        };
+        assert_eq!(event.name, "Meetup");
+        assert_eq!(event.status, ScheduledEventStatus::Scheduled);
+        assert_eq!(event.entity_type, ScheduledEventEntityType::External);
+        assert_eq!(event.scheduled_start_time.unix(), 1_637_863_200);
+        assert!(event.scheduled_end_time.is_some());
+    }
+
+    #[test]
+    fn deserialize_guild_scheduled_event_user_add() {
+        let user_add: DispatchEvent = serde_json::from_value(json!({
+            "t": "GUILD_SCHEDULED_EVENT_USER_ADD",
+            "d": {
+                "guild_scheduled_event_id": "912702226335272960",
+                "user_id": "80351110224678912",
+                "guild_id": "41771983423143937",
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            user_add,
+            DispatchEvent::GuildScheduledEventUserAdd {
+                guild_scheduled_event_id: Snowflake(912_702_226_335_272_960),
+                user_id: Snowflake(80_351_110_224_678_912),
+                guild_id: Snowflake(41_771_983_423_143_937),
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_auto_moderation_action_execution() {
+        let execution: DispatchEvent = serde_json::from_value(json!({
+            "t": "AUTO_MODERATION_ACTION_EXECUTION",
+            "d": {
+                "guild_id": "41771983423143937",
+                "action": { "type": 3, "metadata": { "duration_seconds": 60 } },
+                "rule_id": "969707018069872670",
+                "rule_trigger_type": 1,
+                "user_id": "80351110224678912",
+                "channel_id": "127121515262115840",
+                "message_id": "1001",
+                "content": "a bad word",
+                "matched_keyword": "bad*",
+                "matched_content": "bad",
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::AutoModerationActionExecution(execution) = execution else {
+            panic!("expected an AUTO_MODERATION_ACTION_EXECUTION event");
+        };
+        assert_eq!(execution.action.kind, AutoModerationActionType::Timeout);
+        assert_eq!(execution.action.metadata.duration_seconds, Some(60));
+        assert_eq!(
+            execution.rule_trigger_type,
+            AutoModerationTriggerType::Keyword
+        );
+        assert_eq!(execution.alert_system_message_id, None);
+        assert_eq!(execution.matched_keyword.as_deref(), Some("bad*"));
+    }
+
+    #[test]
+    fn deserialize_auto_moderation_rule_create() {
+        let rule_create: DispatchEvent = serde_json::from_value(json!({
+            "t": "AUTO_MODERATION_RULE_CREATE",
+            "d": {
+                "id": "969707018069872670",
+                "guild_id": "41771983423143937",
+                "name": "Keyword Filter",
+                "creator_id": "80351110224678912",
+                "event_type": 1,
+                "trigger_type": 1,
+                "trigger_metadata": { "keyword_filter": ["bad*"] },
+                "actions": [{ "type": 1, "metadata": { "custom_message": "Please be nice" } }],
+                "enabled": true,
+                "exempt_roles": [],
+                "exempt_channels": [],
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::AutoModerationRuleCreate(rule) = rule_create else {
+            panic!("expected an AUTO_MODERATION_RULE_CREATE event");
+        };
+        assert_eq!(rule.event_type, AutoModerationEventType::MessageSend);
+        assert_eq!(rule.actions[0].kind, AutoModerationActionType::BlockMessage);
+        assert!(rule.enabled);
+    }
 }
@@ -3,14 +3,21 @@
 //! important for the functionality of the client.
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
-use crate::gateway::shard::ShardInformation;
+use crate::{
+    gateway::{error::GatewayDecodeError, shard::ShardInformation},
+    model::snowflake::Snowflake,
+};
 
-use super::{dispatch::DispatchEvent, intents::GatewayIntents};
+use super::{
+    dispatch::DispatchEvent, intents::GatewayIntents, presence::PresenceUpdate,
+    voice::VoiceStateUpdateData,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 #[repr(u8)]
-#[serde(into = "u8", from = "u8")]
+#[serde(into = "u8", try_from = "u8")]
 #[non_exhaustive]
 /// Used to identify the type of event sent and received by the gateway.
 ///
@@ -74,9 +81,11 @@ impl From<OpCode> for u8 {
     }
 }
 
-impl From<u8> for OpCode {
-    fn from(value: u8) -> Self {
-        match value {
+impl TryFrom<u8> for OpCode {
+    type Error = UnknownOpCode;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
             0 => OpCode::Dispatch,
             1 => OpCode::Heartbeat,
             2 => OpCode::Identify,
@@ -89,11 +98,23 @@ impl From<u8> for OpCode {
             10 => OpCode::Hello,
             11 => OpCode::HeartbeatACK,
             31 => OpCode::RequestSoundboardSounds,
-            _ => panic!("Invalid OpCode value: {value}"),
-        }
+            _ => return Err(UnknownOpCode(value)),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The error returned when converting a value that isn't a known [`OpCode`]
+pub struct UnknownOpCode(pub u8);
+
+impl std::fmt::Display for UnknownOpCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown opcode {}", self.0)
     }
 }
 
+impl std::error::Error for UnknownOpCode {}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
 /// Required properties for the [`OpCode::Identify`] opcode
 ///
@@ -104,6 +125,19 @@ pub struct IdentifyProperties {
     device: String,
 }
 
+impl IdentifyProperties {
+    /// Creates the properties the client identifies with. By default, the client is
+    /// identified with the current operating system and `discors` as the browser and device.
+    #[must_use]
+    pub fn new(os: &str, browser: &str, device: &str) -> Self {
+        Self {
+            os: os.to_string(),
+            browser: browser.to_string(),
+            device: device.to_string(),
+        }
+    }
+}
+
 impl Default for IdentifyProperties {
     fn default() -> Self {
         Self {
@@ -114,9 +148,27 @@ impl Default for IdentifyProperties {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(untagged)]
+/// Which members to request with [`OpCode::RequestGuildMembers`]
+pub enum GuildMembersFilter {
+    /// Members whose username starts with `query`, or all members if `query` is empty
+    Query {
+        /// The string the username starts with
+        query: String,
+        /// The maximum number of members to send, 0 requests all members when `query` is empty
+        limit: u32,
+    },
+    /// Members with the given IDs
+    UserIds {
+        /// The IDs of the members to send
+        user_ids: Vec<Snowflake>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(untagged)]
-/// The event data when receiving a [`ReceiveEvent`] via the gateway
+/// The event data when receiving an [`IncomingEvent`] via the gateway
 pub enum ReceiveEventData {
     /// Most events received are dispatched through this variant. As such, the data is
     /// contained within the inner [`DispatchEvent`] variant.
@@ -139,7 +191,7 @@ pub enum ReceiveEventData {
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
 #[serde(untagged)]
-/// The event data when sending a [`SendEvent`] via the gateway
+/// The event data when sending an [`OutgoingEvent`] via the gateway
 pub enum SendEventData {
     /// Sent in response to a [`OpCode::Heartbeat`] event or to keep the connection alive.
     /// Optionally contains the sequence number of the last event received.
@@ -166,10 +218,44 @@ pub enum SendEventData {
         /// The shard information for this connection, the first value is the current shard (based on a zero-based index),
         /// and the second value is the total number of shards.
         shard: Option<ShardInformation>,
-        // presence: Option<PresenceUpdate>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        /// The initial presence of the client
+        presence: Option<PresenceUpdate>,
         /// The intents of the client
         intents: GatewayIntents,
     },
+    /// Update the client's presence.
+    ///
+    /// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#update-presence)
+    PresenceUpdate(PresenceUpdate),
+    /// Request members of a guild, which are sent back in `GUILD_MEMBERS_CHUNK` events.
+    ///
+    /// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#request-guild-members)
+    RequestGuildMembers {
+        /// The ID of the guild to get members for
+        guild_id: Snowflake,
+        #[serde(flatten)]
+        /// Which members to request
+        filter: GuildMembersFilter,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        /// Whether to send the presences of the members
+        presences: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        /// A nonce to identify the `GUILD_MEMBERS_CHUNK` responses
+        nonce: Option<String>,
+    },
+    /// Request the soundboard sounds of a set of guilds, which are sent back in
+    /// `SOUNDBOARD_SOUNDS` events.
+    ///
+    /// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#request-soundboard-sounds)
+    RequestSoundboardSounds {
+        /// The IDs of the guilds to get soundboard sounds for
+        guild_ids: Vec<Snowflake>,
+    },
+    /// Join, move between or leave voice channels.
+    ///
+    /// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#update-voice-state)
+    VoiceStateUpdate(VoiceStateUpdateData),
     /// Resume a previous session that was terminated.
     ///
     /// [Discord documentation](https://discord.com/developers/docs/topics/gateway-events#resume)
@@ -184,74 +270,96 @@ pub enum SendEventData {
     },
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[derive(Debug, Clone, PartialEq)]
 /// An event received from the gateway
-pub struct Event {
+pub struct IncomingEvent {
     /// The opcode of the event
     pub op: OpCode,
-    #[serde(rename = "d")]
-    #[serde(skip_serializing)]
     /// The data of the event
-    pub receive_data: Option<ReceiveEventData>,
-    #[serde(rename = "d")]
-    #[serde(skip_deserializing)]
-    /// The data of the event
-    pub send_data: Option<SendEventData>,
-    #[serde(rename = "s")]
+    pub data: Option<ReceiveEventData>,
     /// The sequence number of the event, which should increment by one for each event
     pub sequence: Option<u64>,
-    #[serde(rename = "t")]
     /// The event name, if applicable
     pub event: Option<String>,
 }
 
-impl Default for Event {
-    fn default() -> Self {
+impl SendEventData {
+    /// The opcode the data is sent with
+    #[must_use]
+    pub fn opcode(&self) -> OpCode {
+        match self {
+            SendEventData::Heartbeat(_) => OpCode::Heartbeat,
+            SendEventData::Identify { .. } => OpCode::Identify,
+            SendEventData::PresenceUpdate(_) => OpCode::PresenceUpdate,
+            SendEventData::RequestGuildMembers { .. } => OpCode::RequestGuildMembers,
+            SendEventData::RequestSoundboardSounds { .. } => OpCode::RequestSoundboardSounds,
+            SendEventData::VoiceStateUpdate(_) => OpCode::VoiceStateUpdate,
+            SendEventData::Resume { .. } => OpCode::Resume,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+/// An event sent to the gateway, created from its data with [`OutgoingEvent::from`]
+pub struct OutgoingEvent {
+    /// The opcode of the event
+    pub op: OpCode,
+    #[serde(rename = "d")]
+    /// The data of the event
+    pub data: SendEventData,
+}
+
+impl From<SendEventData> for OutgoingEvent {
+    fn from(data: SendEventData) -> Self {
         Self {
-            op: OpCode::Heartbeat,
-            receive_data: None,
-            send_data: None,
-            sequence: None,
-            event: None,
+            op: data.opcode(),
+            data,
         }
     }
 }
 
-impl<'de> Deserialize<'de> for Event {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let mut event_map = serde_json::Map::deserialize(deserializer)?;
+impl IncomingEvent {
+    /// Decodes an event received from the gateway, naming where decoding failed if
+    /// it does.
+    pub fn from_map(mut event_map: Map<String, Value>) -> Result<Self, GatewayDecodeError> {
         let sequence = event_map.remove("s").and_then(|s| s.as_u64());
-        let op = OpCode::from(
-            event_map
-                .remove("op")
-                .ok_or_else(|| serde::de::Error::missing_field("op"))?
-                .as_u64()
-                .ok_or_else(|| serde::de::Error::custom("op is not a u64"))? as u8,
-        );
+        let op = event_map
+            .remove("op")
+            .ok_or(GatewayDecodeError::MissingOpcode)?;
+        let op = op
+            .as_u64()
+            .and_then(|op| u8::try_from(op).ok())
+            .and_then(|op| OpCode::try_from(op).ok())
+            .ok_or_else(|| GatewayDecodeError::InvalidOpcode(op.clone()))?;
         let data = match op {
             OpCode::Dispatch => Some(ReceiveEventData::Dispatch(
-                DispatchEvent::deserialize(&event_map)
-                    .map_err(|err| serde::de::Error::custom(err.to_string()))?,
+                <DispatchEvent as Deserialize>::deserialize(&event_map).map_err(|err| {
+                    let event = event_map.get("t").and_then(Value::as_str);
+                    GatewayDecodeError::InvalidDispatch(
+                        event.map(ToString::to_string),
+                        sequence,
+                        err,
+                    )
+                })?,
             )),
             OpCode::Heartbeat => Some(ReceiveEventData::Heartbeat),
             OpCode::Reconnect => Some(ReceiveEventData::Reconnect),
-            OpCode::InvalidSession => Some(ReceiveEventData::InvalidSession(
-                event_map
+            OpCode::InvalidSession => {
+                let inner = event_map
                     .remove("d")
-                    .ok_or_else(|| serde::de::Error::missing_field("d"))?
-                    .as_bool()
-                    .ok_or_else(|| serde::de::Error::custom("d is not a bool"))?,
-            )),
+                    .ok_or(GatewayDecodeError::MissingData(op.clone()))?;
+                Some(ReceiveEventData::InvalidSession(
+                    bool::deserialize(inner)
+                        .map_err(|err| GatewayDecodeError::InvalidData(op.clone(), err))?,
+                ))
+            }
             OpCode::Hello => {
                 let inner = event_map
                     .remove("d")
-                    .ok_or_else(|| serde::de::Error::missing_field("d"))?;
+                    .ok_or(GatewayDecodeError::MissingData(op.clone()))?;
                 Some(
                     ReceiveEventData::deserialize(inner)
-                        .map_err(|err| serde::de::Error::custom(err.to_string()))?,
+                        .map_err(|err| GatewayDecodeError::InvalidData(op.clone(), err))?,
                 )
             }
             OpCode::HeartbeatACK => Some(ReceiveEventData::HeartbeatAck),
@@ -263,10 +371,192 @@ impl<'de> Deserialize<'de> for Event {
 
         Ok(Self {
             op,
-            receive_data: data,
-            send_data: None,
+            data,
             sequence,
             event,
         })
     }
 }
+
+impl<'de> Deserialize<'de> for IncomingEvent {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let event_map = Map::deserialize(deserializer)?;
+        Self::from_map(event_map).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{
+        GatewayIntents, GuildMembersFilter, IdentifyProperties, IncomingEvent, OpCode,
+        OutgoingEvent, ReceiveEventData, SendEventData, UnknownOpCode,
+    };
+    use crate::{gateway::error::GatewayDecodeError, model::snowflake::Snowflake};
+
+    fn decode(value: serde_json::Value) -> Result<IncomingEvent, GatewayDecodeError> {
+        let serde_json::Value::Object(map) = value else {
+            panic!("events are objects");
+        };
+        IncomingEvent::from_map(map)
+    }
+
+    #[test]
+    fn decode_missing_opcode() {
+        assert!(matches!(
+            decode(json!({ "d": null })),
+            Err(GatewayDecodeError::MissingOpcode)
+        ));
+    }
+
+    #[test]
+    fn decode_invalid_opcode() {
+        assert!(matches!(
+            decode(json!({ "op": "10", "d": null })),
+            Err(GatewayDecodeError::InvalidOpcode(op)) if op == "10"
+        ));
+    }
+
+    #[test]
+    fn decode_unknown_opcode() {
+        assert!(matches!(
+            decode(json!({ "op": 99, "d": null })),
+            Err(GatewayDecodeError::InvalidOpcode(op)) if op == 99
+        ));
+        assert!(matches!(
+            decode(json!({ "op": 266, "d": null })),
+            Err(GatewayDecodeError::InvalidOpcode(op)) if op == 266
+        ));
+        assert!(serde_json::from_str::<IncomingEvent>(r#"{"op":99,"d":null}"#).is_err());
+        assert_eq!(OpCode::try_from(99), Err(UnknownOpCode(99)));
+        assert!(serde_json::from_value::<OpCode>(json!(99)).is_err());
+    }
+
+    #[test]
+    fn decode_missing_data() {
+        assert!(matches!(
+            decode(json!({ "op": 9 })),
+            Err(GatewayDecodeError::MissingData(OpCode::InvalidSession))
+        ));
+    }
+
+    #[test]
+    fn decode_invalid_hello() {
+        assert!(matches!(
+            decode(json!({ "op": 10, "d": { "heartbeat_interval": "soon" } })),
+            Err(GatewayDecodeError::InvalidData(OpCode::Hello, _))
+        ));
+    }
+
+    #[test]
+    fn decode_invalid_dispatch() {
+        let result = decode(json!({ "op": 0, "s": 1, "t": "READY", "d": { "v": 10 } }));
+        assert!(matches!(
+            result,
+            Err(GatewayDecodeError::InvalidDispatch(Some(ref event), Some(1), _)) if event == "READY"
+        ));
+        assert!(std::error::Error::source(&result.unwrap_err()).is_some());
+    }
+
+    fn request_guild_members(filter: GuildMembersFilter) -> serde_json::Value {
+        serde_json::to_value(OutgoingEvent::from(SendEventData::RequestGuildMembers {
+            guild_id: Snowflake(41_771_983_444_115_456),
+            filter,
+            presences: None,
+            nonce: Some("nonce".to_string()),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn serialize_request_guild_members_by_query() {
+        let value = request_guild_members(GuildMembersFilter::Query {
+            query: String::new(),
+            limit: 0,
+        });
+        assert_eq!(value["op"], 8);
+        assert_eq!(
+            value["d"],
+            json!({
+                "guild_id": "41771983444115456",
+                "query": "",
+                "limit": 0,
+                "nonce": "nonce",
+            })
+        );
+    }
+
+    #[test]
+    fn serialize_request_guild_members_by_user_ids() {
+        let value = request_guild_members(GuildMembersFilter::UserIds {
+            user_ids: vec![Snowflake(80_351_110_224_678_912)],
+        });
+        assert_eq!(
+            value["d"],
+            json!({
+                "guild_id": "41771983444115456",
+                "user_ids": ["80351110224678912"],
+                "nonce": "nonce",
+            })
+        );
+    }
+
+    #[test]
+    fn serialize_request_soundboard_sounds() {
+        let value = serde_json::to_value(OutgoingEvent::from(
+            SendEventData::RequestSoundboardSounds {
+                guild_ids: vec![Snowflake(613_425_648_685_547_541)],
+            },
+        ))
+        .unwrap();
+
+        assert_eq!(value["op"], 31);
+        assert_eq!(value["d"], json!({ "guild_ids": ["613425648685547541"] }));
+    }
+
+    #[test]
+    fn serialize_identify() {
+        let value = serde_json::to_value(OutgoingEvent::from(SendEventData::Identify {
+            token: "token".to_string(),
+            properties: IdentifyProperties::new("linux", "discors", "discors"),
+            compress: None,
+            large_threshold: None,
+            shard: None,
+            presence: None,
+            intents: GatewayIntents::GUILDS,
+        }))
+        .unwrap();
+
+        assert_eq!(
+            value,
+            json!({
+                "op": 2,
+                "d": {
+                    "token": "token",
+                    "properties": { "os": "linux", "browser": "discors", "device": "discors" },
+                    "intents": 1,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn deserialize_hello() {
+        let event: IncomingEvent =
+            serde_json::from_value(json!({ "op": 10, "d": { "heartbeat_interval": 41250 } }))
+                .unwrap();
+
+        assert_eq!(event.op, OpCode::Hello);
+        assert_eq!(
+            event.data,
+            Some(ReceiveEventData::Hello {
+                heartbeat_interval: 41250
+            })
+        );
+        assert_eq!(event.sequence, None);
+    }
+}
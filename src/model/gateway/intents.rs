@@ -1,10 +1,16 @@
+use std::fmt::Display;
+
 use bitflags::bitflags;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 bitflags! {
     /// [Discord documentation](https://discord.com/developers/docs/topics/gateway#gateway-intents)
+    ///
+    /// Discord sends intents as an integer that grows as new intents are added, so they
+    /// are stored as a `u64` to leave room for intents past bit 31. Deserializing drops
+    /// bits that aren't defined here, and [`GatewayIntents::all`] returns every defined intent.
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-    pub struct GatewayIntents: u32 {
+    pub struct GatewayIntents: u64 {
         /// The guilds intent allows the gateway to send the following events:
         /// - GUILD_CREATE
         /// - GUILD_UPDATE
@@ -132,6 +138,160 @@ bitflags! {
     }
 }
 
+/// The dispatch events each intent allows the gateway to send, as listed on the intents
+const INTENT_EVENTS: &[(GatewayIntents, &[&str])] = &[
+    (
+        GatewayIntents::GUILDS,
+        &[
+            "GUILD_CREATE",
+            "GUILD_UPDATE",
+            "GUILD_DELETE",
+            "GUILD_ROLE_CREATE",
+            "GUILD_ROLE_UPDATE",
+            "GUILD_ROLE_DELETE",
+            "CHANNEL_CREATE",
+            "CHANNEL_UPDATE",
+            "CHANNEL_DELETE",
+            "CHANNEL_PINS_UPDATE",
+            "THREAD_CREATE",
+            "THREAD_UPDATE",
+            "THREAD_DELETE",
+            "THREAD_LIST_SYNC",
+            "THREAD_MEMBER_UPDATE",
+            "THREAD_MEMBERS_UPDATE",
+            "STAGE_INSTANCE_CREATE",
+            "STAGE_INSTANCE_UPDATE",
+            "STAGE_INSTANCE_DELETE",
+        ],
+    ),
+    (
+        GatewayIntents::GUILD_MEMBERS,
+        &[
+            "GUILD_MEMBER_ADD",
+            "GUILD_MEMBER_UPDATE",
+            "GUILD_MEMBER_REMOVE",
+            "THREAD_MEMBERS_UPDATE",
+        ],
+    ),
+    (
+        GatewayIntents::GUILD_MODERATION,
+        &[
+            "GUILD_AUDIT_LOG_ENTRY_CREATE",
+            "GUILD_BAN_ADD",
+            "GUILD_BAN_REMOVE",
+        ],
+    ),
+    (
+        GatewayIntents::GUILD_EXPRESSIONS,
+        &[
+            "GUILD_EMOJIS_UPDATE",
+            "GUILD_STICKERS_UPDATE",
+            "GUILD_SOUNDBOARD_SOUND_CREATE",
+            "GUILD_SOUNDBOARD_SOUND_UPDATE",
+            "GUILD_SOUNDBOARD_SOUND_DELETE",
+            "GUILD_SOUNDBOARD_SOUNDS_UPDATE",
+        ],
+    ),
+    (
+        GatewayIntents::GUILD_INTEGRATIONS,
+        &[
+            "GUILD_INTEGRATIONS_UPDATE",
+            "INTEGRATION_CREATE",
+            "INTEGRATION_UPDATE",
+            "INTEGRATION_DELETE",
+        ],
+    ),
+    (GatewayIntents::GUILD_WEBHOOKS, &["WEBHOOKS_UPDATE"]),
+    (
+        GatewayIntents::GUILD_INVITES,
+        &["INVITE_CREATE", "INVITE_DELETE"],
+    ),
+    (
+        GatewayIntents::GUILD_VOICE_STATES,
+        &["VOICE_CHANNEL_EFFECT_SEND", "VOICE_STATE_UPDATE"],
+    ),
+    (GatewayIntents::GUILD_PRESENCES, &["PRESENCE_UPDATE"]),
+    (
+        GatewayIntents::GUILD_MESSAGES,
+        &[
+            "MESSAGE_CREATE",
+            "MESSAGE_UPDATE",
+            "MESSAGE_DELETE",
+            "MESSAGE_DELETE_BULK",
+        ],
+    ),
+    (
+        GatewayIntents::GUILD_MESSAGE_REACTIONS,
+        &[
+            "MESSAGE_REACTION_ADD",
+            "MESSAGE_REACTION_REMOVE",
+            "MESSAGE_REACTION_REMOVE_ALL",
+            "MESSAGE_REACTION_REMOVE_EMOJI",
+        ],
+    ),
+    (GatewayIntents::GUILD_MESSAGE_TYPING, &["TYPING_START"]),
+    (
+        GatewayIntents::DIRECT_MESSAGES,
+        &[
+            "MESSAGE_CREATE",
+            "MESSAGE_UPDATE",
+            "MESSAGE_DELETE",
+            "CHANNEL_PINS_UPDATE",
+        ],
+    ),
+    (
+        GatewayIntents::DIRECT_MESSAGE_REACTIONS,
+        &[
+            "MESSAGE_REACTION_ADD",
+            "MESSAGE_REACTION_REMOVE",
+            "MESSAGE_REACTION_REMOVE_ALL",
+            "MESSAGE_REACTION_REMOVE_EMOJI",
+        ],
+    ),
+    (GatewayIntents::DIRECT_MESSAGE_TYPING, &["TYPING_START"]),
+    (
+        GatewayIntents::GUILD_SCHEDULED_EVENTS,
+        &[
+            "GUILD_SCHEDULED_EVENT_CREATE",
+            "GUILD_SCHEDULED_EVENT_UPDATE",
+            "GUILD_SCHEDULED_EVENT_DELETE",
+            "GUILD_SCHEDULED_EVENT_USER_ADD",
+            "GUILD_SCHEDULED_EVENT_USER_REMOVE",
+        ],
+    ),
+    (
+        GatewayIntents::AUTO_MODERATION_CONFIGURATION,
+        &[
+            "AUTO_MODERATION_RULE_CREATE",
+            "AUTO_MODERATION_RULE_UPDATE",
+            "AUTO_MODERATION_RULE_DELETE",
+        ],
+    ),
+    (
+        GatewayIntents::AUTO_MODERATION_EXECUTION,
+        &["AUTO_MODERATION_ACTION_EXECUTION"],
+    ),
+    (
+        GatewayIntents::GUILD_MESSAGE_POLLS,
+        &["MESSAGE_POLL_VOTE_ADD", "MESSAGE_POLL_VOTE_REMOVE"],
+    ),
+    (
+        GatewayIntents::DIRECT_MESSAGE_POLLS,
+        &["MESSAGE_POLL_VOTE_ADD", "MESSAGE_POLL_VOTE_REMOVE"],
+    ),
+];
+
+impl<'de> Deserialize<'de> for GatewayIntents {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // Bits added by Discord after this version are dropped rather than rejected
+        let bits = u64::deserialize(deserializer)?;
+        Ok(Self::from_bits_truncate(bits))
+    }
+}
+
 impl Serialize for GatewayIntents {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
@@ -142,14 +302,58 @@ impl Serialize for GatewayIntents {
 }
 
 impl GatewayIntents {
+    /// The bits of every intent documented by Discord. Any other bit is reserved and
+    /// sending it will close the connection with
+    /// [`CloseCode::InvalidIntents`](crate::gateway::error::CloseCode::InvalidIntents).
+    #[must_use]
+    pub const fn known_bits() -> u64 {
+        Self::all().bits()
+    }
+
+    /// The intents needed to receive the named dispatch events, such as `"MESSAGE_CREATE"`.
+    /// An event sent in both guilds and direct messages requires the intents of both.
+    ///
+    /// Events that are sent regardless of intents, such as `READY`, and unknown event
+    /// names add no intents.
+    #[must_use]
+    pub fn for_events(events: &[&str]) -> GatewayIntents {
+        INTENT_EVENTS
+            .iter()
+            .filter(|(_, intent_events)| intent_events.iter().any(|event| events.contains(event)))
+            .fold(Self::empty(), |intents, (intent, _)| intents | *intent)
+    }
+
     #[must_use]
     pub fn privileged() -> GatewayIntents {
         Self::GUILD_MEMBERS | Self::GUILD_PRESENCES | Self::MESSAGE_CONTENT
     }
 
+    /// The privileged intents within these intents, which have to be enabled for the
+    /// application in the developer portal or the gateway closes the connection with
+    /// [`CloseCode::DisallowedIntents`](crate::gateway::error::CloseCode::DisallowedIntents).
+    #[must_use]
+    pub fn privileged_subset(self) -> GatewayIntents {
+        self & Self::privileged()
+    }
+
     #[must_use]
     pub fn non_privileged() -> GatewayIntents {
-        Self::privileged().complement()
+        Self::all().difference(Self::privileged())
+    }
+
+    /// The names of the intents that are set, in order of their bits, such as
+    /// `["GUILDS", "GUILD_MESSAGES"]`
+    #[must_use]
+    pub fn names(&self) -> Vec<&'static str> {
+        self.iter_names().map(|(name, _)| name).collect()
+    }
+}
+
+impl Display for GatewayIntents {
+    /// Lists the names of the intents that are set separated by `|`, such as
+    /// `GUILDS | GUILD_MESSAGES`, or nothing if none are set.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.names().join(" | "))
     }
 }
 
@@ -158,3 +362,133 @@ impl Default for GatewayIntents {
         Self::non_privileged()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{GatewayIntents, INTENT_EVENTS};
+
+    #[test]
+    fn non_privileged_has_no_reserved_bits() {
+        assert_eq!(
+            GatewayIntents::non_privileged().bits() & !GatewayIntents::known_bits(),
+            0
+        );
+    }
+
+    #[test]
+    fn non_privileged_excludes_undocumented_bits() {
+        let reserved = [17, 18, 19, 22, 23, 26, 27, 28, 29, 30, 31, 32, 63];
+        for bit in reserved {
+            assert_eq!(
+                GatewayIntents::non_privileged().bits() & (1 << bit),
+                0,
+                "bit {bit} is reserved"
+            );
+        }
+    }
+
+    #[test]
+    fn every_intent_is_privileged_or_non_privileged() {
+        for (name, intent) in GatewayIntents::all().iter_names() {
+            assert_ne!(
+                GatewayIntents::privileged().contains(intent),
+                GatewayIntents::non_privileged().contains(intent),
+                "{name} must be exactly one of privileged or non-privileged"
+            );
+        }
+        assert_eq!(
+            GatewayIntents::privileged() | GatewayIntents::non_privileged(),
+            GatewayIntents::all()
+        );
+    }
+
+    #[test]
+    fn intent_docs_match_intent_events() {
+        // The events listed in each intent's doc comment, read from this file
+        let source = include_str!("intents.rs");
+        let source = &source[..source.find("const INTENT_EVENTS").unwrap()];
+        let mut documented = Vec::new();
+        let mut events = Vec::new();
+        for line in source.lines().map(str::trim) {
+            if let Some(event) = line.strip_prefix("/// - ") {
+                events.push(event);
+            } else if let Some(constant) = line.strip_prefix("const ") {
+                let name = constant.split_whitespace().next().unwrap();
+                documented.push((name, std::mem::take(&mut events)));
+            }
+        }
+
+        assert_eq!(documented.len(), GatewayIntents::all().iter().count());
+        for (name, events) in documented {
+            let intent = GatewayIntents::from_name(name).unwrap();
+            let row = INTENT_EVENTS
+                .iter()
+                .find(|(row_intent, _)| *row_intent == intent)
+                .map_or(&[][..], |(_, row_events)| *row_events);
+            assert_eq!(events, row, "{name}");
+        }
+    }
+
+    #[test]
+    fn for_events_maps_event_names() {
+        assert!(GatewayIntents::for_events(&["GUILD_MEMBER_ADD"])
+            .contains(GatewayIntents::GUILD_MEMBERS));
+        assert_eq!(
+            GatewayIntents::for_events(&["MESSAGE_CREATE", "TYPING_START"]),
+            GatewayIntents::GUILD_MESSAGES
+                | GatewayIntents::DIRECT_MESSAGES
+                | GatewayIntents::GUILD_MESSAGE_TYPING
+                | GatewayIntents::DIRECT_MESSAGE_TYPING
+        );
+        assert_eq!(
+            GatewayIntents::for_events(&["READY", "NOT_AN_EVENT"]),
+            GatewayIntents::empty()
+        );
+    }
+
+    #[test]
+    fn display_lists_intent_names() {
+        assert_eq!(
+            GatewayIntents::privileged().to_string(),
+            "GUILD_MEMBERS | GUILD_PRESENCES | MESSAGE_CONTENT"
+        );
+        assert_eq!(GatewayIntents::GUILDS.names(), ["GUILDS"]);
+        assert_eq!(GatewayIntents::empty().to_string(), "");
+    }
+
+    #[test]
+    fn privileged_subset_keeps_privileged_intents() {
+        let intents = GatewayIntents::GUILDS | GatewayIntents::MESSAGE_CONTENT;
+        assert_eq!(intents.privileged_subset(), GatewayIntents::MESSAGE_CONTENT);
+        assert!(GatewayIntents::non_privileged()
+            .privileged_subset()
+            .is_empty());
+    }
+
+    #[test]
+    fn intents_round_trip() {
+        let intents = GatewayIntents::privileged() | GatewayIntents::GUILDS;
+        let json = serde_json::to_string(&intents).unwrap();
+        assert_eq!(
+            serde_json::from_str::<GatewayIntents>(&json).unwrap(),
+            intents
+        );
+
+        let unknown = serde_json::json!(GatewayIntents::all().bits() | 1 << 40);
+        assert_eq!(
+            serde_json::from_value::<GatewayIntents>(unknown).unwrap(),
+            GatewayIntents::all()
+        );
+    }
+
+    #[test]
+    fn high_intent_bits_round_trip() {
+        let intents = GatewayIntents::DIRECT_MESSAGE_POLLS;
+        let value = serde_json::to_value(intents).unwrap();
+        assert_eq!(value, serde_json::json!(1 << 25));
+        assert_eq!(
+            GatewayIntents::from_bits_truncate(value.as_u64().unwrap()),
+            intents
+        );
+    }
+}
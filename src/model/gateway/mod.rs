@@ -3,3 +3,6 @@
 pub mod dispatch;
 pub mod event;
 pub mod intents;
+pub mod presence;
+pub mod session;
+pub mod voice;
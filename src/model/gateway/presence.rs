@@ -0,0 +1,328 @@
+//! Presence is the status and activities the client shows to other users.
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{emoji::Emoji, snowflake::Snowflake, user::PartialUser};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// The status of the client
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#update-presence-status-types)
+pub enum StatusType {
+    /// Online
+    Online,
+    /// Do Not Disturb
+    Dnd,
+    /// AFK
+    Idle,
+    /// Invisible and shown as offline
+    Invisible,
+    /// Offline
+    Offline,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+#[non_exhaustive]
+/// The type of an activity, which decides how it is displayed
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#activity-object-activity-types)
+pub enum ActivityType {
+    /// Playing {name}
+    Playing,
+    /// Streaming {details}
+    Streaming,
+    /// Listening to {name}
+    Listening,
+    /// Watching {name}
+    Watching,
+    /// {emoji} {state}
+    Custom,
+    /// Competing in {name}
+    Competing,
+    /// An activity type that is not yet documented
+    Unknown(u8),
+}
+
+impl From<ActivityType> for u8 {
+    fn from(value: ActivityType) -> Self {
+        match value {
+            ActivityType::Playing => 0,
+            ActivityType::Streaming => 1,
+            ActivityType::Listening => 2,
+            ActivityType::Watching => 3,
+            ActivityType::Custom => 4,
+            ActivityType::Competing => 5,
+            ActivityType::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for ActivityType {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => ActivityType::Playing,
+            1 => ActivityType::Streaming,
+            2 => ActivityType::Listening,
+            3 => ActivityType::Watching,
+            4 => ActivityType::Custom,
+            5 => ActivityType::Competing,
+            _ => ActivityType::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// An activity the client is partaking in
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#activity-object)
+pub struct Activity {
+    /// The activity's name
+    pub name: String,
+    #[serde(rename = "type")]
+    /// The activity type
+    pub kind: ActivityType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// The stream URL, only used when the type is [`ActivityType::Streaming`]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// The user's current party status, or the text used for a custom status
+    pub state: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// What the user is currently doing
+    pub details: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// The emoji shown with a custom status
+    pub emoji: Option<Emoji>,
+}
+
+impl Activity {
+    fn new(kind: ActivityType, name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            url: None,
+            state: None,
+            details: None,
+            emoji: None,
+        }
+    }
+
+    /// An activity shown as "Playing {name}"
+    #[must_use]
+    pub fn playing(name: impl Into<String>) -> Self {
+        Self::new(ActivityType::Playing, name)
+    }
+
+    /// An activity shown as "Streaming {name}", linking to the Twitch or `YouTube` stream at `url`
+    #[must_use]
+    pub fn streaming(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            url: Some(url.into()),
+            ..Self::new(ActivityType::Streaming, name)
+        }
+    }
+
+    /// An activity shown as "Listening to {name}"
+    #[must_use]
+    pub fn listening(name: impl Into<String>) -> Self {
+        Self::new(ActivityType::Listening, name)
+    }
+
+    /// An activity shown as "Watching {name}"
+    #[must_use]
+    pub fn watching(name: impl Into<String>) -> Self {
+        Self::new(ActivityType::Watching, name)
+    }
+
+    /// An activity shown as "Competing in {name}"
+    #[must_use]
+    pub fn competing(name: impl Into<String>) -> Self {
+        Self::new(ActivityType::Competing, name)
+    }
+
+    /// A custom status shown as `state`
+    #[must_use]
+    pub fn custom(state: impl Into<String>) -> Self {
+        Self {
+            state: Some(state.into()),
+            // The name of a custom status isn't shown, but is required
+            ..Self::new(ActivityType::Custom, "Custom Status")
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// The presence sent when identifying or updating the client's presence
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#update-presence)
+pub struct PresenceUpdate {
+    /// Unix time (in milliseconds) of when the client went idle, or `None` if the client is not idle
+    pub since: Option<u64>,
+    /// The client's activities
+    pub activities: Vec<Activity>,
+    /// The client's new status
+    pub status: StatusType,
+    /// Whether the client is AFK
+    pub afk: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+/// The status of a user on each platform they are active on
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#client-status-object)
+pub struct ClientStatus {
+    /// The status on a desktop application
+    pub desktop: Option<StatusType>,
+    /// The status on a mobile application
+    pub mobile: Option<StatusType>,
+    /// The status on the web application or a bot
+    pub web: Option<StatusType>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// The presence of a user in a guild
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#presence-update)
+pub struct Presence {
+    /// The user the presence is for. Only the ID is guaranteed to be sent.
+    pub user: PartialUser,
+    /// The ID of the guild, which is not sent with the presences of a guild's members
+    pub guild_id: Option<Snowflake>,
+    /// The user's status
+    pub status: StatusType,
+    /// The user's current activities
+    #[serde(default)]
+    pub activities: Vec<Activity>,
+    /// The user's status on each platform
+    #[serde(default)]
+    pub client_status: ClientStatus,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Activity, ActivityType, ClientStatus, Presence, PresenceUpdate, StatusType};
+    use crate::model::{emoji::Emoji, snowflake::Snowflake};
+
+    #[test]
+    fn serialize_presence_update() {
+        let presence = PresenceUpdate {
+            since: None,
+            activities: vec![Activity {
+                name: "Save the Oxford Comma".to_string(),
+                kind: ActivityType::Playing,
+                url: None,
+                state: None,
+                details: None,
+                emoji: None,
+            }],
+            status: StatusType::Online,
+            afk: false,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&presence).unwrap(),
+            json!({
+                "since": null,
+                "activities": [{ "name": "Save the Oxford Comma", "type": 0 }],
+                "status": "online",
+                "afk": false,
+            })
+        );
+    }
+
+    #[test]
+    fn activity_constructors_set_their_type() {
+        for (activity, kind) in [
+            (Activity::playing("a game"), 0),
+            (
+                Activity::streaming("a stream", "https://twitch.tv/discord"),
+                1,
+            ),
+            (Activity::listening("a song"), 2),
+            (Activity::watching("a video"), 3),
+            (Activity::custom("Busy"), 4),
+            (Activity::competing("a tournament"), 5),
+        ] {
+            assert_eq!(serde_json::to_value(activity).unwrap()["type"], kind);
+        }
+    }
+
+    #[test]
+    fn serialize_streaming_and_custom_activities() {
+        assert_eq!(
+            serde_json::to_value(Activity::streaming("a stream", "https://twitch.tv/discord"))
+                .unwrap(),
+            json!({ "name": "a stream", "type": 1, "url": "https://twitch.tv/discord" })
+        );
+        assert_eq!(
+            serde_json::to_value(Activity::custom("Busy")).unwrap(),
+            json!({ "name": "Custom Status", "type": 4, "state": "Busy" })
+        );
+        assert_eq!(
+            serde_json::to_value(Activity::playing("a game")).unwrap(),
+            json!({ "name": "a game", "type": 0 })
+        );
+    }
+
+    #[test]
+    fn serialize_watching_activity() {
+        let activity = Activity {
+            details: Some("Season 2".to_string()),
+            ..Activity::watching("a series")
+        };
+        assert_eq!(
+            serde_json::to_value(activity).unwrap(),
+            json!({ "name": "a series", "type": 3, "details": "Season 2" })
+        );
+    }
+
+    #[test]
+    fn serialize_custom_status_with_emoji() {
+        let activity = Activity {
+            emoji: Some(Emoji {
+                id: None,
+                name: Some("🔥".to_string()),
+                animated: None,
+            }),
+            ..Activity::custom("On fire")
+        };
+        assert_eq!(
+            serde_json::to_value(activity).unwrap(),
+            json!({
+                "name": "Custom Status",
+                "type": 4,
+                "state": "On fire",
+                "emoji": { "id": null, "name": "🔥", "animated": null },
+            })
+        );
+    }
+
+    #[test]
+    fn deserialize_presence_with_partial_user() {
+        let presence: Presence = serde_json::from_value(json!({
+            "user": { "id": "80351110224678912" },
+            "guild_id": "41771983423143937",
+            "status": "dnd",
+            "activities": [{ "name": "a game", "type": 0, "created_at": 1_618_953_630_000_u64 }],
+            "client_status": { "desktop": "dnd", "mobile": "idle" },
+        }))
+        .unwrap();
+
+        assert_eq!(presence.user.id, Snowflake(80_351_110_224_678_912));
+        assert_eq!(presence.user.username, None);
+        assert_eq!(presence.status, StatusType::Dnd);
+        assert_eq!(presence.activities, [Activity::playing("a game")]);
+        assert_eq!(
+            presence.client_status,
+            ClientStatus {
+                desktop: Some(StatusType::Dnd),
+                mobile: Some(StatusType::Idle),
+                web: None,
+            }
+        );
+    }
+}
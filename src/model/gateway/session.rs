@@ -0,0 +1,18 @@
+//! Session information returned when fetching the gateway for a bot.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// The number of sessions a bot may start, returned by the Get Gateway Bot endpoint.
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway#session-start-limit-object)
+pub struct SessionStartLimit {
+    /// The total number of session starts the current user is allowed
+    pub total: u64,
+    /// The remaining number of session starts the current user is allowed
+    pub remaining: u64,
+    /// The number of milliseconds after which the limit resets
+    pub reset_after: u64,
+    /// The number of identify requests allowed per 5 seconds
+    pub max_concurrency: u64,
+}
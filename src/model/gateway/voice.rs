@@ -0,0 +1,68 @@
+//! Voice payloads sent to the gateway when joining, moving between or leaving voice channels.
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::snowflake::Snowflake;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Sent when the client joins, moves between or leaves a voice channel
+///
+/// [Discord documentation](https://discord.com/developers/docs/events/gateway-events#update-voice-state)
+pub struct VoiceStateUpdateData {
+    /// The ID of the guild
+    pub guild_id: Snowflake,
+    /// The ID of the voice channel the client wants to join, or `None` to disconnect
+    pub channel_id: Option<Snowflake>,
+    /// Whether the client is muted
+    pub self_mute: bool,
+    /// Whether the client is deafened
+    pub self_deaf: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::VoiceStateUpdateData;
+    use crate::model::{
+        gateway::event::{OutgoingEvent, SendEventData},
+        snowflake::Snowflake,
+    };
+
+    #[test]
+    fn serialize_voice_state_update() {
+        let event = OutgoingEvent::from(SendEventData::VoiceStateUpdate(VoiceStateUpdateData {
+            guild_id: Snowflake(41_771_983_423_143_937),
+            channel_id: Some(Snowflake(127_121_515_262_115_840)),
+            self_mute: false,
+            self_deaf: false,
+        }));
+
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["op"], 4);
+        assert_eq!(
+            value["d"],
+            json!({
+                "guild_id": "41771983423143937",
+                "channel_id": "127121515262115840",
+                "self_mute": false,
+                "self_deaf": false,
+            })
+        );
+    }
+
+    #[test]
+    fn serialize_voice_disconnect() {
+        let data = VoiceStateUpdateData {
+            guild_id: Snowflake(41_771_983_423_143_937),
+            channel_id: None,
+            self_mute: false,
+            self_deaf: false,
+        };
+
+        assert_eq!(
+            serde_json::to_value(data).unwrap()["channel_id"],
+            json!(null)
+        );
+    }
+}
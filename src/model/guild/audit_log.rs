@@ -0,0 +1,316 @@
+//! The audit log module contains the entries guilds record for administrative actions.
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::snowflake::Snowflake;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+#[non_exhaustive]
+/// The type of action an audit log entry records
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/audit-log#audit-log-entry-object-audit-log-events)
+pub enum AuditLogEvent {
+    /// The guild's settings were updated
+    GuildUpdate,
+    /// A channel was created
+    ChannelCreate,
+    /// A channel's settings were updated
+    ChannelUpdate,
+    /// A channel was deleted
+    ChannelDelete,
+    /// A permission overwrite was added to a channel
+    ChannelOverwriteCreate,
+    /// A permission overwrite of a channel was updated
+    ChannelOverwriteUpdate,
+    /// A permission overwrite was removed from a channel
+    ChannelOverwriteDelete,
+    /// A member was removed from the guild
+    MemberKick,
+    /// Members were pruned from the guild
+    MemberPrune,
+    /// A member was banned from the guild
+    MemberBanAdd,
+    /// A ban was lifted
+    MemberBanRemove,
+    /// A member was updated
+    MemberUpdate,
+    /// A member was added to or removed from a role
+    MemberRoleUpdate,
+    /// A member was moved to a different voice channel
+    MemberMove,
+    /// A member was disconnected from a voice channel
+    MemberDisconnect,
+    /// A bot was added to the guild
+    BotAdd,
+    /// A role was created
+    RoleCreate,
+    /// A role was updated
+    RoleUpdate,
+    /// A role was deleted
+    RoleDelete,
+    /// An invite was created
+    InviteCreate,
+    /// An invite was updated
+    InviteUpdate,
+    /// An invite was deleted
+    InviteDelete,
+    /// A webhook was created
+    WebhookCreate,
+    /// A webhook was updated
+    WebhookUpdate,
+    /// A webhook was deleted
+    WebhookDelete,
+    /// An emoji was created
+    EmojiCreate,
+    /// An emoji was renamed
+    EmojiUpdate,
+    /// An emoji was deleted
+    EmojiDelete,
+    /// A message was deleted
+    MessageDelete,
+    /// Several messages were deleted at once
+    MessageBulkDelete,
+    /// A message was pinned
+    MessagePin,
+    /// A message was unpinned
+    MessageUnpin,
+    /// An integration was added to the guild
+    IntegrationCreate,
+    /// An integration was updated
+    IntegrationUpdate,
+    /// An integration was removed from the guild
+    IntegrationDelete,
+    /// A stage instance was created
+    StageInstanceCreate,
+    /// A stage instance was updated
+    StageInstanceUpdate,
+    /// A stage instance was deleted
+    StageInstanceDelete,
+    /// A sticker was created
+    StickerCreate,
+    /// A sticker was updated
+    StickerUpdate,
+    /// A sticker was deleted
+    StickerDelete,
+    /// A scheduled event was created
+    ScheduledEventCreate,
+    /// A scheduled event was updated
+    ScheduledEventUpdate,
+    /// A scheduled event was cancelled
+    ScheduledEventDelete,
+    /// A thread was created
+    ThreadCreate,
+    /// A thread was updated
+    ThreadUpdate,
+    /// A thread was deleted
+    ThreadDelete,
+    /// The permissions of an application command were updated
+    ApplicationCommandPermissionUpdate,
+    /// A soundboard sound was created
+    SoundboardSoundCreate,
+    /// A soundboard sound was updated
+    SoundboardSoundUpdate,
+    /// A soundboard sound was deleted
+    SoundboardSoundDelete,
+    /// An auto moderation rule was created
+    AutoModerationRuleCreate,
+    /// An auto moderation rule was updated
+    AutoModerationRuleUpdate,
+    /// An auto moderation rule was deleted
+    AutoModerationRuleDelete,
+    /// A message was blocked by auto moderation
+    AutoModerationBlockMessage,
+    /// A message was flagged by auto moderation
+    AutoModerationFlagToChannel,
+    /// A member was timed out by auto moderation
+    AutoModerationUserCommunicationDisabled,
+    /// A creator monetization request was created
+    CreatorMonetizationRequestCreated,
+    /// The terms of creator monetization were accepted
+    CreatorMonetizationTermsAccepted,
+    /// An onboarding question was created
+    OnboardingPromptCreate,
+    /// An onboarding question was updated
+    OnboardingPromptUpdate,
+    /// An onboarding question was deleted
+    OnboardingPromptDelete,
+    /// Onboarding was created
+    OnboardingCreate,
+    /// Onboarding was updated
+    OnboardingUpdate,
+    /// Server guide was created
+    HomeSettingsCreate,
+    /// Server guide was updated
+    HomeSettingsUpdate,
+    /// An action type that is not yet documented
+    Unknown(u8),
+}
+
+impl From<AuditLogEvent> for u8 {
+    fn from(value: AuditLogEvent) -> Self {
+        match value {
+            AuditLogEvent::GuildUpdate => 1,
+            AuditLogEvent::ChannelCreate => 10,
+            AuditLogEvent::ChannelUpdate => 11,
+            AuditLogEvent::ChannelDelete => 12,
+            AuditLogEvent::ChannelOverwriteCreate => 13,
+            AuditLogEvent::ChannelOverwriteUpdate => 14,
+            AuditLogEvent::ChannelOverwriteDelete => 15,
+            AuditLogEvent::MemberKick => 20,
+            AuditLogEvent::MemberPrune => 21,
+            AuditLogEvent::MemberBanAdd => 22,
+            AuditLogEvent::MemberBanRemove => 23,
+            AuditLogEvent::MemberUpdate => 24,
+            AuditLogEvent::MemberRoleUpdate => 25,
+            AuditLogEvent::MemberMove => 26,
+            AuditLogEvent::MemberDisconnect => 27,
+            AuditLogEvent::BotAdd => 28,
+            AuditLogEvent::RoleCreate => 30,
+            AuditLogEvent::RoleUpdate => 31,
+            AuditLogEvent::RoleDelete => 32,
+            AuditLogEvent::InviteCreate => 40,
+            AuditLogEvent::InviteUpdate => 41,
+            AuditLogEvent::InviteDelete => 42,
+            AuditLogEvent::WebhookCreate => 50,
+            AuditLogEvent::WebhookUpdate => 51,
+            AuditLogEvent::WebhookDelete => 52,
+            AuditLogEvent::EmojiCreate => 60,
+            AuditLogEvent::EmojiUpdate => 61,
+            AuditLogEvent::EmojiDelete => 62,
+            AuditLogEvent::MessageDelete => 72,
+            AuditLogEvent::MessageBulkDelete => 73,
+            AuditLogEvent::MessagePin => 74,
+            AuditLogEvent::MessageUnpin => 75,
+            AuditLogEvent::IntegrationCreate => 80,
+            AuditLogEvent::IntegrationUpdate => 81,
+            AuditLogEvent::IntegrationDelete => 82,
+            AuditLogEvent::StageInstanceCreate => 83,
+            AuditLogEvent::StageInstanceUpdate => 84,
+            AuditLogEvent::StageInstanceDelete => 85,
+            AuditLogEvent::StickerCreate => 90,
+            AuditLogEvent::StickerUpdate => 91,
+            AuditLogEvent::StickerDelete => 92,
+            AuditLogEvent::ScheduledEventCreate => 100,
+            AuditLogEvent::ScheduledEventUpdate => 101,
+            AuditLogEvent::ScheduledEventDelete => 102,
+            AuditLogEvent::ThreadCreate => 110,
+            AuditLogEvent::ThreadUpdate => 111,
+            AuditLogEvent::ThreadDelete => 112,
+            AuditLogEvent::ApplicationCommandPermissionUpdate => 121,
+            AuditLogEvent::SoundboardSoundCreate => 130,
+            AuditLogEvent::SoundboardSoundUpdate => 131,
+            AuditLogEvent::SoundboardSoundDelete => 132,
+            AuditLogEvent::AutoModerationRuleCreate => 140,
+            AuditLogEvent::AutoModerationRuleUpdate => 141,
+            AuditLogEvent::AutoModerationRuleDelete => 142,
+            AuditLogEvent::AutoModerationBlockMessage => 143,
+            AuditLogEvent::AutoModerationFlagToChannel => 144,
+            AuditLogEvent::AutoModerationUserCommunicationDisabled => 145,
+            AuditLogEvent::CreatorMonetizationRequestCreated => 150,
+            AuditLogEvent::CreatorMonetizationTermsAccepted => 151,
+            AuditLogEvent::OnboardingPromptCreate => 163,
+            AuditLogEvent::OnboardingPromptUpdate => 164,
+            AuditLogEvent::OnboardingPromptDelete => 165,
+            AuditLogEvent::OnboardingCreate => 166,
+            AuditLogEvent::OnboardingUpdate => 167,
+            AuditLogEvent::HomeSettingsCreate => 190,
+            AuditLogEvent::HomeSettingsUpdate => 191,
+            AuditLogEvent::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for AuditLogEvent {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => AuditLogEvent::GuildUpdate,
+            10 => AuditLogEvent::ChannelCreate,
+            11 => AuditLogEvent::ChannelUpdate,
+            12 => AuditLogEvent::ChannelDelete,
+            13 => AuditLogEvent::ChannelOverwriteCreate,
+            14 => AuditLogEvent::ChannelOverwriteUpdate,
+            15 => AuditLogEvent::ChannelOverwriteDelete,
+            20 => AuditLogEvent::MemberKick,
+            21 => AuditLogEvent::MemberPrune,
+            22 => AuditLogEvent::MemberBanAdd,
+            23 => AuditLogEvent::MemberBanRemove,
+            24 => AuditLogEvent::MemberUpdate,
+            25 => AuditLogEvent::MemberRoleUpdate,
+            26 => AuditLogEvent::MemberMove,
+            27 => AuditLogEvent::MemberDisconnect,
+            28 => AuditLogEvent::BotAdd,
+            30 => AuditLogEvent::RoleCreate,
+            31 => AuditLogEvent::RoleUpdate,
+            32 => AuditLogEvent::RoleDelete,
+            40 => AuditLogEvent::InviteCreate,
+            41 => AuditLogEvent::InviteUpdate,
+            42 => AuditLogEvent::InviteDelete,
+            50 => AuditLogEvent::WebhookCreate,
+            51 => AuditLogEvent::WebhookUpdate,
+            52 => AuditLogEvent::WebhookDelete,
+            60 => AuditLogEvent::EmojiCreate,
+            61 => AuditLogEvent::EmojiUpdate,
+            62 => AuditLogEvent::EmojiDelete,
+            72 => AuditLogEvent::MessageDelete,
+            73 => AuditLogEvent::MessageBulkDelete,
+            74 => AuditLogEvent::MessagePin,
+            75 => AuditLogEvent::MessageUnpin,
+            80 => AuditLogEvent::IntegrationCreate,
+            81 => AuditLogEvent::IntegrationUpdate,
+            82 => AuditLogEvent::IntegrationDelete,
+            83 => AuditLogEvent::StageInstanceCreate,
+            84 => AuditLogEvent::StageInstanceUpdate,
+            85 => AuditLogEvent::StageInstanceDelete,
+            90 => AuditLogEvent::StickerCreate,
+            91 => AuditLogEvent::StickerUpdate,
+            92 => AuditLogEvent::StickerDelete,
+            100 => AuditLogEvent::ScheduledEventCreate,
+            101 => AuditLogEvent::ScheduledEventUpdate,
+            102 => AuditLogEvent::ScheduledEventDelete,
+            110 => AuditLogEvent::ThreadCreate,
+            111 => AuditLogEvent::ThreadUpdate,
+            112 => AuditLogEvent::ThreadDelete,
+            121 => AuditLogEvent::ApplicationCommandPermissionUpdate,
+            130 => AuditLogEvent::SoundboardSoundCreate,
+            131 => AuditLogEvent::SoundboardSoundUpdate,
+            132 => AuditLogEvent::SoundboardSoundDelete,
+            140 => AuditLogEvent::AutoModerationRuleCreate,
+            141 => AuditLogEvent::AutoModerationRuleUpdate,
+            142 => AuditLogEvent::AutoModerationRuleDelete,
+            143 => AuditLogEvent::AutoModerationBlockMessage,
+            144 => AuditLogEvent::AutoModerationFlagToChannel,
+            145 => AuditLogEvent::AutoModerationUserCommunicationDisabled,
+            150 => AuditLogEvent::CreatorMonetizationRequestCreated,
+            151 => AuditLogEvent::CreatorMonetizationTermsAccepted,
+            163 => AuditLogEvent::OnboardingPromptCreate,
+            164 => AuditLogEvent::OnboardingPromptUpdate,
+            165 => AuditLogEvent::OnboardingPromptDelete,
+            166 => AuditLogEvent::OnboardingCreate,
+            167 => AuditLogEvent::OnboardingUpdate,
+            190 => AuditLogEvent::HomeSettingsCreate,
+            191 => AuditLogEvent::HomeSettingsUpdate,
+            _ => AuditLogEvent::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// A single administrative action recorded in a guild's audit log
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/audit-log#audit-log-entry-object)
+pub struct AuditLogEntry {
+    /// The entry ID
+    pub id: Snowflake,
+    /// The type of action that was taken
+    pub action_type: AuditLogEvent,
+    /// The ID of the user or application that took the action
+    pub user_id: Option<Snowflake>,
+    /// The ID of the entity affected by the action, such as a user, channel or role
+    pub target_id: Option<Snowflake>,
+    /// The reason given for the action
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// The ID of the guild
+    pub guild_id: Snowflake,
+}
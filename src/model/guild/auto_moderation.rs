@@ -0,0 +1,186 @@
+//! The auto moderation module contains the rules guilds use to moderate content
+//! automatically, and the actions taken when they are triggered.
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::snowflake::Snowflake;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+#[non_exhaustive]
+/// What a rule is checked against
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/auto-moderation#auto-moderation-rule-object-event-types)
+pub enum AutoModerationEventType {
+    /// A member sends or edits a message
+    MessageSend,
+    /// A member edits their profile
+    MemberUpdate,
+    /// An event type that is not yet documented
+    Unknown(u8),
+}
+
+impl From<AutoModerationEventType> for u8 {
+    fn from(value: AutoModerationEventType) -> Self {
+        match value {
+            AutoModerationEventType::MessageSend => 1,
+            AutoModerationEventType::MemberUpdate => 2,
+            AutoModerationEventType::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for AutoModerationEventType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => AutoModerationEventType::MessageSend,
+            2 => AutoModerationEventType::MemberUpdate,
+            _ => AutoModerationEventType::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+#[non_exhaustive]
+/// What triggers a rule
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/auto-moderation#auto-moderation-rule-object-trigger-types)
+pub enum AutoModerationTriggerType {
+    /// Content contains a word from a user-defined list
+    Keyword,
+    /// Content is generic spam
+    Spam,
+    /// Content contains a word from a list defined by Discord
+    KeywordPreset,
+    /// Content contains too many unique role and user mentions
+    MentionSpam,
+    /// A member's profile contains a word from a user-defined list
+    MemberProfile,
+    /// A trigger type that is not yet documented
+    Unknown(u8),
+}
+
+impl From<AutoModerationTriggerType> for u8 {
+    fn from(value: AutoModerationTriggerType) -> Self {
+        match value {
+            AutoModerationTriggerType::Keyword => 1,
+            AutoModerationTriggerType::Spam => 3,
+            AutoModerationTriggerType::KeywordPreset => 4,
+            AutoModerationTriggerType::MentionSpam => 5,
+            AutoModerationTriggerType::MemberProfile => 6,
+            AutoModerationTriggerType::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for AutoModerationTriggerType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => AutoModerationTriggerType::Keyword,
+            3 => AutoModerationTriggerType::Spam,
+            4 => AutoModerationTriggerType::KeywordPreset,
+            5 => AutoModerationTriggerType::MentionSpam,
+            6 => AutoModerationTriggerType::MemberProfile,
+            _ => AutoModerationTriggerType::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+#[non_exhaustive]
+/// What is done when a rule is triggered
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/auto-moderation#auto-moderation-action-object-action-types)
+pub enum AutoModerationActionType {
+    /// The message is blocked from being sent
+    BlockMessage,
+    /// An alert is sent to a channel
+    SendAlertMessage,
+    /// The member is timed out
+    Timeout,
+    /// The member is prevented from interacting in the guild
+    BlockMemberInteraction,
+    /// An action type that is not yet documented
+    Unknown(u8),
+}
+
+impl From<AutoModerationActionType> for u8 {
+    fn from(value: AutoModerationActionType) -> Self {
+        match value {
+            AutoModerationActionType::BlockMessage => 1,
+            AutoModerationActionType::SendAlertMessage => 2,
+            AutoModerationActionType::Timeout => 3,
+            AutoModerationActionType::BlockMemberInteraction => 4,
+            AutoModerationActionType::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for AutoModerationActionType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => AutoModerationActionType::BlockMessage,
+            2 => AutoModerationActionType::SendAlertMessage,
+            3 => AutoModerationActionType::Timeout,
+            4 => AutoModerationActionType::BlockMemberInteraction,
+            _ => AutoModerationActionType::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+/// The options of an action, which depend on its type
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/auto-moderation#auto-moderation-action-object-action-metadata)
+pub struct AutoModerationActionMetadata {
+    /// The channel alerts are sent to, for [`AutoModerationActionType::SendAlertMessage`]
+    pub channel_id: Option<Snowflake>,
+    /// How long the member is timed out for, for [`AutoModerationActionType::Timeout`]
+    pub duration_seconds: Option<u32>,
+    /// The message shown to the member, for [`AutoModerationActionType::BlockMessage`]
+    pub custom_message: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// An action taken when a rule is triggered
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/auto-moderation#auto-moderation-action-object)
+pub struct AutoModerationAction {
+    /// The type of action
+    #[serde(rename = "type")]
+    pub kind: AutoModerationActionType,
+    /// The options of the action
+    #[serde(default)]
+    pub metadata: AutoModerationActionMetadata,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// A rule that moderates the content of a guild
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/auto-moderation#auto-moderation-rule-object)
+pub struct AutoModerationRule {
+    /// The rule ID
+    pub id: Snowflake,
+    /// The ID of the guild
+    pub guild_id: Snowflake,
+    /// The name of the rule
+    pub name: String,
+    /// The ID of the user who created the rule
+    pub creator_id: Snowflake,
+    /// What the rule is checked against
+    pub event_type: AutoModerationEventType,
+    /// What triggers the rule
+    pub trigger_type: AutoModerationTriggerType,
+    /// What is done when the rule is triggered
+    pub actions: Vec<AutoModerationAction>,
+    /// Whether the rule is enabled
+    pub enabled: bool,
+    /// The IDs of the roles the rule doesn't apply to
+    #[serde(default)]
+    pub exempt_roles: Vec<Snowflake>,
+    /// The IDs of the channels the rule doesn't apply to
+    #[serde(default)]
+    pub exempt_channels: Vec<Snowflake>,
+}
@@ -0,0 +1,37 @@
+//! The integration module contains the integrations of guilds, such as bots and Twitch
+//! or `YouTube` subscriptions.
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::snowflake::Snowflake;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// The account of an integration
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/guild#integration-account-object)
+pub struct IntegrationAccount {
+    /// The ID of the account, which is not a snowflake for Twitch and `YouTube` accounts
+    pub id: String,
+    /// The name of the account
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// An integration of a guild
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/guild#integration-object)
+pub struct Integration {
+    /// The integration ID
+    pub id: Snowflake,
+    /// The integration name
+    pub name: String,
+    /// The integration type, such as `twitch`, `youtube`, `discord` or
+    /// `guild_subscription`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Whether the integration is enabled
+    #[serde(default)]
+    pub enabled: bool,
+    /// The account of the integration
+    pub account: IntegrationAccount,
+}
@@ -0,0 +1,156 @@
+//! The member module contains all the guild member-related structs and enums.
+
+use bitflags::bitflags;
+use serde::{Deserialize, Serialize};
+
+use crate::model::{snowflake::Snowflake, timestamp::Timestamp, user::User};
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[non_exhaustive]
+    /// The flags of a guild member
+    ///
+    /// [Discord documentation](https://discord.com/developers/docs/resources/guild#guild-member-object-guild-member-flags)
+    pub struct MemberFlags: u64 {
+        /// Member has left and rejoined the guild
+        const DID_REJOIN = 1 << 0;
+        /// Member has completed onboarding
+        const COMPLETED_ONBOARDING = 1 << 1;
+        /// Member is exempt from guild verification requirements
+        const BYPASSES_VERIFICATION = 1 << 2;
+        /// Member has started onboarding
+        const STARTED_ONBOARDING = 1 << 3;
+        /// Member is a guest and can only access the voice channel they were invited to
+        const IS_GUEST = 1 << 4;
+        /// Member has started Server Guide new member actions
+        const STARTED_HOME_ACTIONS = 1 << 5;
+        /// Member has completed Server Guide new member actions
+        const COMPLETED_HOME_ACTIONS = 1 << 6;
+        /// Member's username, display name, or nickname is blocked by AutoMod
+        const AUTOMOD_QUARANTINED_USERNAME = 1 << 7;
+        /// Member has dismissed the DM settings upsell
+        const DM_SETTINGS_UPSELL_ACKNOWLEDGED = 1 << 9;
+    }
+}
+
+impl<'de> Deserialize<'de> for MemberFlags {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let bits = u64::deserialize(deserializer)?;
+        Ok(Self::from_bits_truncate(bits))
+    }
+}
+
+impl Serialize for MemberFlags {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.bits().serialize(serializer)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// A user's membership of a guild
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/guild#guild-member-object)
+pub struct GuildMember {
+    /// The user this member represents. This is missing from members attached to
+    /// `MESSAGE_CREATE` and `MESSAGE_UPDATE` events.
+    pub user: Option<User>,
+    /// The member's guild nickname
+    pub nick: Option<String>,
+    /// The IDs of the member's roles
+    pub roles: Vec<Snowflake>,
+    /// When the member joined the guild
+    pub joined_at: Option<Timestamp>,
+    /// When the member started boosting the guild
+    pub premium_since: Option<Timestamp>,
+    /// Whether the member is deafened in voice channels
+    #[serde(default)]
+    pub deaf: bool,
+    /// Whether the member is muted in voice channels
+    #[serde(default)]
+    pub mute: bool,
+    /// Whether the member has not yet passed the guild's membership screening
+    pub pending: Option<bool>,
+    /// When the member's timeout will expire, if they are timed out
+    pub communication_disabled_until: Option<Timestamp>,
+    /// The member's flags
+    pub flags: Option<MemberFlags>,
+}
+
+impl GuildMember {
+    /// Whether both members represent the same user, regardless of their other fields.
+    /// This is always `false` if either member is missing its user.
+    #[must_use]
+    pub fn same_member(&self, other: &GuildMember) -> bool {
+        match (&self.user, &other.user) {
+            (Some(user), Some(other)) => user.same_user(other),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{GuildMember, MemberFlags};
+    use crate::gateway::mock::user_payload;
+
+    #[test]
+    fn deserialize_onboarded_member() {
+        let member: GuildMember = serde_json::from_value(json!({
+            "user": user_payload(),
+            "nick": "NOT API SUPPORT",
+            "avatar": null,
+            "roles": [],
+            "joined_at": "2015-04-26T06:26:56.936000+00:00",
+            "deaf": false,
+            "mute": false,
+            "flags": 10,
+        }))
+        .unwrap();
+
+        let flags = member.flags.unwrap();
+        assert!(flags.contains(MemberFlags::COMPLETED_ONBOARDING));
+        assert!(flags.contains(MemberFlags::STARTED_ONBOARDING));
+        assert!(!flags.contains(MemberFlags::DID_REJOIN));
+        assert_eq!(member.nick.as_deref(), Some("NOT API SUPPORT"));
+    }
+
+    #[test]
+    fn deserialize_member_without_nick() {
+        let member: GuildMember = serde_json::from_value(json!({
+            "user": user_payload(),
+            "roles": ["41771983423143936"],
+            "joined_at": "2015-04-26T06:26:56.936000+00:00",
+            "premium_since": null,
+            "deaf": false,
+            "mute": true,
+            "pending": false,
+            "communication_disabled_until": "2015-04-27T06:26:56.936000+00:00",
+        }))
+        .unwrap();
+
+        assert_eq!(member.nick, None);
+        assert!(member.mute);
+        assert_eq!(member.pending, Some(false));
+        assert!(member.communication_disabled_until.is_some());
+
+        let renamed = GuildMember {
+            nick: Some("Nelly".to_string()),
+            ..member.clone()
+        };
+        assert!(member.same_member(&renamed));
+        assert_ne!(member, renamed);
+        let without_user = GuildMember {
+            user: None,
+            ..member.clone()
+        };
+        assert!(!without_user.same_member(&member));
+    }
+}
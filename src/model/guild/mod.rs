@@ -2,14 +2,446 @@
 
 use serde::{Deserialize, Serialize};
 
+use self::{member::GuildMember, role::Role};
+use super::{
+    channel::Channel, gateway::presence::Presence, snowflake::Snowflake, timestamp::Timestamp,
+};
+
+pub mod audit_log;
+pub mod auto_moderation;
+pub mod integration;
+pub mod member;
+pub mod role;
+pub mod scheduled_event;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 /// An unavailable guild is a partial guild object that is considered either:
 /// 1. Offline (due to an outage or other temporary issue); or
 /// 2. Further information will be provided in the future (such as through [`GuildCreate`] events)
 pub struct UnavailableGuild {
     /// The guild ID
-    // TODO: Guild IDs come in as Strings but should be u64s
-    pub id: String,
-    /// Whether the guild is unavailable, this should always be true
+    pub id: Snowflake,
+    /// Whether the guild is unavailable. This is always true, except in `GUILD_DELETE`
+    /// events where it is missing if the client was removed from the guild.
+    #[serde(default)]
     pub unavailable: bool,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+#[non_exhaustive]
+/// The server boost level of a guild
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/guild#guild-object-premium-tier)
+pub enum PremiumTier {
+    /// Guild has not unlocked any Server Boost perks
+    None,
+    /// Guild has unlocked Server Boost level 1 perks
+    Tier1,
+    /// Guild has unlocked Server Boost level 2 perks
+    Tier2,
+    /// Guild has unlocked Server Boost level 3 perks
+    Tier3,
+    /// A premium tier that is not yet documented
+    Unknown(u8),
+}
+
+impl From<PremiumTier> for u8 {
+    fn from(value: PremiumTier) -> Self {
+        match value {
+            PremiumTier::None => 0,
+            PremiumTier::Tier1 => 1,
+            PremiumTier::Tier2 => 2,
+            PremiumTier::Tier3 => 3,
+            PremiumTier::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for PremiumTier {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => PremiumTier::None,
+            1 => PremiumTier::Tier1,
+            2 => PremiumTier::Tier2,
+            3 => PremiumTier::Tier3,
+            _ => PremiumTier::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+#[non_exhaustive]
+/// The messages members are notified of by default
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/guild#guild-object-default-message-notification-level)
+pub enum DefaultMessageNotificationLevel {
+    /// Members are notified of all messages
+    #[default]
+    AllMessages,
+    /// Members are only notified of messages that mention them
+    OnlyMentions,
+    /// A notification level that is not yet documented
+    Unknown(u8),
+}
+
+impl From<DefaultMessageNotificationLevel> for u8 {
+    fn from(value: DefaultMessageNotificationLevel) -> Self {
+        match value {
+            DefaultMessageNotificationLevel::AllMessages => 0,
+            DefaultMessageNotificationLevel::OnlyMentions => 1,
+            DefaultMessageNotificationLevel::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for DefaultMessageNotificationLevel {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => DefaultMessageNotificationLevel::AllMessages,
+            1 => DefaultMessageNotificationLevel::OnlyMentions,
+            _ => DefaultMessageNotificationLevel::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+#[non_exhaustive]
+/// The explicit content filter level of a guild
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/guild#guild-object-explicit-content-filter-level)
+pub enum ExplicitContentFilterLevel {
+    /// Media content is not scanned
+    #[default]
+    Disabled,
+    /// Media content sent by members without roles is scanned
+    MembersWithoutRoles,
+    /// Media content sent by all members is scanned
+    AllMembers,
+    /// An explicit content filter level that is not yet documented
+    Unknown(u8),
+}
+
+impl From<ExplicitContentFilterLevel> for u8 {
+    fn from(value: ExplicitContentFilterLevel) -> Self {
+        match value {
+            ExplicitContentFilterLevel::Disabled => 0,
+            ExplicitContentFilterLevel::MembersWithoutRoles => 1,
+            ExplicitContentFilterLevel::AllMembers => 2,
+            ExplicitContentFilterLevel::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for ExplicitContentFilterLevel {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => ExplicitContentFilterLevel::Disabled,
+            1 => ExplicitContentFilterLevel::MembersWithoutRoles,
+            2 => ExplicitContentFilterLevel::AllMembers,
+            _ => ExplicitContentFilterLevel::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+#[non_exhaustive]
+/// The verification level required to talk in a guild
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/guild#guild-object-verification-level)
+pub enum VerificationLevel {
+    /// Unrestricted
+    #[default]
+    None,
+    /// Members must have a verified email
+    Low,
+    /// Members must also be registered on Discord for longer than 5 minutes
+    Medium,
+    /// Members must also be a member of the guild for longer than 10 minutes
+    High,
+    /// Members must have a verified phone number
+    VeryHigh,
+    /// A verification level that is not yet documented
+    Unknown(u8),
+}
+
+impl From<VerificationLevel> for u8 {
+    fn from(value: VerificationLevel) -> Self {
+        match value {
+            VerificationLevel::None => 0,
+            VerificationLevel::Low => 1,
+            VerificationLevel::Medium => 2,
+            VerificationLevel::High => 3,
+            VerificationLevel::VeryHigh => 4,
+            VerificationLevel::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for VerificationLevel {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => VerificationLevel::None,
+            1 => VerificationLevel::Low,
+            2 => VerificationLevel::Medium,
+            3 => VerificationLevel::High,
+            4 => VerificationLevel::VeryHigh,
+            _ => VerificationLevel::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+#[non_exhaustive]
+/// The two-factor authentication requirement for moderation actions in a guild
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/guild#guild-object-mfa-level)
+pub enum MfaLevel {
+    /// Moderators don't need two-factor authentication
+    #[default]
+    None,
+    /// Moderators need two-factor authentication for moderation actions
+    Elevated,
+    /// An MFA level that is not yet documented
+    Unknown(u8),
+}
+
+impl From<MfaLevel> for u8 {
+    fn from(value: MfaLevel) -> Self {
+        match value {
+            MfaLevel::None => 0,
+            MfaLevel::Elevated => 1,
+            MfaLevel::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for MfaLevel {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => MfaLevel::None,
+            1 => MfaLevel::Elevated,
+            _ => MfaLevel::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// The guild object
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/guild#guild-object)
+pub struct Guild {
+    /// The guild ID
+    pub id: Snowflake,
+    /// The guild name (2-100 characters, excluding trailing and leading whitespace)
+    pub name: String,
+    /// The guild's icon hash
+    // TODO: Investigate whether a ImageHash type should be used here
+    pub icon: Option<String>,
+    /// The ID of the guild's owner
+    pub owner_id: Snowflake,
+    /// The ID of the channel idle members are moved to
+    pub afk_channel_id: Option<Snowflake>,
+    /// The number of seconds after which idle members are moved to the AFK channel
+    #[serde(default)]
+    pub afk_timeout: u32,
+    /// The guild's roles
+    #[serde(default)]
+    pub roles: Vec<Role>,
+    /// The level members need to meet to talk in the guild. The level settings of a
+    /// guild default to their lowest value if they were not sent.
+    #[serde(default)]
+    pub verification_level: VerificationLevel,
+    /// The messages members are notified of by default
+    #[serde(default)]
+    pub default_message_notifications: DefaultMessageNotificationLevel,
+    /// Whose media content is scanned for explicit content
+    #[serde(default)]
+    pub explicit_content_filter: ExplicitContentFilterLevel,
+    /// Whether moderators need two-factor authentication for moderation actions
+    #[serde(default)]
+    pub mfa_level: MfaLevel,
+    /// The server boost level of the guild
+    pub premium_tier: PremiumTier,
+    /// The number of boosts the guild currently has
+    pub premium_subscription_count: Option<u64>,
+    /// The channels in the guild, only sent with `GUILD_CREATE`. These channels do not
+    /// include their `guild_id`.
+    #[serde(default)]
+    pub channels: Vec<Channel>,
+    /// The members of the guild, only sent with `GUILD_CREATE`. Large guilds only
+    /// include the client, members in voice channels and, with the
+    /// `GUILD_PRESENCES` intent, online members.
+    #[serde(default)]
+    pub members: Vec<GuildMember>,
+    /// The presences of the guild's members, only sent with `GUILD_CREATE` and the
+    /// `GUILD_PRESENCES` intent. These presences do not include their `guild_id`.
+    #[serde(default)]
+    pub presences: Vec<Presence>,
+    /// The total number of members in the guild, only sent with `GUILD_CREATE`
+    pub member_count: Option<u64>,
+    /// When the client joined the guild, only sent with `GUILD_CREATE`
+    pub joined_at: Option<Timestamp>,
+    /// Whether the guild is considered large, only sent with `GUILD_CREATE`
+    #[serde(default)]
+    pub large: bool,
+    /// Whether the guild is unavailable due to an outage, only sent with `GUILD_CREATE`
+    pub unavailable: Option<bool>,
+}
+
+impl Guild {
+    /// The number of boosts the guild currently has, or 0 if it was not provided
+    #[must_use]
+    pub fn boost_count(&self) -> u64 {
+        self.premium_subscription_count.unwrap_or(0)
+    }
+
+    /// The channel idle members are moved to, if one is set and the guild's channels were
+    /// sent. The channels of guilds from a [`Cache`](crate::cache::Cache) are cached
+    /// separately, see [`Cache::channel`](crate::cache::Cache::channel).
+    #[must_use]
+    pub fn afk_channel(&self) -> Option<&Channel> {
+        let id = self.afk_channel_id?;
+        self.channels.iter().find(|channel| channel.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{
+        DefaultMessageNotificationLevel, ExplicitContentFilterLevel, Guild, MfaLevel, PremiumTier,
+        VerificationLevel,
+    };
+    use crate::gateway::mock::user_payload;
+    use crate::model::{gateway::dispatch::DispatchEvent, snowflake::Snowflake};
+
+    #[test]
+    fn deserialize_boosted_guild() {
+        let guild: Guild = serde_json::from_value(json!({
+            "id": "197038439483310086",
+            "name": "Discord Testers",
+            "owner_id": "73193882359173120",
+            "premium_tier": 2,
+            "premium_subscription_count": 11,
+        }))
+        .unwrap();
+
+        assert_eq!(guild.premium_tier, PremiumTier::Tier2);
+        assert_eq!(guild.boost_count(), 11);
+    }
+
+    #[test]
+    fn deserialize_guild_create() {
+        let guild_create: DispatchEvent = serde_json::from_value(json!({
+            "t": "GUILD_CREATE",
+            "d": {
+                "id": "197038439483310086",
+                "name": "Discord Testers",
+                "icon": "f64c482b807da4f539cff778d174971c",
+                "owner_id": "73193882359173120",
+                "premium_tier": 3,
+                "premium_subscription_count": 33,
+                "afk_timeout": 300,
+                "roles": [{
+                    "id": "197038439483310086",
+                    "name": "@everyone",
+                    "color": 0,
+                    "hoist": false,
+                    "position": 0,
+                    "permissions": "104324673",
+                    "managed": false,
+                    "mentionable": false,
+                }],
+                "channels": [{ "id": "197038439483310087", "type": 0, "name": "general" }],
+                "members": [{
+                    "user": user_payload(),
+                    "roles": [],
+                    "joined_at": "2016-06-22T19:20:40.123000+00:00",
+                    "deaf": false,
+                    "mute": false,
+                }],
+                "member_count": 256_000,
+                "joined_at": "2016-06-22T19:20:40.123000+00:00",
+                "large": true,
+                "unavailable": false,
+                "voice_states": [],
+                "presences": [],
+                "threads": [],
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::GuildCreate(guild_create) = guild_create else {
+            panic!("expected a GUILD_CREATE event");
+        };
+        let guild = guild_create.0;
+        assert_eq!(guild.owner_id, Snowflake(73_193_882_359_173_120));
+        assert_eq!(guild.roles[0].name, "@everyone");
+        assert_eq!(guild.channels.len(), 1);
+        assert_eq!(guild.members.len(), 1);
+        assert_eq!(guild.member_count, Some(256_000));
+        assert!(guild.large);
+        assert_eq!(guild.unavailable, Some(false));
+    }
+
+    #[test]
+    fn deserialize_afk_channel() {
+        let guild: Guild = serde_json::from_value(json!({
+            "id": "197038439483310086",
+            "name": "Discord Testers",
+            "owner_id": "73193882359173120",
+            "premium_tier": 0,
+            "afk_channel_id": "197038439483310088",
+            "afk_timeout": 900,
+            "channels": [
+                { "id": "197038439483310087", "type": 0, "name": "general" },
+                { "id": "197038439483310088", "type": 2, "name": "AFK" },
+            ],
+        }))
+        .unwrap();
+
+        assert_eq!(guild.afk_timeout, 900);
+        let afk_channel = guild.afk_channel().unwrap();
+        assert_eq!(afk_channel.id, Snowflake(197_038_439_483_310_088));
+        assert_eq!(afk_channel.name.as_deref(), Some("AFK"));
+    }
+
+    #[test]
+    fn deserialize_moderation_settings() {
+        let guild: Guild = serde_json::from_value(json!({
+            "id": "197038439483310086",
+            "name": "Discord Testers",
+            "owner_id": "73193882359173120",
+            "premium_tier": 0,
+            "verification_level": 4,
+            "default_message_notifications": 1,
+            "explicit_content_filter": 2,
+            "mfa_level": 1,
+        }))
+        .unwrap();
+
+        assert_eq!(guild.verification_level, VerificationLevel::VeryHigh);
+        assert_eq!(
+            guild.default_message_notifications,
+            DefaultMessageNotificationLevel::OnlyMentions
+        );
+        assert_eq!(
+            guild.explicit_content_filter,
+            ExplicitContentFilterLevel::AllMembers
+        );
+        assert_eq!(guild.mfa_level, MfaLevel::Elevated);
+        assert_eq!(VerificationLevel::from(9), VerificationLevel::Unknown(9));
+    }
+
+    #[test]
+    fn unknown_premium_tier() {
+        assert_eq!(PremiumTier::from(7), PremiumTier::Unknown(7));
+        assert_eq!(u8::from(PremiumTier::Unknown(7)), 7);
+    }
+}
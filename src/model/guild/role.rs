@@ -0,0 +1,123 @@
+//! The role module contains all the role-related structs and enums.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::model::{permissions::Permissions, snowflake::Snowflake};
+
+/// Some role tags are `null` when set and missing otherwise.
+fn deserialize_present<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<()>::deserialize(deserializer).map(|_| true)
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn serialize_present<S>(_: &bool, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_none()
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+/// The tags of a role, describing what the role belongs to
+///
+/// [Discord documentation](https://discord.com/developers/docs/topics/permissions#role-object-role-tags-structure)
+pub struct RoleTags {
+    /// The ID of the bot this role belongs to
+    pub bot_id: Option<Snowflake>,
+    /// The ID of the integration this role belongs to
+    pub integration_id: Option<Snowflake>,
+    /// Whether this is the guild's Booster role
+    #[serde(
+        default,
+        deserialize_with = "deserialize_present",
+        serialize_with = "serialize_present",
+        skip_serializing_if = "is_false"
+    )]
+    pub premium_subscriber: bool,
+    /// The ID of this role's subscription SKU and listing
+    pub subscription_listing_id: Option<Snowflake>,
+    /// Whether this role is available for purchase
+    #[serde(
+        default,
+        deserialize_with = "deserialize_present",
+        serialize_with = "serialize_present",
+        skip_serializing_if = "is_false"
+    )]
+    pub available_for_purchase: bool,
+    /// Whether this role is a guild's linked role
+    #[serde(
+        default,
+        deserialize_with = "deserialize_present",
+        serialize_with = "serialize_present",
+        skip_serializing_if = "is_false"
+    )]
+    pub guild_connections: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// A role, which grants permissions to the members it is assigned to
+///
+/// [Discord documentation](https://discord.com/developers/docs/topics/permissions#role-object)
+pub struct Role {
+    /// The role ID
+    pub id: Snowflake,
+    /// The role name
+    pub name: String,
+    /// The integer representation of the role's hexadecimal color code, or 0 for no color
+    pub color: u32,
+    /// Whether the role is shown separately in the member list
+    pub hoist: bool,
+    /// The position of the role in the guild's role list
+    pub position: i32,
+    /// The permissions granted by the role
+    pub permissions: Permissions,
+    /// Whether the role is managed by an integration
+    pub managed: bool,
+    /// Whether the role can be mentioned by everyone
+    pub mentionable: bool,
+    /// The tags of the role
+    pub tags: Option<RoleTags>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::Role;
+    use crate::model::{permissions::Permissions, snowflake::Snowflake};
+
+    #[test]
+    fn deserialize_role() {
+        let role: Role = serde_json::from_value(json!({
+            "id": "41771983423143936",
+            "name": "WE DEM BOYZZ!!!!!!",
+            "color": 3_447_003,
+            "hoist": true,
+            "icon": "cf3ced8600b777c9486c8d0e6b4e2fbf",
+            "unicode_emoji": null,
+            "position": 1,
+            "permissions": "66321471",
+            "managed": false,
+            "mentionable": false,
+            "flags": 0,
+            "tags": { "bot_id": "41771983423143937", "premium_subscriber": null },
+        }))
+        .unwrap();
+
+        assert_eq!(role.color, 3_447_003);
+        assert_eq!(role.permissions.bits(), 66_321_471);
+        assert!(role.permissions.contains(Permissions::ADMINISTRATOR));
+        let tags = role.tags.unwrap();
+        assert_eq!(tags.bot_id, Some(Snowflake(41_771_983_423_143_937)));
+        assert!(tags.premium_subscriber);
+        assert!(!tags.available_for_purchase);
+    }
+}
@@ -0,0 +1,117 @@
+//! The scheduled event module contains the events scheduled in guilds, such as stage
+//! sessions or meetups.
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{snowflake::Snowflake, timestamp::Timestamp};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+#[non_exhaustive]
+/// The status of a scheduled event
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/guild-scheduled-event#guild-scheduled-event-object-guild-scheduled-event-status)
+pub enum ScheduledEventStatus {
+    /// The event hasn't started yet
+    Scheduled,
+    /// The event is in progress
+    Active,
+    /// The event has ended
+    Completed,
+    /// The event was canceled before it started
+    Canceled,
+    /// A status that is not yet documented
+    Unknown(u8),
+}
+
+impl From<ScheduledEventStatus> for u8 {
+    fn from(value: ScheduledEventStatus) -> Self {
+        match value {
+            ScheduledEventStatus::Scheduled => 1,
+            ScheduledEventStatus::Active => 2,
+            ScheduledEventStatus::Completed => 3,
+            ScheduledEventStatus::Canceled => 4,
+            ScheduledEventStatus::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for ScheduledEventStatus {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => ScheduledEventStatus::Scheduled,
+            2 => ScheduledEventStatus::Active,
+            3 => ScheduledEventStatus::Completed,
+            4 => ScheduledEventStatus::Canceled,
+            _ => ScheduledEventStatus::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+#[non_exhaustive]
+/// Where a scheduled event takes place
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/guild-scheduled-event#guild-scheduled-event-object-guild-scheduled-event-entity-types)
+pub enum ScheduledEventEntityType {
+    /// In a stage channel
+    StageInstance,
+    /// In a voice channel
+    Voice,
+    /// Somewhere outside of Discord
+    External,
+    /// An entity type that is not yet documented
+    Unknown(u8),
+}
+
+impl From<ScheduledEventEntityType> for u8 {
+    fn from(value: ScheduledEventEntityType) -> Self {
+        match value {
+            ScheduledEventEntityType::StageInstance => 1,
+            ScheduledEventEntityType::Voice => 2,
+            ScheduledEventEntityType::External => 3,
+            ScheduledEventEntityType::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for ScheduledEventEntityType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => ScheduledEventEntityType::StageInstance,
+            2 => ScheduledEventEntityType::Voice,
+            3 => ScheduledEventEntityType::External,
+            _ => ScheduledEventEntityType::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// An event scheduled in a guild
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/guild-scheduled-event#guild-scheduled-event-object)
+pub struct ScheduledEvent {
+    /// The scheduled event ID
+    pub id: Snowflake,
+    /// The ID of the guild
+    pub guild_id: Snowflake,
+    /// The ID of the channel the event takes place in, or `None` for external events
+    pub channel_id: Option<Snowflake>,
+    /// The ID of the user who created the event
+    pub creator_id: Option<Snowflake>,
+    /// The name of the event
+    pub name: String,
+    /// The description of the event
+    pub description: Option<String>,
+    /// When the event starts
+    pub scheduled_start_time: Timestamp,
+    /// When the event ends, which is always set for external events
+    pub scheduled_end_time: Option<Timestamp>,
+    /// The status of the event
+    pub status: ScheduledEventStatus,
+    /// Where the event takes place
+    pub entity_type: ScheduledEventEntityType,
+    /// The number of users subscribed to the event
+    pub user_count: Option<u32>,
+}
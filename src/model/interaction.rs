@@ -0,0 +1,146 @@
+//! The interaction module contains all the interaction-related structs and enums.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{guild::member::GuildMember, snowflake::Snowflake, user::User};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+#[non_exhaustive]
+/// The type of an interaction
+///
+/// [Discord documentation](https://discord.com/developers/docs/interactions/receiving-and-responding#interaction-object-interaction-type)
+pub enum InteractionType {
+    /// Sent to check the interactions endpoint, never through the gateway
+    Ping,
+    /// A slash, user or message command was used
+    ApplicationCommand,
+    /// A component, such as a button, was used
+    MessageComponent,
+    /// A command option is being autocompleted
+    ApplicationCommandAutocomplete,
+    /// A modal was submitted
+    ModalSubmit,
+    /// An interaction type that is not yet documented
+    Unknown(u8),
+}
+
+impl From<InteractionType> for u8 {
+    fn from(value: InteractionType) -> Self {
+        match value {
+            InteractionType::Ping => 1,
+            InteractionType::ApplicationCommand => 2,
+            InteractionType::MessageComponent => 3,
+            InteractionType::ApplicationCommandAutocomplete => 4,
+            InteractionType::ModalSubmit => 5,
+            InteractionType::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for InteractionType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => InteractionType::Ping,
+            2 => InteractionType::ApplicationCommand,
+            3 => InteractionType::MessageComponent,
+            4 => InteractionType::ApplicationCommandAutocomplete,
+            5 => InteractionType::ModalSubmit,
+            _ => InteractionType::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// A user using a command, component or modal of the application
+///
+/// [Discord documentation](https://discord.com/developers/docs/interactions/receiving-and-responding#interaction-object)
+pub struct Interaction {
+    /// The interaction ID
+    pub id: Snowflake,
+    /// The ID of the application the interaction is for
+    pub application_id: Snowflake,
+    /// The type of the interaction
+    #[serde(rename = "type")]
+    pub kind: InteractionType,
+    /// The token used to respond to the interaction, valid for 15 minutes
+    pub token: String,
+    /// The ID of the guild the interaction was sent from
+    pub guild_id: Option<Snowflake>,
+    /// The ID of the channel the interaction was sent from
+    pub channel_id: Option<Snowflake>,
+    /// The member who invoked the interaction, if it was sent from a guild
+    pub member: Option<GuildMember>,
+    /// The user who invoked the interaction, if it was sent from a direct message
+    pub user: Option<User>,
+    /// The data of the interaction, which depends on its type
+    // TODO: Model the data of each interaction type
+    pub data: Option<Value>,
+}
+
+impl Interaction {
+    /// The user who invoked the interaction, whether it was sent from a guild or not
+    #[must_use]
+    pub fn invoker(&self) -> Option<&User> {
+        self.member
+            .as_ref()
+            .and_then(|member| member.user.as_ref())
+            .or(self.user.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::InteractionType;
+    use crate::model::{gateway::dispatch::DispatchEvent, snowflake::Snowflake};
+
+    #[test]
+    fn deserialize_slash_command() {
+        let interaction_create: DispatchEvent = serde_json::from_value(json!({
+            "t": "INTERACTION_CREATE",
+            "d": {
+                "id": "786008729715212338",
+                "application_id": "775799577604522054",
+                "type": 2,
+                "token": "A_UNIQUE_TOKEN",
+                "version": 1,
+                "guild_id": "290926798626357999",
+                "channel_id": "645027906669510667",
+                "member": {
+                    "user": {
+                        "id": "53908232506183680",
+                        "username": "Mason",
+                        "discriminator": "0",
+                        "global_name": "Mason",
+                        "avatar": null,
+                    },
+                    "roles": ["539082325061836999"],
+                    "joined_at": "2017-03-13T19:19:14.040000+00:00",
+                    "deaf": false,
+                    "mute": false,
+                },
+                "data": {
+                    "id": "771825006014889984",
+                    "name": "blep",
+                    "type": 1,
+                    "options": [{ "type": 3, "name": "animal", "value": "animal_dog" }],
+                },
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::InteractionCreate(interaction) = interaction_create else {
+            panic!("expected an INTERACTION_CREATE event");
+        };
+        assert_eq!(interaction.kind, InteractionType::ApplicationCommand);
+        assert_eq!(
+            interaction.guild_id,
+            Some(Snowflake(290_926_798_626_357_999))
+        );
+        assert_eq!(interaction.invoker().unwrap().username, "Mason");
+        assert_eq!(interaction.data.unwrap()["name"], "blep");
+    }
+}
@@ -1,6 +1,15 @@
 //! The model module contains all the structs, enums and types used by the library
 //! to represent the data received from the Discord API.
 
+pub mod channel;
+pub mod emoji;
 pub mod gateway;
 pub mod guild;
+pub mod interaction;
+pub mod permissions;
+pub mod snowflake;
+pub mod soundboard;
+pub mod sticker;
+pub mod timestamp;
 pub mod user;
+pub mod voice;
@@ -0,0 +1,103 @@
+//! Snowflakes are the unique IDs Discord gives to users, guilds, channels and more.
+
+use std::{fmt::Display, num::ParseIntError, str::FromStr};
+
+use serde::{de::Visitor, Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A unique ID, sent by Discord as a string to avoid integer overflows in some languages
+///
+/// [Discord documentation](https://discord.com/developers/docs/reference#snowflakes)
+pub struct Snowflake(pub u64);
+
+impl From<u64> for Snowflake {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Snowflake> for u64 {
+    fn from(value: Snowflake) -> Self {
+        value.0
+    }
+}
+
+impl FromStr for Snowflake {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl Display for Snowflake {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Serialize for Snowflake {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(&self.0)
+    }
+}
+
+struct SnowflakeVisitor;
+
+impl Visitor<'_> for SnowflakeVisitor {
+    type Value = Snowflake;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a snowflake as a string or integer")
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Snowflake(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        v.parse().map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Snowflake {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(SnowflakeVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::Snowflake;
+
+    #[test]
+    fn snowflake_round_trips_as_string() {
+        let snowflake: Snowflake = serde_json::from_value(json!("175928847299117063")).unwrap();
+        assert_eq!(snowflake, Snowflake(175_928_847_299_117_063));
+        assert_eq!(
+            serde_json::to_value(snowflake).unwrap(),
+            json!("175928847299117063")
+        );
+    }
+
+    #[test]
+    fn snowflake_from_integer() {
+        let snowflake: Snowflake =
+            serde_json::from_value(json!(41_771_983_423_143_937_u64)).unwrap();
+        assert_eq!(snowflake, Snowflake(41_771_983_423_143_937));
+    }
+}
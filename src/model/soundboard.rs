@@ -0,0 +1,116 @@
+//! The soundboard module contains all the soundboard-related structs and enums.
+
+use serde::{Deserialize, Serialize};
+
+use super::{snowflake::Snowflake, user::User};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// A sound that can be played in voice channels
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/soundboard#soundboard-sound-object)
+pub struct SoundboardSound {
+    /// The name of the sound
+    pub name: String,
+    /// The ID of the sound
+    pub sound_id: Snowflake,
+    /// The volume of the sound, from 0 to 1
+    pub volume: f64,
+    /// The ID of the sound's custom emoji
+    pub emoji_id: Option<Snowflake>,
+    /// The unicode character of the sound's standard emoji
+    pub emoji_name: Option<String>,
+    /// The ID of the guild the sound is in, if it is not a default sound
+    pub guild_id: Option<Snowflake>,
+    /// Whether the sound can be used, may be false due to loss of Server Boosts
+    pub available: bool,
+    /// The user who created the sound
+    pub user: Option<User>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::SoundboardSound;
+    use crate::gateway::mock::user_payload;
+    use crate::model::{gateway::dispatch::DispatchEvent, snowflake::Snowflake};
+
+    #[test]
+    fn soundboard_sounds_round_trip() {
+        let sound = json!({
+            "name": "quack",
+            "sound_id": "1106714396018884649",
+            "volume": 1.0,
+            "emoji_id": null,
+            "emoji_name": "🦆",
+            "guild_id": "613425648685547541",
+            "available": true,
+            "user": null,
+        });
+        let event: DispatchEvent = serde_json::from_value(json!({
+            "t": "SOUNDBOARD_SOUNDS",
+            "d": {
+                "soundboard_sounds": [sound],
+                "guild_id": "613425648685547541",
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::SoundboardSounds(event) = event else {
+            panic!("expected a SOUNDBOARD_SOUNDS event");
+        };
+        assert_eq!(event.guild_id, Snowflake(613_425_648_685_547_541));
+        assert_eq!(event.soundboard_sounds[0].name, "quack");
+        assert_eq!(
+            serde_json::to_value(&event.soundboard_sounds[0]).unwrap(),
+            sound
+        );
+        assert_eq!(
+            serde_json::from_value::<SoundboardSound>(sound.clone()).unwrap(),
+            event.soundboard_sounds[0]
+        );
+    }
+
+    #[test]
+    fn deserialize_soundboard_sound_create() {
+        let event: DispatchEvent = serde_json::from_value(json!({
+            "t": "GUILD_SOUNDBOARD_SOUND_CREATE",
+            "d": {
+                "name": "Yay",
+                "sound_id": "1106714396018884649",
+                "volume": 0.5,
+                "emoji_id": "1106714396018884650",
+                "emoji_name": null,
+                "guild_id": "613425648685547541",
+                "available": true,
+                "user": user_payload(),
+            },
+        }))
+        .unwrap();
+
+        let DispatchEvent::GuildSoundboardSoundCreate(sound) = event else {
+            panic!("expected a GUILD_SOUNDBOARD_SOUND_CREATE event");
+        };
+        assert_eq!(sound.name, "Yay");
+        assert!((sound.volume - 0.5).abs() < f64::EPSILON);
+        assert_eq!(sound.emoji_id, Some(Snowflake(1_106_714_396_018_884_650)));
+        assert_eq!(sound.user.unwrap().username, "Nelly");
+    }
+
+    #[test]
+    fn deserialize_soundboard_sound_delete() {
+        let event: DispatchEvent = serde_json::from_value(json!({
+            "t": "GUILD_SOUNDBOARD_SOUND_DELETE",
+            "d": { "sound_id": "1106714396018884649", "guild_id": "613425648685547541" },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            event,
+            DispatchEvent::GuildSoundboardSoundDelete {
+                sound_id: Snowflake(1_106_714_396_018_884_649),
+                guild_id: Snowflake(613_425_648_685_547_541),
+            }
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! The sticker module contains all the sticker-related structs and enums.
+
+use serde::{Deserialize, Serialize};
+
+use super::{snowflake::Snowflake, user::User};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+/// The type of a sticker
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/sticker#sticker-object-sticker-types)
+pub enum StickerType {
+    /// An official sticker in a pack
+    Standard,
+    /// A sticker uploaded to a guild
+    Guild,
+    /// A sticker type that is not yet documented
+    Unknown(u8),
+}
+
+impl From<StickerType> for u8 {
+    fn from(value: StickerType) -> Self {
+        match value {
+            StickerType::Standard => 1,
+            StickerType::Guild => 2,
+            StickerType::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for StickerType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => StickerType::Standard,
+            2 => StickerType::Guild,
+            _ => StickerType::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "u8", from = "u8")]
+/// The file format of a sticker
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/sticker#sticker-object-sticker-format-types)
+pub enum StickerFormatType {
+    Png,
+    Apng,
+    Lottie,
+    Gif,
+    /// A sticker format that is not yet documented
+    Unknown(u8),
+}
+
+impl From<StickerFormatType> for u8 {
+    fn from(value: StickerFormatType) -> Self {
+        match value {
+            StickerFormatType::Png => 1,
+            StickerFormatType::Apng => 2,
+            StickerFormatType::Lottie => 3,
+            StickerFormatType::Gif => 4,
+            StickerFormatType::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for StickerFormatType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => StickerFormatType::Png,
+            2 => StickerFormatType::Apng,
+            3 => StickerFormatType::Lottie,
+            4 => StickerFormatType::Gif,
+            _ => StickerFormatType::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// The smallest amount of data needed to render a sticker, as sent with messages
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/sticker#sticker-item-object)
+pub struct StickerItem {
+    /// The sticker ID
+    pub id: Snowflake,
+    /// The name of the sticker
+    pub name: String,
+    /// The file format of the sticker
+    pub format_type: StickerFormatType,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// A sticker that can be sent in messages
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/sticker#sticker-object)
+pub struct Sticker {
+    /// The sticker ID
+    pub id: Snowflake,
+    /// The ID of the pack the sticker is in, if it is a standard sticker
+    pub pack_id: Option<Snowflake>,
+    /// The name of the sticker
+    pub name: String,
+    /// The description of the sticker
+    pub description: Option<String>,
+    /// Autocomplete and suggestion tags for the sticker, separated by commas
+    pub tags: String,
+    /// The type of the sticker
+    #[serde(rename = "type")]
+    pub kind: StickerType,
+    /// The file format of the sticker
+    pub format_type: StickerFormatType,
+    /// Whether the guild sticker can be used, may be false due to loss of Server Boosts
+    pub available: Option<bool>,
+    /// The ID of the guild the sticker is in
+    pub guild_id: Option<Snowflake>,
+    /// The user who uploaded the guild sticker
+    pub user: Option<User>,
+    /// The sticker's sort order within its pack
+    pub sort_value: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Sticker, StickerFormatType, StickerType};
+
+    #[test]
+    fn deserialize_guild_sticker() {
+        let sticker: Sticker = serde_json::from_value(json!({
+            "id": "749054660769218631",
+            "name": "Wave",
+            "tags": "wumpus, hello, sup, hi, oi, heyo, heya, yo, wave",
+            "type": 2,
+            "format_type": 3,
+            "description": "Wumpus waves hello",
+            "available": true,
+            "guild_id": "613425648685547541",
+        }))
+        .unwrap();
+
+        assert_eq!(sticker.kind, StickerType::Guild);
+        assert_eq!(sticker.format_type, StickerFormatType::Lottie);
+        assert_eq!(sticker.pack_id, None);
+    }
+}
@@ -0,0 +1,384 @@
+//! Timestamps are points in time sent by Discord, such as when a message was sent.
+
+use std::{fmt::Display, str::FromStr};
+
+use serde::{de::Visitor, Deserialize, Serialize};
+
+#[cfg(not(any(feature = "chrono", feature = "time")))]
+compile_error!("either the `chrono` or the `time` feature must be enabled");
+
+#[cfg(feature = "chrono")]
+type DateTime = chrono::DateTime<chrono::Utc>;
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+type DateTime = time::OffsetDateTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A point in time in UTC. Discord sends these as RFC 3339 strings such as
+/// `2017-07-11T17:27:07.299000+00:00`, which is also how they are displayed and serialized.
+///
+/// A timestamp wraps `chrono::DateTime<Utc>` with the `chrono` feature, which is enabled
+/// by default, or `time::OffsetDateTime` with only the `time` feature, and converts to
+/// and from either.
+pub struct Timestamp(DateTime);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The error returned when a string is not an RFC 3339 timestamp
+pub struct TimestampParseError;
+
+impl Display for TimestampParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid RFC 3339 timestamp")
+    }
+}
+
+impl std::error::Error for TimestampParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// How Discord displays a timestamp inserted into a message, in the reader's locale and
+/// timezone
+///
+/// [Discord documentation](https://discord.com/developers/docs/reference#message-formatting-timestamp-styles)
+pub enum TimestampStyle {
+    /// For example, `16:20`
+    ShortTime,
+    /// For example, `16:20:30`
+    LongTime,
+    /// For example, `20/04/2021`
+    ShortDate,
+    /// For example, `20 April 2021`
+    LongDate,
+    /// For example, `20 April 2021 16:20`
+    #[default]
+    ShortDateTime,
+    /// For example, `Tuesday, 20 April 2021 16:20`
+    LongDateTime,
+    /// For example, `2 months ago`
+    Relative,
+}
+
+impl TimestampStyle {
+    /// The flag used for this style in the timestamp markdown
+    #[must_use]
+    pub fn flag(&self) -> char {
+        match self {
+            TimestampStyle::ShortTime => 't',
+            TimestampStyle::LongTime => 'T',
+            TimestampStyle::ShortDate => 'd',
+            TimestampStyle::LongDate => 'D',
+            TimestampStyle::ShortDateTime => 'f',
+            TimestampStyle::LongDateTime => 'F',
+            TimestampStyle::Relative => 'R',
+        }
+    }
+}
+
+impl Timestamp {
+    /// Creates a timestamp from the number of seconds since the Unix epoch.
+    #[must_use]
+    pub fn from_unix(seconds: i64) -> Self {
+        Self::from_unix_millis(seconds.saturating_mul(1000))
+    }
+
+    /// Creates a timestamp from the number of milliseconds since the Unix epoch, saturating
+    /// at the earliest or latest date the wrapped type supports.
+    #[cfg(feature = "chrono")]
+    #[must_use]
+    pub fn from_unix_millis(unix_millis: i64) -> Self {
+        Self(
+            chrono::DateTime::from_timestamp_millis(unix_millis).unwrap_or(if unix_millis < 0 {
+                DateTime::MIN_UTC
+            } else {
+                DateTime::MAX_UTC
+            }),
+        )
+    }
+
+    /// Creates a timestamp from the number of milliseconds since the Unix epoch, saturating
+    /// at the earliest or latest date the wrapped type supports.
+    #[cfg(not(feature = "chrono"))]
+    #[must_use]
+    pub fn from_unix_millis(unix_millis: i64) -> Self {
+        Self(offset_date_time_from_unix_millis(unix_millis))
+    }
+
+    /// The number of whole seconds since the Unix epoch
+    #[must_use]
+    pub fn unix(&self) -> i64 {
+        self.unix_millis().div_euclid(1000)
+    }
+
+    /// The number of milliseconds since the Unix epoch
+    #[cfg(feature = "chrono")]
+    #[must_use]
+    pub fn unix_millis(&self) -> i64 {
+        self.0.timestamp_millis()
+    }
+
+    /// The number of milliseconds since the Unix epoch
+    #[cfg(not(feature = "chrono"))]
+    #[must_use]
+    pub fn unix_millis(&self) -> i64 {
+        offset_date_time_unix_millis(self.0)
+    }
+
+    /// The markdown that displays this timestamp in a message, such as `<t:1618953630:R>`.
+    ///
+    /// [Discord documentation](https://discord.com/developers/docs/reference#message-formatting)
+    #[must_use]
+    pub fn discord_format(&self, style: TimestampStyle) -> String {
+        format!("<t:{}:{}>", self.unix(), style.flag())
+    }
+}
+
+#[cfg(feature = "time")]
+fn offset_date_time_from_unix_millis(unix_millis: i64) -> time::OffsetDateTime {
+    let nanos = i128::from(unix_millis) * 1_000_000;
+    time::OffsetDateTime::from_unix_timestamp_nanos(nanos).unwrap_or_else(|_| {
+        if unix_millis < 0 {
+            time::PrimitiveDateTime::MIN.assume_utc()
+        } else {
+            time::PrimitiveDateTime::MAX.assume_utc()
+        }
+    })
+}
+
+#[cfg(feature = "time")]
+fn offset_date_time_unix_millis(date_time: time::OffsetDateTime) -> i64 {
+    date_time.unix_timestamp_nanos().div_euclid(1_000_000) as i64
+}
+
+impl FromStr for Timestamp {
+    type Err = TimestampParseError;
+
+    /// Parses an RFC 3339 timestamp, such as `2017-07-11T17:27:07.299000+00:00` or
+    /// `2017-07-11T17:27:07Z`.
+    #[cfg(feature = "chrono")]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        chrono::DateTime::parse_from_rfc3339(s)
+            .map(|date_time| Self(date_time.with_timezone(&chrono::Utc)))
+            .map_err(|_| TimestampParseError)
+    }
+
+    /// Parses an RFC 3339 timestamp, such as `2017-07-11T17:27:07.299000+00:00` or
+    /// `2017-07-11T17:27:07Z`.
+    #[cfg(not(feature = "chrono"))]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        time::OffsetDateTime::parse(s, &time::format_description::well_known::Rfc3339)
+            .map(|date_time| Self(date_time.to_offset(time::UtcOffset::UTC)))
+            .map_err(|_| TimestampParseError)
+    }
+}
+
+impl Display for Timestamp {
+    /// Formats the timestamp as RFC 3339 in UTC, the same way Discord does, such as
+    /// `2017-07-11T17:27:07.299000+00:00`.
+    #[cfg(feature = "chrono")]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.to_rfc3339_opts(chrono::SecondsFormat::Micros, false))
+    }
+
+    /// Formats the timestamp as RFC 3339 in UTC, the same way Discord does, such as
+    /// `2017-07-11T17:27:07.299000+00:00`.
+    #[cfg(not(feature = "chrono"))]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}+00:00",
+            self.0.year(),
+            u8::from(self.0.month()),
+            self.0.day(),
+            self.0.hour(),
+            self.0.minute(),
+            self.0.second(),
+            self.0.microsecond()
+        )
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+struct TimestampVisitor;
+
+impl Visitor<'_> for TimestampVisitor {
+    type Value = Timestamp;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an RFC 3339 timestamp")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        v.parse().map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(TimestampVisitor)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::DateTime<chrono::Utc>> for Timestamp {
+    fn from(date_time: chrono::DateTime<chrono::Utc>) -> Self {
+        Self(date_time)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Timestamp> for chrono::DateTime<chrono::Utc> {
+    fn from(timestamp: Timestamp) -> Self {
+        timestamp.0
+    }
+}
+
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+impl From<time::OffsetDateTime> for Timestamp {
+    fn from(date_time: time::OffsetDateTime) -> Self {
+        Self(date_time.to_offset(time::UtcOffset::UTC))
+    }
+}
+
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+impl From<Timestamp> for time::OffsetDateTime {
+    fn from(timestamp: Timestamp) -> Self {
+        timestamp.0
+    }
+}
+
+#[cfg(all(feature = "time", feature = "chrono"))]
+impl From<time::OffsetDateTime> for Timestamp {
+    /// Converts the date time to the wrapped `chrono` type, keeping millisecond precision.
+    fn from(date_time: time::OffsetDateTime) -> Self {
+        Self::from_unix_millis(offset_date_time_unix_millis(date_time))
+    }
+}
+
+#[cfg(all(feature = "time", feature = "chrono"))]
+impl From<Timestamp> for time::OffsetDateTime {
+    /// Converts the timestamp with millisecond precision, saturating at the earliest or
+    /// latest date time supports.
+    fn from(timestamp: Timestamp) -> Self {
+        offset_date_time_from_unix_millis(timestamp.unix_millis())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Timestamp, TimestampStyle};
+
+    #[test]
+    fn relative_format() {
+        let timestamp = Timestamp::from_unix(1_618_953_630);
+        assert_eq!(
+            timestamp.discord_format(TimestampStyle::Relative),
+            "<t:1618953630:R>"
+        );
+    }
+
+    #[test]
+    fn full_format_truncates_milliseconds() {
+        let timestamp = Timestamp::from_unix_millis(1_618_953_630_999);
+        assert_eq!(
+            timestamp.discord_format(TimestampStyle::LongDateTime),
+            "<t:1618953630:F>"
+        );
+    }
+
+    #[test]
+    fn parse_joined_at() {
+        let joined_at: Timestamp =
+            serde_json::from_value(json!("2015-04-26T06:26:56.936000+00:00")).unwrap();
+        assert_eq!(joined_at.unix_millis(), 1_430_029_616_936);
+        assert_eq!(
+            serde_json::to_value(joined_at).unwrap(),
+            json!("2015-04-26T06:26:56.936000+00:00")
+        );
+    }
+
+    #[test]
+    fn parse_null_as_none() {
+        let premium_since: Option<Timestamp> = serde_json::from_value(json!(null)).unwrap();
+        assert_eq!(premium_since, None);
+    }
+
+    #[test]
+    fn parse_offsets_and_precision() {
+        let utc: Timestamp = "2021-04-20T21:20:30Z".parse().unwrap();
+        assert_eq!(utc, Timestamp::from_unix(1_618_953_630));
+        let offset: Timestamp = "2021-04-20T23:50:30.5+02:30".parse().unwrap();
+        assert_eq!(offset.unix_millis(), 1_618_953_630_500);
+        let before_epoch: Timestamp = "1969-12-31T23:59:59.999+00:00".parse().unwrap();
+        assert_eq!(before_epoch.unix_millis(), -1);
+        assert_eq!(before_epoch.to_string(), "1969-12-31T23:59:59.999000+00:00");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn converts_to_and_from_chrono() {
+        use chrono::{DateTime, TimeZone, Utc};
+
+        let joined_at: Timestamp = "2015-04-26T06:26:56.936000+00:00".parse().unwrap();
+        let date_time = DateTime::<Utc>::from(joined_at);
+        assert_eq!(
+            date_time,
+            Utc.with_ymd_and_hms(2015, 4, 26, 6, 26, 56).unwrap()
+                + chrono::Duration::milliseconds(936)
+        );
+        assert_eq!(Timestamp::from(date_time), joined_at);
+        assert_eq!(
+            DateTime::<Utc>::from(Timestamp::from_unix_millis(i64::MAX)),
+            DateTime::<Utc>::MAX_UTC
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn converts_to_and_from_time() {
+        use time::{Date, Month, OffsetDateTime, PrimitiveDateTime};
+
+        let before_epoch: Timestamp = "1969-12-31T23:59:59.999+00:00".parse().unwrap();
+        let date_time = OffsetDateTime::from(before_epoch);
+        let expected = Date::from_calendar_date(1969, Month::December, 31)
+            .unwrap()
+            .with_hms_milli(23, 59, 59, 999)
+            .unwrap()
+            .assume_utc();
+        assert_eq!(date_time, expected);
+        assert_eq!(Timestamp::from(date_time), before_epoch);
+        assert_eq!(
+            OffsetDateTime::from(Timestamp::from_unix_millis(i64::MIN)),
+            PrimitiveDateTime::MIN.assume_utc()
+        );
+    }
+
+    #[test]
+    fn reject_invalid_timestamps() {
+        for invalid in [
+            "",
+            "2021-04-20",
+            "2021-04-20T21:20:30",
+            "2021-02-29T00:00:00Z",
+            "2021-04-20T24:00:00Z",
+            "2021-04-20T21:20:30.Z",
+            "2021-04-20T21:20:30+0200",
+            "2021-04-20T21:20:30Zjunk",
+        ] {
+            assert!(invalid.parse::<Timestamp>().is_err(), "{invalid}");
+        }
+    }
+}
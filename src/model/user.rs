@@ -3,6 +3,8 @@
 use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
 
+use super::snowflake::Snowflake;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 /// The avatar decoration of a user
 ///
@@ -75,7 +77,6 @@ impl Serialize for UserFlags {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[repr(u8)]
 #[serde(into = "u8", from = "u8")]
 #[non_exhaustive]
 /// The subscription type of a user
@@ -83,18 +84,26 @@ impl Serialize for UserFlags {
 /// [Discord documentation](https://discord.com/developers/docs/resources/user#user-object-premium-types)
 pub enum PremiumType {
     /// User is not subscribed to Nitro
-    None = 0,
+    None,
     /// User is a Nitro Classic subscriber
-    NitroClassic = 1,
+    NitroClassic,
     /// User is a Nitro subscriber
-    Nitro = 2,
+    Nitro,
     /// User is a Nitro Basic subscriber
-    NitroBasic = 3,
+    NitroBasic,
+    /// A subscription type that is not yet documented
+    Unknown(u8),
 }
 
 impl From<PremiumType> for u8 {
     fn from(value: PremiumType) -> Self {
-        value as u8
+        match value {
+            PremiumType::None => 0,
+            PremiumType::NitroClassic => 1,
+            PremiumType::Nitro => 2,
+            PremiumType::NitroBasic => 3,
+            PremiumType::Unknown(value) => value,
+        }
     }
 }
 
@@ -105,7 +114,7 @@ impl From<u8> for PremiumType {
             1 => PremiumType::NitroClassic,
             2 => PremiumType::Nitro,
             3 => PremiumType::NitroBasic,
-            _ => panic!("Invalid PremiumType value: {value}"),
+            _ => PremiumType::Unknown(value),
         }
     }
 }
@@ -116,8 +125,7 @@ impl From<u8> for PremiumType {
 /// [Discord documentation](https://discord.com/developers/docs/resources/user#user-object)
 pub struct User {
     /// The user's ID
-    // TODO: User IDs come in as Strings but should be u64s
-    pub id: String,
+    pub id: Snowflake,
     /// The user's username, not unique across the platform (although this is becoming
     /// increasingly uncommon due to the discontinuation of the discriminator field)
     pub username: String,
@@ -163,3 +171,112 @@ pub struct User {
     /// The user's avatar decoration data
     pub avatar_decoration_data: Option<AvatarDecoration>,
 }
+
+impl User {
+    /// Whether the user belongs to an `OAuth2` application
+    #[must_use]
+    pub fn is_bot(&self) -> bool {
+        self.bot.unwrap_or(false)
+    }
+
+    /// Whether the user is an Official Discord System user
+    #[must_use]
+    pub fn is_system(&self) -> bool {
+        self.system.unwrap_or(false)
+    }
+
+    /// Whether both users are the same user, regardless of whether their other fields differ,
+    /// such as when comparing a cached user with an updated one
+    #[must_use]
+    pub fn same_user(&self, other: &User) -> bool {
+        self.id == other.id
+    }
+
+    /// Whether the user is neither a bot nor a system user
+    #[must_use]
+    pub fn is_human(&self) -> bool {
+        !self.is_bot() && !self.is_system()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// A user of which only the ID is guaranteed to be sent, such as in presence updates
+pub struct PartialUser {
+    /// The user's ID
+    pub id: Snowflake,
+    /// The user's username
+    pub username: Option<String>,
+    /// The user's display name
+    pub global_name: Option<String>,
+    /// The user's [avatar hash](https://discord.com/developers/docs/reference#image-formatting)
+    pub avatar: Option<String>,
+    /// Whether the user belongs to an `OAuth2` application
+    pub bot: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PremiumType, User};
+    use crate::gateway::mock::user_payload;
+    use crate::model::snowflake::Snowflake;
+
+    fn user(extra: &serde_json::Value) -> User {
+        let mut value = user_payload();
+        value
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn bot_user() {
+        let user = user(&serde_json::json!({ "bot": true }));
+        assert!(user.is_bot());
+        assert!(!user.is_system());
+        assert!(!user.is_human());
+    }
+
+    #[test]
+    fn system_user() {
+        let user = user(&serde_json::json!({ "system": true }));
+        assert!(!user.is_bot());
+        assert!(user.is_system());
+        assert!(!user.is_human());
+    }
+
+    #[test]
+    fn unknown_premium_type_round_trips() {
+        let unknown = user(&serde_json::json!({ "premium_type": 4 }));
+        assert_eq!(unknown.premium_type, Some(PremiumType::Unknown(4)));
+        assert_eq!(serde_json::to_value(&unknown).unwrap()["premium_type"], 4);
+
+        let nitro = user(&serde_json::json!({ "premium_type": 2 }));
+        assert_eq!(nitro.premium_type, Some(PremiumType::Nitro));
+    }
+
+    #[test]
+    fn regular_user() {
+        let user = user(&serde_json::json!({ "bot": false }));
+        assert!(!user.is_bot());
+        assert!(!user.is_system());
+        assert!(user.is_human());
+    }
+
+    #[test]
+    fn same_user_compares_ids() {
+        let user = user(&serde_json::json!({}));
+        let renamed = User {
+            global_name: Some("Nelly 2".to_string()),
+            ..user.clone()
+        };
+        let other = User {
+            id: Snowflake(53_908_232_506_183_680),
+            ..user.clone()
+        };
+
+        assert!(user.same_user(&renamed));
+        assert_ne!(user, renamed);
+        assert!(!user.same_user(&other));
+    }
+}
@@ -0,0 +1,47 @@
+//! The voice module contains the voice state of users connected to voice channels.
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{guild::member::GuildMember, snowflake::Snowflake, timestamp::Timestamp};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
+/// A user's connection to a voice channel
+///
+/// [Discord documentation](https://discord.com/developers/docs/resources/voice#voice-state-object)
+pub struct VoiceState {
+    /// The ID of the guild, if the voice channel is in one
+    pub guild_id: Option<Snowflake>,
+    /// The ID of the voice channel the user is connected to, or `None` if they left voice
+    pub channel_id: Option<Snowflake>,
+    /// The ID of the user
+    pub user_id: Snowflake,
+    /// The member the voice state is for, if the voice channel is in a guild
+    pub member: Option<GuildMember>,
+    /// The ID of the user's voice session
+    pub session_id: String,
+    /// Whether the user is deafened by the guild
+    pub deaf: bool,
+    /// Whether the user is muted by the guild
+    pub mute: bool,
+    /// Whether the user deafened themselves
+    pub self_deaf: bool,
+    /// Whether the user muted themselves
+    pub self_mute: bool,
+    /// Whether the user is streaming with Go Live
+    pub self_stream: Option<bool>,
+    /// Whether the user's camera is enabled
+    pub self_video: bool,
+    /// Whether the user can't speak in a stage channel
+    pub suppress: bool,
+    /// When the user requested to speak in a stage channel, if they have
+    pub request_to_speak_timestamp: Option<Timestamp>,
+}
+
+impl VoiceState {
+    /// Whether the user is connected to a voice channel, rather than having left voice
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.channel_id.is_some()
+    }
+}